@@ -44,11 +44,11 @@ export const ConfigPanel = () => {
           <strong>{formatTokenAmount(faucetCap)} tokens</strong>
         </div>
         <div className="stack stack-sm">
-          <span className="text-muted">Proof reward</span>
+          <span className="text-muted">Default task reward</span>
           <strong>{formatTokenAmount(powReward)} tokens</strong>
         </div>
         <div className="stack stack-sm">
-          <span className="text-muted">Difficulty target</span>
+          <span className="text-muted">Default task difficulty</span>
           <strong>{powDifficulty} leading zero bits</strong>
         </div>
         <div className="stack stack-sm">
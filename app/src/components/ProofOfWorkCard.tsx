@@ -1,8 +1,9 @@
 import { FormEvent, useState } from 'react'
 import { useRecoilValue } from 'recoil'
-import { powDifficultyState, powRewardState } from '../state/atoms'
-import { useProofOfWork } from '../hooks/useProofOfWork'
+import { powHashAlgorithmState, proofHistoryState } from '../state/atoms'
+import { POW_HASH_NAMES, useProofOfWork } from '../hooks/useProofOfWork'
 import { useRecordProof } from '../hooks/useRecordProof'
+import { useTask } from '../hooks/useTask'
 import { useToast } from '../hooks/useToast'
 
 interface ProofOfWorkCardProps {
@@ -13,13 +14,14 @@ const formatTokenAmount = (amount: number) =>
   Number.isFinite(amount) ? amount.toLocaleString(undefined, { maximumFractionDigits: 4 }) : '0'
 
 export const ProofOfWorkCard = ({ onSubmitted }: ProofOfWorkCardProps) => {
-  const powDifficulty = useRecoilValue(powDifficultyState)
-  const powReward = useRecoilValue(powRewardState)
-  const { solve, verifyNonce, isSolving } = useProofOfWork()
+  const proofHistory = useRecoilValue(proofHistoryState)
+  const powHashAlgorithm = useRecoilValue(powHashAlgorithmState)
+  const [taskId, setTaskId] = useState('')
+  const { task, isLoading: isLoadingTask } = useTask(taskId)
+  const { solve, verifyNonce, isSolving } = useProofOfWork(task?.difficulty ?? 0, powHashAlgorithm)
   const { submitProof, isSubmitting } = useRecordProof({ onComplete: onSubmitted })
   const { pushToast } = useToast()
 
-  const [taskId, setTaskId] = useState(() => Math.floor(Date.now() / 1000).toString())
   const [nonce, setNonce] = useState('')
   const [hash, setHash] = useState<string | null>(null)
   const [iterations, setIterations] = useState<string | null>(null)
@@ -27,8 +29,12 @@ export const ProofOfWorkCard = ({ onSubmitted }: ProofOfWorkCardProps) => {
   const [abortController, setAbortController] = useState<AbortController | null>(null)
 
   const handleSolve = async () => {
-    if (!taskId.trim()) {
-      pushToast({ title: 'Task ID required', description: 'Provide a numeric task identifier.', variant: 'error' })
+    if (!task) {
+      pushToast({
+        title: 'Task not registered',
+        description: 'Enter the id of a task published by the oracle.',
+        variant: 'error',
+      })
       return
     }
 
@@ -86,6 +92,15 @@ export const ProofOfWorkCard = ({ onSubmitted }: ProofOfWorkCardProps) => {
       return
     }
 
+    if (!task) {
+      pushToast({
+        title: 'Task not registered',
+        description: 'Enter the id of a task published by the oracle.',
+        variant: 'error',
+      })
+      return
+    }
+
     const verification = verifyNonce(taskId, nonce)
     if (!verification.valid) {
       pushToast({
@@ -105,13 +120,23 @@ export const ProofOfWorkCard = ({ onSubmitted }: ProofOfWorkCardProps) => {
       <div className="stack stack-sm">
         <div className="section-title">Proof of work</div>
         <p className="text-muted">
-          Submit a task-specific proof to earn an additional reward. Difficulty is expressed in leading zero bits for
-          the Keccak hash of (domain | wallet | mint | taskId | nonce).
+          Solve a task published by the oracle to earn its reward. Difficulty is expressed in leading zero bits for
+          the {POW_HASH_NAMES[powHashAlgorithm] ?? POW_HASH_NAMES[0]} hash of (domain | wallet | mint | taskId |
+          nonce).
         </p>
-        <div className="hstack hstack-wrap" style={{ gap: '1.5rem' }}>
-          <span className="pill">Difficulty: {powDifficulty} bits</span>
-          <span className="pill text-accent">Reward: {formatTokenAmount(powReward)} tokens</span>
-        </div>
+        {task ? (
+          <div className="hstack hstack-wrap" style={{ gap: '1.5rem' }}>
+            <span className="pill">Difficulty: {task.difficulty} bits</span>
+            <span className="pill text-accent">Reward: {formatTokenAmount(task.reward)} tokens</span>
+            <span className="pill">
+              Solvers: {task.solvers}
+              {task.maxSolvers > 0 ? ` / ${task.maxSolvers}` : ''}
+            </span>
+            <span className="pill">Opens: {new Date(task.opensAt * 1000).toLocaleString()}</span>
+          </div>
+        ) : (
+          <span className="text-muted">{isLoadingTask ? 'Loading task...' : 'No registered task with this id.'}</span>
+        )}
       </div>
 
       <form className="stack stack-sm" onSubmit={handleSubmit}>
@@ -126,13 +151,6 @@ export const ProofOfWorkCard = ({ onSubmitted }: ProofOfWorkCardProps) => {
             onChange={(event) => setTaskId(event.target.value)}
             style={{ flex: 1 }}
           />
-          <button
-            className="btn btn-outline"
-            type="button"
-            onClick={() => setTaskId(Math.floor(Date.now() / 1000).toString())}
-          >
-            New task
-          </button>
         </div>
 
         <label htmlFor="pow-nonce">Nonce</label>
@@ -172,6 +190,20 @@ export const ProofOfWorkCard = ({ onSubmitted }: ProofOfWorkCardProps) => {
           ) : null}
         </div>
       ) : null}
+
+      {proofHistory.length > 0 ? (
+        <div className="stack stack-sm">
+          <span className="text-muted">Recent proofs</span>
+          {proofHistory.map((item) => (
+            <div key={item.taskId} className="hstack hstack-wrap" style={{ gap: '1.5rem' }}>
+              <span>Task {item.taskId}</span>
+              <span className="text-muted">Nonce {item.nonce}</span>
+              <span className="text-accent">+{formatTokenAmount(item.reward)} tokens</span>
+              <span className="text-muted">{new Date(item.timestamp * 1000).toLocaleString()}</span>
+            </div>
+          ))}
+        </div>
+      ) : null}
     </section>
   )
 }
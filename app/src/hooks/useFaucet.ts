@@ -5,7 +5,14 @@ import {
   createAssociatedTokenAccountInstruction,
   getAssociatedTokenAddressSync,
 } from '@solana/spl-token'
-import { PublicKey, SYSVAR_RENT_PUBKEY, SystemProgram, LAMPORTS_PER_SOL, SendTransactionError } from '@solana/web3.js'
+import {
+  PublicKey,
+  SYSVAR_INSTRUCTIONS_PUBKEY,
+  SYSVAR_RENT_PUBKEY,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+  SendTransactionError,
+} from '@solana/web3.js'
 import { useSkillStakeProgram, BN } from './useSkillStakeProgram'
 import { useSkillStakeWallet } from './useSkillStakeWallet'
 import { getFaucetAmount, getMintPublicKey, getTokenDecimals } from '../config/appConfig'
@@ -16,6 +23,7 @@ import { seed } from '../utils/seeds'
 const STATE_SEED = seed('state')
 const MINT_AUTH_SEED = seed('mint_auth')
 const STAKE_SEED = seed('stake')
+const ORACLE_SET_SEED = seed('oracle_set')
 
 export const useFaucet = (options: { onComplete?: () => Promise<void> | void } = {}) => {
   const program = useSkillStakeProgram()
@@ -75,6 +83,7 @@ export const useFaucet = (options: { onComplete?: () => Promise<void> | void } =
         }
 
         const [statePda] = PublicKey.findProgramAddressSync([STATE_SEED], program.programId)
+        const [oracleSetPda] = PublicKey.findProgramAddressSync([ORACLE_SET_SEED], program.programId)
         const [mintAuthPda] = PublicKey.findProgramAddressSync([MINT_AUTH_SEED], program.programId)
         const [stakePda] = PublicKey.findProgramAddressSync([STAKE_SEED, publicKey.toBuffer()], program.programId)
         const userToken = getAssociatedTokenAddressSync(
@@ -100,8 +109,9 @@ export const useFaucet = (options: { onComplete?: () => Promise<void> | void } =
           )
         }
 
+        // Ungated pools only; PoW and oracle gates need a proof from the CLI or the oracle.
         await program.methods
-          .faucet(baseUnits)
+          .faucet(baseUnits, { none: {} })
           .accounts({
             user: publicKey,
             state: statePda,
@@ -112,6 +122,8 @@ export const useFaucet = (options: { onComplete?: () => Promise<void> | void } =
             stakeAccount: stakePda,
             systemProgram: SystemProgram.programId,
             rent: SYSVAR_RENT_PUBKEY,
+            oracleSet: oracleSetPda,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
           })
           .preInstructions(preInstructions)
           .rpc()
@@ -1,7 +1,7 @@
 import { useCallback, useMemo, useState } from 'react'
 import { keccak_256 } from 'js-sha3'
-import { useRecoilValue } from 'recoil'
-import { powDifficultyState } from '../state/atoms'
+import { blake3 } from '@noble/hashes/blake3'
+import { sha256 } from '@noble/hashes/sha256'
 import { useSkillStakeWallet } from './useSkillStakeWallet'
 import { getMintPublicKey } from '../config/appConfig'
 import { parseU64, MAX_U64_BIGINT } from '../utils/u64'
@@ -51,19 +51,41 @@ const meetsDifficulty = (hash: Uint8Array, difficulty: number) => {
   return false
 }
 
-const hashPow = (user: Uint8Array, mint: Uint8Array, taskId: bigint, nonce: bigint) => {
-  const payload = concatBytes([POW_DOMAIN_BYTES, user, mint, toU64LeBytes(taskId), toU64LeBytes(nonce)])
-  return new Uint8Array(keccak_256.arrayBuffer(payload))
+// `GlobalState::pow_hash_algorithm` values
+export const POW_HASH_KECCAK256 = 0
+export const POW_HASH_SHA256 = 1
+export const POW_HASH_BLAKE3 = 2
+
+export const POW_HASH_NAMES: Record<number, string> = {
+  [POW_HASH_KECCAK256]: 'Keccak-256',
+  [POW_HASH_SHA256]: 'SHA-256',
+  [POW_HASH_BLAKE3]: 'BLAKE3',
 }
 
+// Mirrors `pow_hashv`; unknown values fall back to keccak256. Checked against the vectors in
+// programs/skill_stake/tests/vectors.rs.
+const hashWith = (algorithm: number, payload: Uint8Array) => {
+  switch (algorithm) {
+    case POW_HASH_SHA256:
+      return sha256(payload)
+    case POW_HASH_BLAKE3:
+      return blake3(payload)
+    default:
+      return new Uint8Array(keccak_256.arrayBuffer(payload))
+  }
+}
+
+const hashPow = (algorithm: number, user: Uint8Array, mint: Uint8Array, taskId: bigint, nonce: bigint) =>
+  hashWith(algorithm, concatBytes([POW_DOMAIN_BYTES, user, mint, toU64LeBytes(taskId), toU64LeBytes(nonce)]))
+
 interface SolveOptions {
   signal?: AbortSignal
   startingNonce?: string
 }
 
-export const useProofOfWork = () => {
+// `powDifficulty` is the difficulty of the task being solved; `algorithm` the pool's hash.
+export const useProofOfWork = (powDifficulty: number, algorithm: number = POW_HASH_KECCAK256) => {
   const { publicKey, connectWallet, connected } = useSkillStakeWallet()
-  const powDifficulty = useRecoilValue(powDifficultyState)
   const [isSolving, setIsSolving] = useState(false)
 
   const mintBytes = useMemo(() => getMintPublicKey().toBytes(), [])
@@ -78,14 +100,14 @@ export const useProofOfWork = () => {
       try {
         const taskId = parseU64(taskIdInput)
         const nonce = parseU64(nonceInput)
-        const hashBytes = hashPow(userBytes, mintBytes, taskId, nonce)
+        const hashBytes = hashPow(algorithm, userBytes, mintBytes, taskId, nonce)
         const valid = meetsDifficulty(hashBytes, powDifficulty)
         return { valid, hashHex: bytesToHex(hashBytes) }
       } catch {
         return { valid: false, hashHex: null }
       }
     },
-    [publicKey, connected, userBytes, mintBytes, powDifficulty],
+    [publicKey, connected, userBytes, mintBytes, powDifficulty, algorithm],
   )
 
   const solve = useCallback(
@@ -108,7 +130,7 @@ export const useProofOfWork = () => {
             throw new Error('Proof generation cancelled')
           }
 
-          const hashBytes = hashPow(userBytes, mintBytes, taskId, nonce)
+          const hashBytes = hashPow(algorithm, userBytes, mintBytes, taskId, nonce)
           iterations += 1n
 
           if (meetsDifficulty(hashBytes, powDifficulty)) {
@@ -131,7 +153,7 @@ export const useProofOfWork = () => {
         setIsSolving(false)
       }
     },
-    [publicKey, userBytes, mintBytes, powDifficulty, connectWallet],
+    [publicKey, userBytes, mintBytes, powDifficulty, algorithm, connectWallet],
   )
 
   return { solve, verifyNonce, isSolving, powDifficulty }
@@ -1,14 +1,17 @@
 import { useCallback, useState } from 'react'
 import { BN } from '@coral-xyz/anchor'
-import { PublicKey } from '@solana/web3.js'
+import { PublicKey, SYSVAR_INSTRUCTIONS_PUBKEY, SystemProgram } from '@solana/web3.js'
 import { useToast } from './useToast'
 import { useSkillStakeProgram } from './useSkillStakeProgram'
 import { useSkillStakeWallet } from './useSkillStakeWallet'
 import { parseU64 } from '../utils/u64'
 import { seed } from '../utils/seeds'
+import { taskAddress } from './useTask'
 
 const STATE_SEED = seed('state')
 const STAKE_SEED = seed('stake')
+const PROOF_HISTORY_SEED = seed('proof_history')
+const ORACLE_SET_SEED = seed('oracle_set')
 
 interface UseRecordProofOptions {
   onComplete?: () => Promise<void> | void
@@ -64,14 +67,24 @@ export const useRecordProof = (options: UseRecordProofOptions = {}) => {
       setIsSubmitting(true)
       try {
         const [statePda] = PublicKey.findProgramAddressSync([STATE_SEED], program.programId)
+        const [oracleSetPda] = PublicKey.findProgramAddressSync([ORACLE_SET_SEED], program.programId)
         const [stakePda] = PublicKey.findProgramAddressSync([STAKE_SEED, publicKey.toBuffer()], program.programId)
+        const [historyPda] = PublicKey.findProgramAddressSync(
+          [PROOF_HISTORY_SEED, publicKey.toBuffer()],
+          program.programId,
+        )
 
         await program.methods
-          .recordProof(new BN(taskIdBig.toString()), new BN(nonceBig.toString()))
+          .recordProof(new BN(taskIdBig.toString()), new BN(nonceBig.toString()), null)
           .accounts({
             user: publicKey,
             state: statePda,
             stakeAccount: stakePda,
+            task: taskAddress(program.programId, taskIdBig),
+            proofHistory: historyPda,
+            systemProgram: SystemProgram.programId,
+            oracleSet: oracleSetPda,
+            instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
           })
           .rpc()
 
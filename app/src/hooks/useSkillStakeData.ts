@@ -10,7 +10,10 @@ import {
   oracleNonceState,
   pendingRewardsState,
   powDifficultyState,
+  powHashAlgorithmState,
   powRewardState,
+  proofHistoryState,
+  type ProofHistoryItem,
   tokenBalanceState,
   totalStakedState,
 } from '../state/atoms'
@@ -23,6 +26,19 @@ import { seed } from '../utils/seeds'
 
 const STATE_SEED = seed('state')
 const STAKE_SEED = seed('stake')
+const PROOF_HISTORY_SEED = seed('proof_history')
+
+// The account is a ring buffer: once full, `head` is the oldest entry.
+const toProofHistory = (account: any, decimals: number): ProofHistoryItem[] => {
+  const entries: any[] = account.entries ?? []
+  const head = Number(account.head ?? 0)
+  return [...entries.slice(head), ...entries.slice(0, head)].reverse().map((entry) => ({
+    taskId: entry.record.taskId.toString(),
+    nonce: entry.record.nonce.toString(),
+    reward: fromBaseUnits(BigInt(entry.reward.toString()), decimals),
+    timestamp: Number(entry.timestamp),
+  }))
+}
 
 export const useSkillStakeData = () => {
   const program = useSkillStakeProgram()
@@ -36,9 +52,11 @@ export const useSkillStakeData = () => {
   const setFaucetCap = useSetRecoilState(faucetCapState)
   const setPowDifficulty = useSetRecoilState(powDifficultyState)
   const setPowReward = useSetRecoilState(powRewardState)
+  const setPowHashAlgorithm = useSetRecoilState(powHashAlgorithmState)
   const setOracleAuthority = useSetRecoilState(oracleAuthorityState)
   const setOracleNonce = useSetRecoilState(oracleNonceState)
   const setFaucetClaimed = useSetRecoilState(faucetClaimedState)
+  const setProofHistory = useSetRecoilState(proofHistoryState)
   const [isRefreshing, setIsRefreshing] = useState(false)
   const warnedMissingAtaRef = useRef(false)
 
@@ -60,6 +78,7 @@ export const useSkillStakeData = () => {
         setFaucetCap(fromBaseUnits(BigInt(stateAccount.faucetCap.toString()), decimals))
         setPowDifficulty(Number(stateAccount.powDifficulty ?? 0))
         setPowReward(fromBaseUnits(BigInt(stateAccount.powReward.toString()), decimals))
+        setPowHashAlgorithm(Number(stateAccount.powHashAlgorithm ?? 0))
         setOracleAuthority(
           stateAccount.oracleAuthority ? new PublicKey(stateAccount.oracleAuthority).toBase58() : null,
         )
@@ -70,6 +89,7 @@ export const useSkillStakeData = () => {
         setFaucetCap(0)
         setPowDifficulty(0)
         setPowReward(0)
+        setPowHashAlgorithm(0)
         setOracleAuthority(null)
         setOracleNonce(0)
         setFaucetClaimed(0)
@@ -107,10 +127,18 @@ export const useSkillStakeData = () => {
           setPendingRewards(0)
           setFaucetClaimed(0)
         }
+
+        const [historyPda] = PublicKey.findProgramAddressSync(
+          [PROOF_HISTORY_SEED, publicKey.toBuffer()],
+          program.programId,
+        )
+        const history = await (program.account as any).proofHistory.fetchNullable(historyPda)
+        setProofHistory(history ? toProofHistory(history, decimals) : [])
       } else {
         setTokenBalance(0)
         setPendingRewards(0)
         setFaucetClaimed(0)
+        setProofHistory([])
       }
     } catch (error) {
       const message = error instanceof Error ? error.message : 'Unknown error'
@@ -0,0 +1,92 @@
+import { useEffect, useMemo, useState } from 'react'
+import { PublicKey } from '@solana/web3.js'
+import { useRecoilValue } from 'recoil'
+import { powDifficultyState, powRewardState } from '../state/atoms'
+import { useSkillStakeProgram } from './useSkillStakeProgram'
+import { getTokenDecimals } from '../config/appConfig'
+import { fromBaseUnits } from '../utils/token'
+import { parseU64 } from '../utils/u64'
+import { seed } from '../utils/seeds'
+
+const TASK_SEED = seed('task')
+
+export interface TaskInfo {
+  reward: number
+  difficulty: number
+  opensAt: number
+  closesAt: number
+  // 0 means unlimited
+  maxSolvers: number
+  solvers: number
+}
+
+export const taskAddress = (programId: PublicKey, taskId: bigint) => {
+  const taskIdBytes = new Uint8Array(8)
+  new DataView(taskIdBytes.buffer).setBigUint64(0, taskId, true)
+  return PublicKey.findProgramAddressSync([TASK_SEED, taskIdBytes], programId)[0]
+}
+
+// Loads the oracle-registered task; null while the id is invalid or unregistered. A stored reward
+// or difficulty of 0 follows the pool's current values.
+export const useTask = (taskIdInput: string) => {
+  const program = useSkillStakeProgram()
+  const powDifficulty = useRecoilValue(powDifficultyState)
+  const powReward = useRecoilValue(powRewardState)
+  const [task, setTask] = useState<TaskInfo | null>(null)
+  const [isLoading, setIsLoading] = useState(false)
+
+  useEffect(() => {
+    let taskId: bigint
+    try {
+      taskId = parseU64(taskIdInput)
+    } catch {
+      setTask(null)
+      return
+    }
+    if (!program) {
+      setTask(null)
+      return
+    }
+
+    let cancelled = false
+    setIsLoading(true)
+    ;(program.account as any).task
+      .fetchNullable(taskAddress(program.programId, taskId))
+      .then((account: any) => {
+        if (cancelled) return
+        setTask(
+          account
+            ? {
+                reward: fromBaseUnits(BigInt(account.reward.toString()), getTokenDecimals()),
+                difficulty: Number(account.difficulty),
+                opensAt: Number(account.opensAt),
+                closesAt: Number(account.closesAt),
+                maxSolvers: Number(account.maxSolvers),
+                solvers: Number(account.solvers),
+              }
+            : null,
+        )
+      })
+      .catch(() => {
+        if (!cancelled) setTask(null)
+      })
+      .finally(() => {
+        if (!cancelled) setIsLoading(false)
+      })
+    return () => {
+      cancelled = true
+    }
+  }, [program, taskIdInput])
+
+  const resolved = useMemo(
+    () =>
+      task && {
+        ...task,
+        reward: task.reward || powReward,
+        difficulty: task.difficulty || powDifficulty,
+      },
+    [task, powDifficulty, powReward],
+  )
+
+  return { task: resolved, isLoading }
+}
@@ -55,6 +55,12 @@ export const powDifficultyState = atom<number>({
   default: 0,
 })
 
+// Mirrors `GlobalState::pow_hash_algorithm`: 0 keccak256, 1 sha256, 2 blake3.
+export const powHashAlgorithmState = atom<number>({
+  key: 'powHashAlgorithmState',
+  default: 0,
+})
+
 export const powRewardState = atom<number>({
   key: 'powRewardState',
   default: 0,
@@ -74,3 +80,16 @@ export const faucetClaimedState = atom<number>({
   key: 'faucetClaimedState',
   default: 0,
 })
+
+export interface ProofHistoryItem {
+  taskId: string
+  nonce: string
+  reward: number
+  timestamp: number
+}
+
+// Most recent first.
+export const proofHistoryState = atom<ProofHistoryItem[]>({
+  key: 'proofHistoryState',
+  default: [],
+})
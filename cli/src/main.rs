@@ -0,0 +1,999 @@
+//! `skill-stake`: operator CLI for the skill_stake program.
+//!
+//! Replaces `scripts/devnet_init.ts`, `set_mint_authority.ts` and `airdrop_faucet.ts` for
+//! day-to-day operations. Every transaction command accepts `--dry-run` (simulate only) and `--json`.
+
+mod output;
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde_json::json;
+use skill_stake_client::{
+    instructions, pda, pow_config_message, relayed_pow_config_message, task_message, GlobalState,
+    OracleSet, PowRetarget, ProgramAccount, ProofHistory, ProofStakePolicy, SkillStakeError,
+    StakeAccount, TaskParams, POW_HASH_BLAKE3, POW_HASH_KECCAK256, POW_HASH_SHA256,
+};
+#[cfg(feature = "devnet")]
+use skill_stake_client::{
+    faucet_attestation_message, faucet_pow_hash, meets_difficulty, FaucetProof, FAUCET_GATE_NONE,
+    FAUCET_GATE_ORACLE, FAUCET_GATE_POW,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::RpcSimulateTransactionConfig;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::hash::{hash, Hash};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use spl_token_2022::instruction::AuthorityType;
+
+use crate::output::Output;
+
+#[derive(Parser)]
+#[command(
+    name = "skill-stake",
+    version,
+    about = "Operate the skill_stake program"
+)]
+struct Cli {
+    /// RPC endpoint
+    #[arg(
+        long,
+        short = 'u',
+        env = "SOLANA_RPC_URL",
+        default_value = "https://api.devnet.solana.com",
+        global = true
+    )]
+    url: String,
+    /// Keypair that signs and pays for transactions
+    #[arg(long, short = 'k', env = "SKILL_STAKE_KEYPAIR", global = true)]
+    keypair: Option<PathBuf>,
+    /// Simulate the transaction instead of sending it
+    #[arg(long, global = true)]
+    dry_run: bool,
+    /// Print machine-readable JSON
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create the state, vault and roles accounts
+    Init {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long, default_value_t = 1_000)]
+        apr_bps: u64,
+        /// Per-wallet lifetime faucet cap, in raw units
+        #[arg(long, default_value_t = 100_000_000_000)]
+        faucet_cap: u64,
+        /// Initial lifetime faucet budget across all wallets, in raw units; 0 leaves the faucet closed
+        #[cfg(feature = "devnet")]
+        #[arg(long, default_value_t = 1_000_000_000_000_000)]
+        faucet_budget: u64,
+        #[arg(long, default_value_t = 0)]
+        pow_reward: u64,
+        #[arg(long, default_value_t = 18)]
+        pow_difficulty: u8,
+        /// Defaults to the admin keypair
+        #[arg(long)]
+        oracle_authority: Option<Pubkey>,
+    },
+    /// Hand the mint authority to the program's mint_auth PDA (current mint authority)
+    SetMintAuthority {
+        /// Defaults to the pool mint
+        #[arg(long)]
+        mint: Option<Pubkey>,
+    },
+    /// Set the staking APR in basis points (APR manager)
+    SetApr {
+        apr_bps: u64,
+    },
+    #[cfg(feature = "devnet")]
+    /// Set the per-wallet faucet cap (faucet manager)
+    UpdateFaucetCap {
+        faucet_cap: u64,
+    },
+    #[cfg(feature = "devnet")]
+    /// Add to the lifetime faucet budget (treasury manager)
+    TopUpFaucetBudget {
+        amount: u64,
+    },
+    #[cfg(feature = "devnet")]
+    /// Set faucet rate limits (faucet manager); 0 disables a limit
+    SetFaucetLimits {
+        /// Seconds a wallet must wait between claims
+        #[arg(long, default_value_t = 0)]
+        cooldown: i64,
+        /// Rolling window length in seconds, set together with --window-cap
+        #[arg(long, default_value_t = 0)]
+        window: i64,
+        /// Per-wallet amount per window, in raw units
+        #[arg(long, default_value_t = 0)]
+        window_cap: u64,
+        /// Pool-wide amount per UTC day, in raw units
+        #[arg(long, default_value_t = 0)]
+        daily_budget: u64,
+    },
+    #[cfg(feature = "devnet")]
+    /// Choose what faucet claims must carry (faucet manager)
+    SetFaucetGate {
+        #[arg(value_enum)]
+        mode: FaucetGate,
+        /// Leading zero bits required by the `pow` gate
+        #[arg(long, default_value_t = 0)]
+        pow_difficulty: u8,
+    },
+    /// Replace the oracle authority (admin)
+    SetOracleAuthority {
+        authority: Pubkey,
+    },
+    /// Replace the oracle authority with an M-of-N oracle set (admin)
+    InitOracleSet {
+        /// Repeat once per member
+        #[arg(long = "member", required = true)]
+        members: Vec<Pubkey>,
+        /// Distinct member signatures each oracle message needs
+        #[arg(long)]
+        threshold: u8,
+    },
+    /// Add a member to the oracle set (admin)
+    AddOracleMember {
+        member: Pubkey,
+    },
+    /// Remove a member from the oracle set (admin)
+    RemoveOracleMember {
+        member: Pubkey,
+    },
+    /// Set how many members must sign each oracle message (admin)
+    SetOracleThreshold {
+        threshold: u8,
+    },
+    /// Register a task for `record_proof` (PoW manager, or the oracle authority while no oracle set
+    /// exists); with `--oracle-signature`, any keypair relays a task the oracle signed
+    CreateTask {
+        task_id: u64,
+        #[command(flatten)]
+        task: TaskArgs,
+        /// `ORACLE=SIGNATURE` from `sign-task`; under an oracle set, once per signing member. The
+        /// signer then only relays the task; pass the `--opens-at` that `sign-task` printed
+        #[arg(long = "oracle-signature")]
+        oracle_signatures: Vec<OracleSignature>,
+    },
+    /// Sign a task for `create-task --oracle-signature` without sending anything
+    SignTask {
+        task_id: u64,
+        #[command(flatten)]
+        task: TaskArgs,
+    },
+    /// Update PoW difficulty and reward; the oracle nonce defaults to the on-chain nonce + 1
+    SetPowConfig {
+        #[arg(long)]
+        difficulty: u8,
+        #[arg(long)]
+        reward: u64,
+        #[arg(long)]
+        nonce: Option<u64>,
+        /// `MEMBER=SIGNATURE` over the PoW config message, once per signing oracle set member;
+        /// the signer then only relays the update
+        #[arg(long = "oracle-signature")]
+        oracle_signatures: Vec<OracleSignature>,
+    },
+    /// Sign a PoW config update for `relay-pow-config` without sending anything; the oracle nonce
+    /// defaults to the on-chain nonce + 1
+    SignPowConfig {
+        #[arg(long)]
+        difficulty: u8,
+        #[arg(long)]
+        reward: u64,
+        #[arg(long)]
+        nonce: Option<u64>,
+        /// Seconds the signature stays valid
+        #[arg(long, default_value_t = 300)]
+        ttl: i64,
+    },
+    /// Submit a PoW config update the oracle signed off-chain; any keypair can relay it
+    RelayPowConfig {
+        #[arg(long)]
+        difficulty: u8,
+        #[arg(long)]
+        reward: u64,
+        #[arg(long)]
+        nonce: u64,
+        /// Unix seconds, as printed by `sign-pow-config`
+        #[arg(long)]
+        expiry: i64,
+        /// `ORACLE=SIGNATURE` from `sign-pow-config`; under an oracle set, once per signing member
+        #[arg(long = "oracle-signature", required = true)]
+        oracle_signatures: Vec<OracleSignature>,
+    },
+    /// Retarget PoW difficulty automatically (PoW manager); --window 0 turns it off
+    SetPowRetarget {
+        /// Measurement window in seconds
+        #[arg(long)]
+        window: i64,
+        /// Proofs the pool aims to accept per window
+        #[arg(long, default_value_t = 0)]
+        target_proofs: u64,
+        /// Most bits the difficulty moves per retarget
+        #[arg(long, default_value_t = 1)]
+        max_step: u8,
+        #[arg(long, default_value_t = 0)]
+        min_difficulty: u8,
+        #[arg(long, default_value_t = 32)]
+        max_difficulty: u8,
+    },
+    /// Require an oracle attestation on every recorded proof (PoW manager)
+    SetProofAttestation {
+        #[arg(action = clap::ArgAction::Set)]
+        required: bool,
+    },
+    /// Require proofs to be mined over a slot hash committed to at most this many slots earlier
+    /// (PoW manager); 0 turns challenges off
+    SetPowChallenge {
+        window_slots: u64,
+    },
+    /// Select the hash proofs are mined with (PoW manager)
+    SetPowHashAlgorithm {
+        #[arg(value_enum)]
+        algorithm: PowHashAlgorithm,
+    },
+    /// Commit the signer to the newest slot hash for challenged proofs
+    CommitPowChallenge,
+    /// Set the stake provers need and the stake boost on PoW rewards (PoW manager)
+    SetProofStakePolicy {
+        /// Smallest stake that may record a proof, in raw token units
+        #[arg(long, default_value_t = 0)]
+        min_stake: u64,
+        /// Stake at which the boost peaks; 0 turns the boost off
+        #[arg(long, default_value_t = 0)]
+        full_boost_stake: u64,
+        /// Extra reward at --full-boost-stake, in basis points
+        #[arg(long, default_value_t = 0)]
+        max_boost_bps: u64,
+    },
+    #[cfg(feature = "devnet")]
+    /// Mint faucet tokens to the signer, creating its token account if needed. PoW-gated pools are
+    /// mined locally; oracle-gated pools need the oracle's signature and expiry
+    Faucet {
+        amount: u64,
+        /// Attestation expiry (unix seconds) issued by the oracle
+        #[arg(long)]
+        expiry: Option<i64>,
+        /// Base58 oracle signature over the faucet attestation message
+        #[arg(long, requires = "expiry")]
+        signature: Option<Signature>,
+        /// `MEMBER=SIGNATURE` over the faucet attestation message, once per signing oracle set
+        /// member
+        #[arg(long = "oracle-signature", requires = "expiry", conflicts_with = "signature")]
+        oracle_signatures: Vec<OracleSignature>,
+    },
+    /// Stake tokens from the signer's token account
+    Stake {
+        amount: u64,
+    },
+    /// Unstake tokens back to the signer's token account
+    Unstake {
+        amount: u64,
+    },
+    /// Mint pending rewards to the signer
+    Claim,
+    /// Print the global state and, optionally, a stake account and its proof history
+    InspectState {
+        /// Stake account owner to include
+        #[arg(long)]
+        owner: Option<Pubkey>,
+    },
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let out = Output::new(cli.json);
+    if let Err(err) = run(&cli, &out) {
+        out.error(&err);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(feature = "devnet")]
+#[derive(Clone, Copy, ValueEnum)]
+enum FaucetGate {
+    /// No proof required
+    None,
+    /// A proof-of-work nonce per claim
+    Pow,
+    /// An ed25519 attestation from the oracle authority per claim
+    Oracle,
+}
+
+#[cfg(feature = "devnet")]
+impl FaucetGate {
+    fn code(self) -> u8 {
+        match self {
+            FaucetGate::None => FAUCET_GATE_NONE,
+            FaucetGate::Pow => FAUCET_GATE_POW,
+            FaucetGate::Oracle => FAUCET_GATE_ORACLE,
+        }
+    }
+}
+
+/// The task fields `create-task` and `sign-task` share.
+#[derive(Args)]
+struct TaskArgs {
+    /// Stored as its SHA-256 hash
+    #[arg(long)]
+    description: String,
+    /// 0 follows the pool's `pow_reward`
+    #[arg(long, default_value_t = 0)]
+    reward: u64,
+    /// 0 follows the pool's `pow_difficulty`, including retargets
+    #[arg(long, default_value_t = 0)]
+    difficulty: u8,
+    /// Unix seconds; defaults to now
+    #[arg(long)]
+    opens_at: Option<i64>,
+    /// Unix seconds; defaults to never
+    #[arg(long, default_value_t = i64::MAX)]
+    closes_at: i64,
+    /// 0 for unlimited
+    #[arg(long, default_value_t = 0)]
+    max_solvers: u32,
+}
+
+impl TaskArgs {
+    fn params(&self) -> Result<TaskParams> {
+        let opens_at = match self.opens_at {
+            Some(opens_at) => opens_at,
+            None => std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)?
+                .as_secs() as i64,
+        };
+        Ok(TaskParams {
+            description_hash: hash(self.description.as_bytes()).to_bytes(),
+            reward: self.reward,
+            difficulty: self.difficulty,
+            opens_at,
+            closes_at: self.closes_at,
+            max_solvers: self.max_solvers,
+        })
+    }
+}
+
+/// One oracle's or oracle set member's signature, given as `MEMBER=SIGNATURE` in base58.
+#[derive(Clone)]
+struct OracleSignature {
+    member: Pubkey,
+    signature: Signature,
+}
+
+impl FromStr for OracleSignature {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let (member, signature) = value
+            .split_once('=')
+            .context("expected MEMBER=SIGNATURE")?;
+        Ok(Self {
+            member: member.parse().context("invalid member public key")?,
+            signature: signature.parse().context("invalid signature")?,
+        })
+    }
+}
+
+/// The ed25519 instruction an oracle message needs ahead of the instruction it authorizes.
+fn oracle_set_signatures(signatures: &[OracleSignature], message: &[u8]) -> Instruction {
+    let signatures: Vec<_> = signatures
+        .iter()
+        .map(|signed| (signed.member, signed.signature))
+        .collect();
+    instructions::ed25519_verify_many(&signatures, message)
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PowHashAlgorithm {
+    Keccak256,
+    /// Cheapest in compute units: a syscall on every cluster
+    Sha256,
+    /// Computed in the program, so the most expensive
+    Blake3,
+}
+
+impl PowHashAlgorithm {
+    fn code(self) -> u8 {
+        match self {
+            PowHashAlgorithm::Keccak256 => POW_HASH_KECCAK256,
+            PowHashAlgorithm::Sha256 => POW_HASH_SHA256,
+            PowHashAlgorithm::Blake3 => POW_HASH_BLAKE3,
+        }
+    }
+}
+
+fn run(cli: &Cli, out: &Output) -> Result<()> {
+    let rpc = RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed());
+
+    if let Command::InspectState { owner } = &cli.command {
+        return inspect_state(&rpc, owner.as_ref(), out);
+    }
+
+    let signer = load_keypair(cli.keypair.as_ref())?;
+    let payer = signer.pubkey();
+
+    if let Command::SignPowConfig {
+        difficulty,
+        reward,
+        nonce,
+        ttl,
+    } = &cli.command
+    {
+        return sign_pow_config(&rpc, &signer, (*difficulty, *reward, *nonce), *ttl, out);
+    }
+    if let Command::SignTask { task_id, task } = &cli.command {
+        return sign_task(&rpc, &signer, *task_id, &task.params()?, out);
+    }
+
+    let instructions = match &cli.command {
+        Command::Init {
+            mint,
+            apr_bps,
+            faucet_cap,
+            #[cfg(feature = "devnet")]
+            faucet_budget,
+            pow_reward,
+            pow_difficulty,
+            oracle_authority,
+        } => {
+            let token_program = mint_token_program(&rpc, mint)?;
+            #[cfg_attr(not(feature = "devnet"), allow(unused_mut))]
+            let mut ixs = vec![
+                instructions::initialize(
+                    &payer,
+                    mint,
+                    &token_program,
+                    *apr_bps,
+                    *faucet_cap,
+                    *pow_reward,
+                    *pow_difficulty,
+                    &oracle_authority.unwrap_or_default(),
+                ),
+                instructions::initialize_roles(&payer),
+            ];
+            #[cfg(feature = "devnet")]
+            if *faucet_budget > 0 {
+                ixs.push(instructions::top_up_faucet_budget(&payer, *faucet_budget));
+            }
+            ixs
+        }
+        Command::SetMintAuthority { mint } => {
+            let mint = match mint {
+                Some(mint) => *mint,
+                None => fetch_state(&rpc)?.mint,
+            };
+            let token_program = mint_token_program(&rpc, &mint)?;
+            vec![spl_token_2022::instruction::set_authority(
+                &token_program,
+                &mint,
+                Some(&pda::mint_auth().0),
+                AuthorityType::MintTokens,
+                &payer,
+                &[],
+            )?]
+        }
+        Command::SetApr { apr_bps } => vec![instructions::set_apr(&payer, *apr_bps)],
+        #[cfg(feature = "devnet")]
+        Command::UpdateFaucetCap { faucet_cap } => {
+            vec![instructions::update_faucet_cap(&payer, *faucet_cap)]
+        }
+        #[cfg(feature = "devnet")]
+        Command::TopUpFaucetBudget { amount } => {
+            vec![instructions::top_up_faucet_budget(&payer, *amount)]
+        }
+        #[cfg(feature = "devnet")]
+        Command::SetFaucetLimits {
+            cooldown,
+            window,
+            window_cap,
+            daily_budget,
+        } => vec![instructions::set_faucet_limits(
+            &payer,
+            *cooldown,
+            *window,
+            *window_cap,
+            *daily_budget,
+        )],
+        #[cfg(feature = "devnet")]
+        Command::SetFaucetGate {
+            mode,
+            pow_difficulty,
+        } => vec![instructions::set_faucet_gate(
+            &payer,
+            mode.code(),
+            *pow_difficulty,
+        )],
+        Command::SetOracleAuthority { authority } => {
+            vec![instructions::set_oracle_authority(&payer, authority)]
+        }
+        Command::InitOracleSet { members, threshold } => {
+            vec![instructions::initialize_oracle_set(&payer, members, *threshold)]
+        }
+        Command::AddOracleMember { member } => {
+            vec![instructions::add_oracle_member(&payer, member)]
+        }
+        Command::RemoveOracleMember { member } => {
+            vec![instructions::remove_oracle_member(&payer, member)]
+        }
+        Command::SetOracleThreshold { threshold } => {
+            vec![instructions::set_oracle_threshold(&payer, *threshold)]
+        }
+        Command::CreateTask {
+            task_id,
+            task,
+            oracle_signatures,
+        } => {
+            let params = task.params()?;
+            if oracle_signatures.is_empty() {
+                vec![instructions::create_task(&payer, *task_id, params)]
+            } else {
+                let message = task_message(&fetch_state(&rpc)?.mint, *task_id, &params);
+                vec![
+                    oracle_set_signatures(oracle_signatures, &message),
+                    instructions::relay_create_task(&payer, *task_id, params),
+                ]
+            }
+        }
+        Command::SetPowConfig {
+            difficulty,
+            reward,
+            nonce,
+            oracle_signatures,
+        } => {
+            let state = fetch_state(&rpc)?;
+            let nonce = match nonce {
+                Some(nonce) => *nonce,
+                None => state
+                    .oracle_nonce
+                    .checked_add(1)
+                    .context("oracle nonce overflow")?,
+            };
+            let mut ixs = Vec::new();
+            if !oracle_signatures.is_empty() {
+                let message = pow_config_message(&state.mint, *difficulty, *reward, nonce);
+                ixs.push(oracle_set_signatures(oracle_signatures, &message));
+            }
+            ixs.push(instructions::set_pow_config(
+                &payer,
+                *difficulty,
+                *reward,
+                nonce,
+            ));
+            ixs
+        }
+        Command::SetPowRetarget {
+            window,
+            target_proofs,
+            max_step,
+            min_difficulty,
+            max_difficulty,
+        } => vec![instructions::set_pow_retarget(
+            &payer,
+            PowRetarget {
+                window: *window,
+                target_proofs: *target_proofs,
+                max_step: *max_step,
+                min_difficulty: *min_difficulty,
+                max_difficulty: *max_difficulty,
+            },
+        )],
+        Command::SetProofAttestation { required } => {
+            vec![instructions::set_proof_attestation(&payer, *required)]
+        }
+        Command::SetPowChallenge { window_slots } => {
+            vec![instructions::set_pow_challenge(&payer, *window_slots)]
+        }
+        Command::SetPowHashAlgorithm { algorithm } => {
+            vec![instructions::set_pow_hash_algorithm(&payer, algorithm.code())]
+        }
+        Command::CommitPowChallenge => vec![instructions::commit_pow_challenge(&payer)],
+        Command::SetProofStakePolicy {
+            min_stake,
+            full_boost_stake,
+            max_boost_bps,
+        } => vec![instructions::set_proof_stake_policy(
+            &payer,
+            ProofStakePolicy {
+                min_stake: *min_stake,
+                full_boost_stake: *full_boost_stake,
+                max_boost_bps: *max_boost_bps,
+            },
+        )],
+        #[cfg(feature = "devnet")]
+        Command::Faucet {
+            amount,
+            expiry,
+            signature,
+            oracle_signatures,
+        } => {
+            let (mint, token_program, user_token) = user_token_account(&rpc, &payer)?;
+            let mut ixs = vec![create_associated_token_account_idempotent(
+                &payer,
+                &payer,
+                &mint,
+                &token_program,
+            )];
+            let state = fetch_state(&rpc)?;
+            let stake_account = fetch_optional_account(&rpc, &pda::stake_account(&payer).0)?;
+            let faucet_claimed = match stake_account {
+                Some(data) => StakeAccount::try_from_account_data(&data)?.faucet_claimed,
+                None => 0,
+            };
+            let proof = match state.faucet_gate {
+                FAUCET_GATE_POW => {
+                    let difficulty = state.faucet_pow_difficulty;
+                    let nonce = mine_faucet_nonce(&payer, &mint, faucet_claimed, difficulty);
+                    FaucetProof::Pow { nonce }
+                }
+                FAUCET_GATE_ORACLE => {
+                    let Some(expiry) = expiry else {
+                        bail!("the faucet is oracle-gated; pass --expiry and --signature");
+                    };
+                    let message =
+                        faucet_attestation_message(&payer, &mint, *amount, faucet_claimed, *expiry);
+                    match signature {
+                        Some(signature) => ixs.push(instructions::ed25519_verify(
+                            &state.oracle_authority,
+                            signature,
+                            &message,
+                        )),
+                        None if !oracle_signatures.is_empty() => {
+                            ixs.push(oracle_set_signatures(oracle_signatures, &message))
+                        }
+                        None => bail!(
+                            "the faucet is oracle-gated; pass --signature or --oracle-signature"
+                        ),
+                    }
+                    FaucetProof::Attestation { expiry: *expiry }
+                }
+                _ => FaucetProof::None,
+            };
+            ixs.push(instructions::faucet(
+                &payer,
+                &mint,
+                &user_token,
+                &token_program,
+                *amount,
+                proof,
+            ));
+            ixs
+        }
+        Command::Stake { amount } => {
+            let (mint, token_program, user_token) = user_token_account(&rpc, &payer)?;
+            vec![instructions::stake(
+                &payer,
+                &mint,
+                &user_token,
+                &token_program,
+                *amount,
+            )]
+        }
+        Command::Unstake { amount } => {
+            let (mint, token_program, user_token) = user_token_account(&rpc, &payer)?;
+            vec![instructions::unstake(
+                &payer,
+                &mint,
+                &user_token,
+                &token_program,
+                *amount,
+            )]
+        }
+        Command::Claim => {
+            let (mint, token_program, user_token) = user_token_account(&rpc, &payer)?;
+            vec![
+                create_associated_token_account_idempotent(&payer, &payer, &mint, &token_program),
+                instructions::claim(&payer, &mint, &user_token, &token_program),
+            ]
+        }
+        Command::RelayPowConfig {
+            difficulty,
+            reward,
+            nonce,
+            expiry,
+            oracle_signatures,
+        } => {
+            let mint = fetch_state(&rpc)?.mint;
+            let message =
+                relayed_pow_config_message(&mint, *difficulty, *reward, *nonce, *expiry);
+            vec![
+                oracle_set_signatures(oracle_signatures, &message),
+                instructions::relay_pow_config(&payer, *difficulty, *reward, *nonce, *expiry),
+            ]
+        }
+        Command::InspectState { .. }
+        | Command::SignPowConfig { .. }
+        | Command::SignTask { .. } => unreachable!("handled above"),
+    };
+
+    submit(&rpc, &signer, &instructions, cli.dry_run, out)
+}
+
+/// Prints the signer's `ORACLE=SIGNATURE` over `relayed_pow_config_message` for a relayer to
+/// pass to `relay-pow-config`.
+fn sign_pow_config(
+    rpc: &RpcClient,
+    signer: &Keypair,
+    (difficulty, reward, nonce): (u8, u64, Option<u64>),
+    ttl: i64,
+    out: &Output,
+) -> Result<()> {
+    let state = fetch_state(rpc)?;
+    let nonce = match nonce {
+        Some(nonce) => nonce,
+        None => state
+            .oracle_nonce
+            .checked_add(1)
+            .context("oracle nonce overflow")?,
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs() as i64;
+    let expiry = now.checked_add(ttl).context("expiry overflow")?;
+    let message = relayed_pow_config_message(&state.mint, difficulty, reward, nonce, expiry);
+    let signature = signer.sign_message(&message);
+    out.report(&json!({
+        "pow_config": {
+            "difficulty": difficulty,
+            "reward": reward,
+            "nonce": nonce,
+            "expiry": expiry,
+            "oracle_signature": format!("{}={signature}", signer.pubkey()),
+        },
+    }));
+    Ok(())
+}
+
+/// Prints the signer's `ORACLE=SIGNATURE` over `task_message` for a relayer to pass to
+/// `create-task --oracle-signature`, with the `opens_at` it covers.
+fn sign_task(
+    rpc: &RpcClient,
+    signer: &Keypair,
+    task_id: u64,
+    params: &TaskParams,
+    out: &Output,
+) -> Result<()> {
+    let message = task_message(&fetch_state(rpc)?.mint, task_id, params);
+    let signature = signer.sign_message(&message);
+    out.report(&json!({
+        "task": {
+            "task_id": task_id,
+            "opens_at": params.opens_at,
+            "oracle_signature": format!("{}={signature}", signer.pubkey()),
+        },
+    }));
+    Ok(())
+}
+
+fn load_keypair(path: Option<&PathBuf>) -> Result<Keypair> {
+    let path = match path {
+        Some(path) => path.clone(),
+        None => {
+            let home = std::env::var("HOME").context("HOME is not set; pass --keypair")?;
+            PathBuf::from(home).join(".config/solana/id.json")
+        }
+    };
+    read_keypair_file(&path)
+        .map_err(|err| anyhow::anyhow!("reading keypair {}: {err}", path.display()))
+}
+
+/// Searches nonces from 0 until the faucet PoW hash meets `difficulty`.
+#[cfg(feature = "devnet")]
+fn mine_faucet_nonce(user: &Pubkey, mint: &Pubkey, faucet_claimed: u64, difficulty: u8) -> u64 {
+    (0..)
+        .find(|nonce| {
+            let hash = faucet_pow_hash(user, mint, faucet_claimed, *nonce);
+            meets_difficulty(&hash, difficulty)
+        })
+        .expect("a nonce exists below u64::MAX")
+}
+
+fn fetch_state(rpc: &RpcClient) -> Result<GlobalState> {
+    let data = rpc
+        .get_account_data(&pda::state().0)
+        .context("fetching state account; has `init` run?")?;
+    Ok(GlobalState::try_from_account_data(&data)?)
+}
+
+/// Data of the account at `address`, or `None` when there is none; RPC failures stay errors.
+fn fetch_optional_account(rpc: &RpcClient, address: &Pubkey) -> Result<Option<Vec<u8>>> {
+    let account = rpc
+        .get_account_with_commitment(address, rpc.commitment())
+        .with_context(|| format!("fetching account {address}"))?
+        .value;
+    Ok(account.map(|account| account.data))
+}
+
+fn mint_token_program(rpc: &RpcClient, mint: &Pubkey) -> Result<Pubkey> {
+    let account = rpc
+        .get_account(mint)
+        .with_context(|| format!("fetching mint {mint}"))?;
+    Ok(account.owner)
+}
+
+/// Returns `(mint, token_program, associated token account)` for `owner` and the pool mint.
+fn user_token_account(rpc: &RpcClient, owner: &Pubkey) -> Result<(Pubkey, Pubkey, Pubkey)> {
+    let mint = fetch_state(rpc)?.mint;
+    let token_program = mint_token_program(rpc, &mint)?;
+    let ata = get_associated_token_address_with_program_id(owner, &mint, &token_program);
+    Ok((mint, token_program, ata))
+}
+
+fn submit(
+    rpc: &RpcClient,
+    signer: &Keypair,
+    instructions: &[Instruction],
+    dry_run: bool,
+    out: &Output,
+) -> Result<()> {
+    let blockhash = rpc.get_latest_blockhash()?;
+    let tx = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&signer.pubkey()),
+        &[signer],
+        blockhash,
+    );
+
+    if dry_run {
+        let result = rpc
+            .simulate_transaction_with_config(
+                &tx,
+                RpcSimulateTransactionConfig {
+                    sig_verify: true,
+                    ..RpcSimulateTransactionConfig::default()
+                },
+            )?
+            .value;
+        let program_error = result
+            .err
+            .as_ref()
+            .and_then(SkillStakeError::from_transaction_error);
+        out.simulation(&json!({
+            "ok": result.err.is_none(),
+            "error": result.err.as_ref().map(|err| err.to_string()),
+            "program_error": program_error.map(|err| err.to_string()),
+            "units_consumed": result.units_consumed,
+            "logs": result.logs.unwrap_or_default(),
+        }));
+        return Ok(());
+    }
+
+    match rpc.send_and_confirm_transaction(&tx) {
+        Ok(signature) => {
+            out.signature(&signature.to_string());
+            Ok(())
+        }
+        Err(err) => {
+            if let Some(program_error) = err
+                .get_transaction_error()
+                .as_ref()
+                .and_then(SkillStakeError::from_transaction_error)
+            {
+                bail!("{program_error} ({})", program_error.code());
+            }
+            Err(err.into())
+        }
+    }
+}
+
+fn inspect_state(rpc: &RpcClient, owner: Option<&Pubkey>, out: &Output) -> Result<()> {
+    let (state_address, _) = pda::state();
+    let state = fetch_state(rpc)?;
+    let mut report = json!({
+        "state": {
+            "address": state_address.to_string(),
+            "version": state.version,
+            "admin": state.admin.to_string(),
+            "oracle_authority": state.oracle_authority.to_string(),
+            "mint": state.mint.to_string(),
+            "vault": state.vault.to_string(),
+            "apr_bps": state.apr_bps,
+            "total_staked": state.total_staked,
+            "faucet_cap": state.faucet_cap,
+            "pow_reward": state.pow_reward,
+            "pow_difficulty": state.pow_difficulty,
+            "oracle_nonce": state.oracle_nonce,
+            "timelock_delay": state.timelock_delay,
+            "change_count": state.change_count,
+            "paused": state.paused,
+            "faucet_cooldown": state.faucet_cooldown,
+            "faucet_window": state.faucet_window,
+            "faucet_window_cap": state.faucet_window_cap,
+            "faucet_daily_budget": state.faucet_daily_budget,
+            "faucet_day": state.faucet_day,
+            "faucet_day_minted": state.faucet_day_minted,
+            "faucet_budget": state.faucet_budget,
+            "faucet_minted": state.faucet_minted,
+            "faucet_remaining": state.faucet_remaining(),
+            "faucet_gate": state.faucet_gate,
+            "faucet_pow_difficulty": state.faucet_pow_difficulty,
+            "pow_retarget_window": state.pow_retarget.window,
+            "pow_retarget_target_proofs": state.pow_retarget.target_proofs,
+            "pow_retarget_max_step": state.pow_retarget.max_step,
+            "pow_min_difficulty": state.pow_retarget.min_difficulty,
+            "pow_max_difficulty": state.pow_retarget.max_difficulty,
+            "pow_window_start": state.pow_window_start,
+            "pow_window_proofs": state.pow_window_proofs,
+            "require_proof_attestation": state.require_proof_attestation,
+            "min_proof_stake": state.proof_stake_policy.min_stake,
+            "full_boost_stake": state.proof_stake_policy.full_boost_stake,
+            "max_proof_boost_bps": state.proof_stake_policy.max_boost_bps,
+            "pow_challenge_slots": state.pow_challenge_slots,
+            "pow_hash_algorithm": state.pow_hash_algorithm,
+        },
+    });
+
+    // Absent until the admin initializes one; the oracle authority applies until then.
+    let (address, _) = pda::oracle_set();
+    if let Some(data) = fetch_optional_account(rpc, &address)? {
+        let oracle_set = OracleSet::try_from_account_data(&data)?;
+        let members: Vec<_> = oracle_set.members.iter().map(Pubkey::to_string).collect();
+        report["oracle_set"] = json!({
+            "address": address.to_string(),
+            "version": oracle_set.version,
+            "members": members,
+            "threshold": oracle_set.threshold,
+        });
+    }
+
+    if let Some(owner) = owner {
+        let (address, _) = pda::stake_account(owner);
+        let data = rpc
+            .get_account_data(&address)
+            .with_context(|| format!("fetching stake account for {owner}"))?;
+        let stake = StakeAccount::try_from_account_data(&data)?;
+        report["stake_account"] = json!({
+            "address": address.to_string(),
+            "version": stake.version,
+            "owner": stake.owner.to_string(),
+            "amount_staked": stake.amount_staked,
+            "pending_rewards": stake.pending_rewards,
+            "faucet_claimed": stake.faucet_claimed,
+            "last_accrued_ts": stake.last_accrued_ts,
+            "last_proof_ts": stake.last_proof_ts,
+            "last_task_id": stake.last_task_id,
+            "last_faucet_ts": stake.last_faucet_ts,
+            "faucet_window_start": stake.faucet_window_start,
+            "faucet_window_claimed": stake.faucet_window_claimed,
+            "pow_challenge_slot": stake.pow_challenge_slot,
+            "pow_challenge_hash": Hash::new_from_array(stake.pow_challenge_hash).to_string(),
+        });
+
+        // Absent until the owner's first proof.
+        let (address, _) = pda::proof_history(owner);
+        if let Some(data) = fetch_optional_account(rpc, &address)? {
+            let history = ProofHistory::try_from_account_data(&data)?;
+            let entries: Vec<_> = history
+                .chronological()
+                .map(|entry| {
+                    json!({
+                        "task_id": entry.record.task_id,
+                        "nonce": entry.record.nonce,
+                        "reward": entry.reward,
+                        "timestamp": entry.timestamp,
+                    })
+                })
+                .collect();
+            report["proof_history"] = json!({
+                "address": address.to_string(),
+                "entries": entries,
+            });
+        }
+    }
+
+    out.report(&report);
+    Ok(())
+}
@@ -0,0 +1,65 @@
+//! Human-readable or JSON output, selected by `--json`.
+
+use serde_json::{json, Value};
+
+pub struct Output {
+    json: bool,
+}
+
+impl Output {
+    pub fn new(json: bool) -> Self {
+        Self { json }
+    }
+
+    pub fn signature(&self, signature: &str) {
+        if self.json {
+            println!("{}", json!({ "signature": signature }));
+        } else {
+            println!("Signature: {signature}");
+        }
+    }
+
+    pub fn simulation(&self, result: &Value) {
+        if self.json {
+            println!("{result}");
+            return;
+        }
+        match result["error"].as_str() {
+            None => println!("Simulation succeeded"),
+            Some(err) => match result["program_error"].as_str() {
+                Some(program_error) => println!("Simulation failed: {program_error} ({err})"),
+                None => println!("Simulation failed: {err}"),
+            },
+        }
+        if let Some(units) = result["units_consumed"].as_u64() {
+            println!("Compute units: {units}");
+        }
+        for line in result["logs"].as_array().into_iter().flatten() {
+            println!("  {}", line.as_str().unwrap_or_default());
+        }
+    }
+
+    pub fn report(&self, report: &Value) {
+        if self.json {
+            println!("{report}");
+            return;
+        }
+        for (section, fields) in report.as_object().into_iter().flatten() {
+            println!("{section}:");
+            for (key, value) in fields.as_object().into_iter().flatten() {
+                match value {
+                    Value::String(text) => println!("  {key:<18} {text}"),
+                    other => println!("  {key:<18} {other}"),
+                }
+            }
+        }
+    }
+
+    pub fn error(&self, err: &anyhow::Error) {
+        if self.json {
+            println!("{}", json!({ "error": format!("{err:#}") }));
+        } else {
+            eprintln!("error: {err:#}");
+        }
+    }
+}
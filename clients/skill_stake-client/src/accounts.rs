@@ -0,0 +1,418 @@
+//! Borsh mirrors of the program's accounts, instruction argument types and view return values.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::discriminator;
+
+#[derive(Debug, thiserror::Error)]
+pub enum DecodeError {
+    #[error("account data is shorter than the discriminator")]
+    TooShort,
+    #[error("account discriminator does not match {0}")]
+    WrongDiscriminator(&'static str),
+    #[error("borsh: {0}")]
+    Borsh(#[from] std::io::Error),
+}
+
+/// An Anchor account: 8-byte discriminator followed by the Borsh body.
+pub trait ProgramAccount: BorshDeserialize + Sized {
+    const NAME: &'static str;
+
+    fn discriminator() -> [u8; 8] {
+        discriminator("account", Self::NAME)
+    }
+
+    /// Decodes raw account data. Trailing bytes (unused `SPACE`) are ignored.
+    fn try_from_account_data(data: &[u8]) -> Result<Self, DecodeError> {
+        if data.len() < 8 {
+            return Err(DecodeError::TooShort);
+        }
+        if data[..8] != Self::discriminator() {
+            return Err(DecodeError::WrongDiscriminator(Self::NAME));
+        }
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GlobalState {
+    pub admin: Pubkey,
+    pub oracle_authority: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub apr_bps: u64,
+    pub total_staked: u64,
+    pub faucet_cap: u64,
+    pub pow_reward: u64,
+    pub oracle_nonce: u64,
+    pub pow_difficulty: u8,
+    pub bump: u8,
+    pub vault_bump: u8,
+    pub mint_auth_bump: u8,
+    pub version: u8,
+    pub _padding: [u8; 3],
+    pub timelock_delay: i64,
+    pub change_count: u64,
+    pub paused: u8,
+    pub faucet_cooldown: i64,
+    pub faucet_window: i64,
+    pub faucet_window_cap: u64,
+    pub faucet_daily_budget: u64,
+    pub faucet_day: i64,
+    pub faucet_day_minted: u64,
+    pub faucet_budget: u64,
+    pub faucet_minted: u64,
+    pub faucet_gate: u8,
+    pub faucet_pow_difficulty: u8,
+    pub pow_retarget: PowRetarget,
+    pub pow_window_start: i64,
+    pub pow_window_proofs: u64,
+    pub require_proof_attestation: bool,
+    pub proof_stake_policy: ProofStakePolicy,
+    pub pow_challenge_slots: u64,
+    pub pow_hash_algorithm: u8,
+}
+
+impl GlobalState {
+    pub const VERSION: u8 = 9;
+
+    /// Tokens the faucet can still mint before it shuts off.
+    pub fn faucet_remaining(&self) -> u64 {
+        self.faucet_budget.saturating_sub(self.faucet_minted)
+    }
+
+    pub fn is_paused(&self, flag: u8) -> bool {
+        self.paused & flag != 0
+    }
+}
+
+impl ProgramAccount for GlobalState {
+    const NAME: &'static str = "GlobalState";
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StakeAccount {
+    pub owner: Pubkey,
+    pub amount_staked: u64,
+    pub pending_rewards: u64,
+    pub faucet_claimed: u64,
+    pub last_accrued_ts: i64,
+    pub last_proof_ts: i64,
+    pub last_task_id: u64,
+    pub bump: u8,
+    pub version: u8,
+    pub _padding: [u8; 6],
+    pub last_proof: Vec<u8>,
+    pub last_faucet_ts: i64,
+    pub faucet_window_start: i64,
+    pub faucet_window_claimed: u64,
+    pub task_bitmap: [u64; 4],
+    pub pow_challenge_slot: u64,
+    pub pow_challenge_hash: [u8; 32],
+}
+
+impl StakeAccount {
+    pub const VERSION: u8 = 4;
+
+    /// Whether `record_proof` would reject `task_id` as a replay or as too old.
+    pub fn is_task_spent(&self, task_id: u64) -> bool {
+        if self.last_task_id.saturating_sub(task_id) >= crate::PROOF_REPLAY_WINDOW {
+            return true;
+        }
+        if task_id > self.last_task_id {
+            return false;
+        }
+        let slot = task_id % crate::PROOF_REPLAY_WINDOW;
+        self.task_bitmap[(slot / 64) as usize] & (1 << (slot % 64)) != 0
+    }
+
+    /// Decodes `last_proof`; `None` before the first `record_proof`.
+    pub fn last_proof_record(&self) -> Result<Option<ProofRecord>, DecodeError> {
+        if self.last_proof.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(ProofRecord::deserialize(
+            &mut self.last_proof.as_slice(),
+        )?))
+    }
+}
+
+impl ProgramAccount for StakeAccount {
+    const NAME: &'static str = "StakeAccount";
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProofRecord {
+    pub task_id: u64,
+    pub nonce: u64,
+    pub hash: [u8; 32],
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProofHistory {
+    pub owner: Pubkey,
+    pub bump: u8,
+    pub version: u8,
+    pub head: u16,
+    pub entries: Vec<ProofHistoryEntry>,
+}
+
+impl ProofHistory {
+    pub const VERSION: u8 = 1;
+
+    /// Entries from the oldest to the most recent.
+    pub fn chronological(&self) -> impl Iterator<Item = &ProofHistoryEntry> {
+        let (newer, older) = self.entries.split_at(self.head as usize);
+        older.iter().chain(newer)
+    }
+}
+
+impl ProgramAccount for ProofHistory {
+    const NAME: &'static str = "ProofHistory";
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProofHistoryEntry {
+    pub record: ProofRecord,
+    pub reward: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Task {
+    pub task_id: u64,
+    pub description_hash: [u8; 32],
+    /// Zero follows the pool's current `pow_reward`.
+    pub reward: u64,
+    pub opens_at: i64,
+    pub closes_at: i64,
+    /// Zero means unlimited.
+    pub max_solvers: u32,
+    pub solvers: u32,
+    /// Zero follows the pool's current `pow_difficulty`, including retargets.
+    pub difficulty: u8,
+    pub bump: u8,
+    pub version: u8,
+}
+
+impl ProgramAccount for Task {
+    const NAME: &'static str = "Task";
+}
+
+impl Task {
+    /// Leading zero bits a proof for this task needs under `state`.
+    pub fn proof_difficulty(&self, state: &GlobalState) -> u8 {
+        match self.difficulty {
+            0 => state.pow_difficulty,
+            difficulty => difficulty,
+        }
+    }
+
+    /// Reward for a proof of this task before any attestation or stake boost.
+    pub fn proof_reward(&self, state: &GlobalState) -> u64 {
+        match self.reward {
+            0 => state.pow_reward,
+            reward => reward,
+        }
+    }
+}
+
+/// Argument to `create_task`. Proofs are accepted from `opens_at` until just before `closes_at`; a
+/// zero `reward` or `difficulty` follows the pool's values.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TaskParams {
+    pub description_hash: [u8; 32],
+    pub reward: u64,
+    pub difficulty: u8,
+    pub opens_at: i64,
+    pub closes_at: i64,
+    pub max_solvers: u32,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ParamChange {
+    Apr { apr_bps: u64 },
+    FaucetCap { faucet_cap: u64 },
+    OracleAuthority { new_authority: Pubkey },
+    TimelockDelay { delay: i64 },
+    FaucetLimits {
+        cooldown: i64,
+        window: i64,
+        window_cap: u64,
+        daily_budget: u64,
+    },
+    FaucetGate {
+        gate: u8,
+        pow_difficulty: u8,
+    },
+    PowChallenge {
+        window_slots: u64,
+    },
+    PowHashAlgorithm {
+        algorithm: u8,
+    },
+}
+
+/// Argument to `set_pow_retarget`; a zero `window` disables retargeting.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PowRetarget {
+    pub window: i64,
+    pub target_proofs: u64,
+    pub max_step: u8,
+    pub min_difficulty: u8,
+    pub max_difficulty: u8,
+}
+
+/// Argument to `set_proof_stake_policy`; a zero `full_boost_stake` disables the reward boost.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProofStakePolicy {
+    pub min_stake: u64,
+    pub full_boost_stake: u64,
+    pub max_boost_bps: u64,
+}
+
+/// Argument to `record_proof`. Must follow an `instructions::ed25519_verify` of the oracle's
+/// signature over `proof_attestation_message`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofAttestation {
+    pub reward: u64,
+    pub expiry: i64,
+}
+
+/// One `(task_id, nonce)` pair of a `record_proofs` batch.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofSubmission {
+    pub task_id: u64,
+    pub nonce: u64,
+}
+
+/// Argument to `faucet`; must match `GlobalState::faucet_gate`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FaucetProof {
+    None,
+    Pow { nonce: u64 },
+    /// Must follow an `instructions::ed25519_verify` of the oracle's signature over
+    /// `faucet_attestation_message`.
+    Attestation { expiry: i64 },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PendingChange {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub change: ParamChange,
+    pub queued_ts: i64,
+    pub eta: i64,
+    pub bump: u8,
+}
+
+impl ProgramAccount for PendingChange {
+    const NAME: &'static str = "PendingChange";
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoleMember {
+    pub key: Pubkey,
+    pub permissions: u8,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Roles {
+    pub members: Vec<RoleMember>,
+    pub bump: u8,
+}
+
+impl Roles {
+    pub fn permissions(&self, key: &Pubkey) -> u8 {
+        self.members
+            .iter()
+            .find(|m| m.key == *key)
+            .map_or(0, |m| m.permissions)
+    }
+}
+
+impl ProgramAccount for Roles {
+    const NAME: &'static str = "Roles";
+}
+
+/// Absent until `initialize_oracle_set`; from then on it replaces `GlobalState::oracle_authority`.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct OracleSet {
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub bump: u8,
+    pub version: u8,
+}
+
+impl ProgramAccount for OracleSet {
+    const NAME: &'static str = "OracleSet";
+}
+
+// View return values, decoded from simulated transaction return data.
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RewardsPreview {
+    pub owner: Pubkey,
+    pub amount_staked: u64,
+    pub accrued_since_last: u64,
+    pub pending_rewards: u64,
+    pub apr_bps: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UnstakePreview {
+    pub amount: u64,
+    pub remaining_staked: u64,
+    pub pending_rewards: u64,
+    pub total_staked_after: u64,
+    pub timestamp: i64,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProofCheck {
+    pub hash: [u8; 32],
+    pub difficulty: u8,
+    pub meets_difficulty: bool,
+    pub is_replay: bool,
+    pub reward: u64,
+    pub is_open: bool,
+    pub meets_min_stake: bool,
+    pub is_challenge_fresh: bool,
+    pub requires_attestation: bool,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PoolInfo {
+    pub admin: Pubkey,
+    pub oracle_authority: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub vault_balance: u64,
+    pub apr_bps: u64,
+    pub total_staked: u64,
+    pub faucet_cap: u64,
+    pub pow_reward: u64,
+    pub pow_difficulty: u8,
+    pub oracle_nonce: u64,
+    pub timelock_delay: i64,
+    pub paused: u8,
+    pub version: u8,
+    pub faucet_cooldown: i64,
+    pub faucet_window: i64,
+    pub faucet_window_cap: u64,
+    pub faucet_daily_budget: u64,
+    pub faucet_day_minted: u64,
+    pub faucet_budget: u64,
+    pub faucet_minted: u64,
+    pub faucet_gate: u8,
+    pub faucet_pow_difficulty: u8,
+    pub pow_retarget: PowRetarget,
+    pub pow_window_start: i64,
+    pub pow_window_proofs: u64,
+    pub require_proof_attestation: bool,
+    pub proof_stake_policy: ProofStakePolicy,
+    pub pow_challenge_slots: u64,
+    pub pow_hash_algorithm: u8,
+}
@@ -0,0 +1,191 @@
+//! Program error codes. Anchor offsets custom errors by 6000.
+
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::transaction::TransactionError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+#[repr(u32)]
+pub enum SkillStakeError {
+    #[error("APR exceeds the maximum for this cluster")]
+    AprTooHigh = 6000,
+    #[error("Operation would overflow maths")]
+    MathOverflow = 6001,
+    #[error("Provided amount must be greater than zero")]
+    InvalidAmount = 6002,
+    #[error("Nothing to claim yet")]
+    NothingToClaim = 6003,
+    #[error("Insufficient staked balance")]
+    InsufficientStake = 6004,
+    #[error("Caller is not authorized for this action")]
+    Unauthorized = 6005,
+    #[error("Missing PDA bump in context")]
+    BumpNotFound = 6006,
+    #[error("Provided mint does not match program state")]
+    MintMismatch = 6007,
+    #[error("Proof payload too large")]
+    ProofTooLarge = 6008,
+    #[error("Faucet cap exceeded for this wallet")]
+    FaucetCapExceeded = 6009,
+    #[error("Oracle authority must be a valid public key")]
+    InvalidOracleAuthority = 6010,
+    #[error("Proof of work difficulty is invalid for this program")]
+    InvalidPowDifficulty = 6011,
+    #[error("Oracle update nonce is stale")]
+    StaleOracleUpdate = 6012,
+    #[error("Proof does not satisfy the difficulty target")]
+    ProofDifficultyNotMet = 6013,
+    #[error("Task identifier has already been used")]
+    ProofTaskReplay = 6014,
+    #[error("Timelock is active; queue this change instead")]
+    TimelockActive = 6015,
+    #[error("Execution time is earlier than the timelock delay allows")]
+    TimelockTooShort = 6016,
+    #[error("Timelock delay has not elapsed yet")]
+    TimelockNotElapsed = 6017,
+    #[error("Timelock delay has already elapsed")]
+    TimelockElapsed = 6018,
+    #[error("Timelock delay is out of range")]
+    InvalidTimelockDelay = 6019,
+    #[error("This instruction is currently paused")]
+    InstructionPaused = 6020,
+    #[error("Unknown pause flag bits")]
+    InvalidPauseFlags = 6021,
+    #[error("Role permissions are invalid")]
+    InvalidRole = 6022,
+    #[error("Roles account is full")]
+    RoleCapacityExceeded = 6023,
+    #[error("Account layout is outdated; run the migration instruction first")]
+    AccountNotMigrated = 6024,
+    #[error("Account layout is already current")]
+    AccountAlreadyMigrated = 6025,
+    #[error("Faucet cooldown has not elapsed for this wallet")]
+    FaucetCooldownActive = 6026,
+    #[error("Faucet rolling window cap exceeded for this wallet")]
+    FaucetWindowCapExceeded = 6027,
+    #[error("Faucet daily budget exhausted")]
+    FaucetDailyBudgetExceeded = 6028,
+    #[error("Faucet limits are invalid")]
+    InvalidFaucetLimits = 6029,
+    #[error("Faucet lifetime budget exceeded; awaiting top-up")]
+    FaucetBudgetExceeded = 6030,
+    #[error("Faucet is not available on this cluster")]
+    FaucetUnavailable = 6031,
+    #[error("Faucet gate mode or difficulty is invalid")]
+    InvalidFaucetGate = 6032,
+    #[error("Faucet proof does not match the configured gate")]
+    FaucetProofMismatch = 6033,
+    #[error("Expected an ed25519 oracle attestation before this instruction")]
+    AttestationMissing = 6034,
+    #[error("Oracle attestation signer or message does not match")]
+    AttestationMismatch = 6035,
+    #[error("Oracle attestation has expired")]
+    AttestationExpired = 6036,
+    #[error("PoW retarget configuration is invalid")]
+    InvalidRetargetConfig = 6037,
+    #[error("Task configuration is invalid")]
+    InvalidTaskConfig = 6038,
+    #[error("Task is not open yet")]
+    TaskNotOpen = 6039,
+    #[error("Task is closed")]
+    TaskClosed = 6040,
+    #[error("Task has reached its solver limit")]
+    TaskFull = 6041,
+    #[error("Task is too far below the highest recorded task")]
+    ProofTaskTooOld = 6042,
+    #[error("Proof stake policy is invalid")]
+    InvalidProofStakePolicy = 6043,
+    #[error("Stake is below the minimum for submitting proofs")]
+    InsufficientProofStake = 6044,
+    #[error("PoW challenge window is invalid")]
+    InvalidPowChallengeConfig = 6045,
+    #[error("Commit to a slot hash before submitting proofs")]
+    PowChallengeMissing = 6046,
+    #[error("Committed slot hash is too old")]
+    PowChallengeExpired = 6047,
+    #[error("Unknown PoW hash algorithm")]
+    InvalidPowHashAlgorithm = 6048,
+    #[error("Proof batch is empty, too large, or does not match its task accounts")]
+    InvalidProofBatch = 6049,
+    #[error("Oracle set members or threshold are invalid")]
+    InvalidOracleSet = 6050,
+    #[error("Fewer distinct oracle members signed than the threshold")]
+    OracleThresholdNotMet = 6051,
+    #[error("Queued change expired before it was executed")]
+    ChangeExpired = 6052,
+}
+
+impl SkillStakeError {
+    pub fn code(self) -> u32 {
+        self as u32
+    }
+
+    pub fn from_code(code: u32) -> Option<Self> {
+        use SkillStakeError::*;
+        Some(match code {
+            6000 => AprTooHigh,
+            6001 => MathOverflow,
+            6002 => InvalidAmount,
+            6003 => NothingToClaim,
+            6004 => InsufficientStake,
+            6005 => Unauthorized,
+            6006 => BumpNotFound,
+            6007 => MintMismatch,
+            6008 => ProofTooLarge,
+            6009 => FaucetCapExceeded,
+            6010 => InvalidOracleAuthority,
+            6011 => InvalidPowDifficulty,
+            6012 => StaleOracleUpdate,
+            6013 => ProofDifficultyNotMet,
+            6014 => ProofTaskReplay,
+            6015 => TimelockActive,
+            6016 => TimelockTooShort,
+            6017 => TimelockNotElapsed,
+            6018 => TimelockElapsed,
+            6019 => InvalidTimelockDelay,
+            6020 => InstructionPaused,
+            6021 => InvalidPauseFlags,
+            6022 => InvalidRole,
+            6023 => RoleCapacityExceeded,
+            6024 => AccountNotMigrated,
+            6025 => AccountAlreadyMigrated,
+            6026 => FaucetCooldownActive,
+            6027 => FaucetWindowCapExceeded,
+            6028 => FaucetDailyBudgetExceeded,
+            6029 => InvalidFaucetLimits,
+            6030 => FaucetBudgetExceeded,
+            6031 => FaucetUnavailable,
+            6032 => InvalidFaucetGate,
+            6033 => FaucetProofMismatch,
+            6034 => AttestationMissing,
+            6035 => AttestationMismatch,
+            6036 => AttestationExpired,
+            6037 => InvalidRetargetConfig,
+            6038 => InvalidTaskConfig,
+            6039 => TaskNotOpen,
+            6040 => TaskClosed,
+            6041 => TaskFull,
+            6042 => ProofTaskTooOld,
+            6043 => InvalidProofStakePolicy,
+            6044 => InsufficientProofStake,
+            6045 => InvalidPowChallengeConfig,
+            6046 => PowChallengeMissing,
+            6047 => PowChallengeExpired,
+            6048 => InvalidPowHashAlgorithm,
+            6049 => InvalidProofBatch,
+            6050 => InvalidOracleSet,
+            6051 => OracleThresholdNotMet,
+            6052 => ChangeExpired,
+            _ => return None,
+        })
+    }
+
+    /// Extracts the program error from a failed transaction, if it was one of ours.
+    pub fn from_transaction_error(err: &TransactionError) -> Option<Self> {
+        match err {
+            TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+                Self::from_code(*code)
+            }
+            _ => None,
+        }
+    }
+}
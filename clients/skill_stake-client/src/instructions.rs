@@ -0,0 +1,741 @@
+//! Instruction builders. Account order matches the program's `#[derive(Accounts)]` structs;
+//! PDAs are derived here so callers only pass wallets, mints and token accounts.
+
+use borsh::BorshSerialize;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::{ed25519_program, system_program, sysvar};
+
+#[cfg(feature = "devnet")]
+use crate::FaucetProof;
+use crate::{
+    discriminator, pda, record_proofs_compute_units, ParamChange, PowRetarget, ProofAttestation,
+    ProofStakePolicy, ProofSubmission, TaskParams, ID,
+};
+
+fn build(
+    name: &str,
+    args: impl BorshSerialize,
+    mut accounts: Vec<AccountMeta>,
+    emits: bool,
+) -> Instruction {
+    let mut data = discriminator("global", name).to_vec();
+    args.serialize(&mut data)
+        .expect("instruction args serialize into a Vec");
+    if emits {
+        accounts.push(AccountMeta::new_readonly(pda::event_authority().0, false));
+        accounts.push(AccountMeta::new_readonly(ID, false));
+    }
+    Instruction {
+        program_id: ID,
+        accounts,
+        data,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn initialize(
+    admin: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    apr_bps: u64,
+    faucet_cap: u64,
+    pow_reward: u64,
+    pow_difficulty: u8,
+    oracle_authority: &Pubkey,
+) -> Instruction {
+    build(
+        "initialize",
+        (
+            apr_bps,
+            faucet_cap,
+            pow_reward,
+            pow_difficulty,
+            *oracle_authority,
+        ),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(pda::state().0, false),
+            AccountMeta::new_readonly(pda::mint_auth().0, false),
+            AccountMeta::new(pda::vault().0, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+        true,
+    )
+}
+
+pub fn initialize_roles(admin: &Pubkey) -> Instruction {
+    build(
+        "initialize_roles",
+        (),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(pda::state().0, false),
+            AccountMeta::new(pda::roles().0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        true,
+    )
+}
+
+fn manage_roles_accounts(admin: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(pda::state().0, false),
+        AccountMeta::new(pda::roles().0, false),
+    ]
+}
+
+pub fn grant_role(admin: &Pubkey, member: &Pubkey, permissions: u8) -> Instruction {
+    build(
+        "grant_role",
+        (*member, permissions),
+        manage_roles_accounts(admin),
+        true,
+    )
+}
+
+pub fn revoke_role(admin: &Pubkey, member: &Pubkey, permissions: u8) -> Instruction {
+    build(
+        "revoke_role",
+        (*member, permissions),
+        manage_roles_accounts(admin),
+        true,
+    )
+}
+
+pub fn initialize_oracle_set(admin: &Pubkey, members: &[Pubkey], threshold: u8) -> Instruction {
+    build(
+        "initialize_oracle_set",
+        (members.to_vec(), threshold),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(pda::state().0, false),
+            AccountMeta::new(pda::oracle_set().0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        true,
+    )
+}
+
+fn manage_oracle_set_accounts(admin: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new_readonly(pda::state().0, false),
+        AccountMeta::new(pda::oracle_set().0, false),
+    ]
+}
+
+pub fn add_oracle_member(admin: &Pubkey, member: &Pubkey) -> Instruction {
+    build(
+        "add_oracle_member",
+        (*member,),
+        manage_oracle_set_accounts(admin),
+        true,
+    )
+}
+
+pub fn remove_oracle_member(admin: &Pubkey, member: &Pubkey) -> Instruction {
+    build(
+        "remove_oracle_member",
+        (*member,),
+        manage_oracle_set_accounts(admin),
+        true,
+    )
+}
+
+pub fn set_oracle_threshold(admin: &Pubkey, threshold: u8) -> Instruction {
+    build(
+        "set_oracle_threshold",
+        (threshold,),
+        manage_oracle_set_accounts(admin),
+        true,
+    )
+}
+
+fn role_update_accounts(authority: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(*authority, true),
+        AccountMeta::new(pda::state().0, false),
+        AccountMeta::new_readonly(pda::roles().0, false),
+    ]
+}
+
+pub fn set_apr(authority: &Pubkey, apr_bps: u64) -> Instruction {
+    build("set_apr", (apr_bps,), role_update_accounts(authority), true)
+}
+
+#[cfg(feature = "devnet")]
+pub fn update_faucet_cap(authority: &Pubkey, faucet_cap: u64) -> Instruction {
+    build(
+        "update_faucet_cap",
+        (faucet_cap,),
+        role_update_accounts(authority),
+        true,
+    )
+}
+
+/// Zero disables each limit; `window` and `window_cap` must be set together.
+#[cfg(feature = "devnet")]
+pub fn set_faucet_limits(
+    authority: &Pubkey,
+    cooldown: i64,
+    window: i64,
+    window_cap: u64,
+    daily_budget: u64,
+) -> Instruction {
+    build(
+        "set_faucet_limits",
+        (cooldown, window, window_cap, daily_budget),
+        role_update_accounts(authority),
+        true,
+    )
+}
+
+/// `gate` is one of the `FAUCET_GATE_*` modes; `pow_difficulty` applies to `FAUCET_GATE_POW`.
+#[cfg(feature = "devnet")]
+pub fn set_faucet_gate(authority: &Pubkey, gate: u8, pow_difficulty: u8) -> Instruction {
+    build(
+        "set_faucet_gate",
+        (gate, pow_difficulty),
+        role_update_accounts(authority),
+        true,
+    )
+}
+
+#[cfg(feature = "devnet")]
+pub fn top_up_faucet_budget(authority: &Pubkey, amount: u64) -> Instruction {
+    build(
+        "top_up_faucet_budget",
+        (amount,),
+        role_update_accounts(authority),
+        true,
+    )
+}
+
+pub fn set_paused(authority: &Pubkey, paused: u8) -> Instruction {
+    build(
+        "set_paused",
+        (paused,),
+        role_update_accounts(authority),
+        true,
+    )
+}
+
+pub fn set_oracle_authority(admin: &Pubkey, new_authority: &Pubkey) -> Instruction {
+    build(
+        "set_oracle_authority",
+        (*new_authority,),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(pda::state().0, false),
+        ],
+        true,
+    )
+}
+
+/// `change_id` must equal `GlobalState::change_count` at submission time.
+pub fn queue_change(
+    authority: &Pubkey,
+    change_id: u64,
+    change: ParamChange,
+    eta: i64,
+) -> Instruction {
+    build(
+        "queue_change",
+        (change, eta),
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(pda::state().0, false),
+            AccountMeta::new_readonly(pda::roles().0, false),
+            AccountMeta::new(pda::pending_change(change_id).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        true,
+    )
+}
+
+pub fn execute_change(executor: &Pubkey, proposer: &Pubkey, change_id: u64) -> Instruction {
+    build(
+        "execute_change",
+        (),
+        vec![
+            AccountMeta::new_readonly(*executor, true),
+            AccountMeta::new(pda::state().0, false),
+            AccountMeta::new(*proposer, false),
+            AccountMeta::new(pda::pending_change(change_id).0, false),
+        ],
+        true,
+    )
+}
+
+pub fn cancel_change(admin: &Pubkey, proposer: &Pubkey, change_id: u64) -> Instruction {
+    build(
+        "cancel_change",
+        (),
+        vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new_readonly(pda::state().0, false),
+            AccountMeta::new(*proposer, false),
+            AccountMeta::new(pda::pending_change(change_id).0, false),
+        ],
+        true,
+    )
+}
+
+pub fn migrate_state(admin: &Pubkey) -> Instruction {
+    build(
+        "migrate_state",
+        (),
+        vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(pda::state().0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        true,
+    )
+}
+
+pub fn migrate_stake_account(payer: &Pubkey, owner: &Pubkey) -> Instruction {
+    build(
+        "migrate_stake_account",
+        (),
+        vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new(pda::stake_account(owner).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        true,
+    )
+}
+
+pub fn create_task(authority: &Pubkey, task_id: u64, params: TaskParams) -> Instruction {
+    build(
+        "create_task",
+        (task_id, params),
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(pda::state().0, false),
+            AccountMeta::new_readonly(pda::roles().0, false),
+            AccountMeta::new_readonly(pda::oracle_set().0, false),
+            AccountMeta::new(pda::task(task_id).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        true,
+    )
+}
+
+/// Precede this with an ed25519 check of the oracle's signature over `task_message`
+/// (`ed25519_verify`), or the oracle set members' (`ed25519_verify_many`). Any `relayer` may sign
+/// and pays the task's rent.
+pub fn relay_create_task(relayer: &Pubkey, task_id: u64, params: TaskParams) -> Instruction {
+    build(
+        "relay_create_task",
+        (task_id, params),
+        vec![
+            AccountMeta::new(*relayer, true),
+            AccountMeta::new_readonly(pda::state().0, false),
+            AccountMeta::new_readonly(pda::oracle_set().0, false),
+            AccountMeta::new_readonly(sysvar::instructions::ID, false),
+            AccountMeta::new(pda::task(task_id).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        true,
+    )
+}
+
+/// Under an oracle set, `authority` only relays: precede this with an `ed25519_verify_many` of the
+/// members' signatures over `pow_config_message`.
+pub fn set_pow_config(
+    authority: &Pubkey,
+    pow_difficulty: u8,
+    pow_reward: u64,
+    oracle_nonce: u64,
+) -> Instruction {
+    build(
+        "set_pow_config",
+        (pow_difficulty, pow_reward, oracle_nonce),
+        vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(pda::state().0, false),
+            AccountMeta::new_readonly(pda::roles().0, false),
+            AccountMeta::new_readonly(pda::oracle_set().0, false),
+            AccountMeta::new_readonly(sysvar::instructions::ID, false),
+        ],
+        true,
+    )
+}
+
+/// Precede this with an ed25519 check of the oracle's signature over `relayed_pow_config_message`
+/// (`ed25519_verify`), or the oracle set members' (`ed25519_verify_many`). Any `relayer` may sign.
+pub fn relay_pow_config(
+    relayer: &Pubkey,
+    pow_difficulty: u8,
+    pow_reward: u64,
+    oracle_nonce: u64,
+    expiry: i64,
+) -> Instruction {
+    build(
+        "relay_pow_config",
+        (pow_difficulty, pow_reward, oracle_nonce, expiry),
+        vec![
+            AccountMeta::new_readonly(*relayer, true),
+            AccountMeta::new(pda::state().0, false),
+            AccountMeta::new_readonly(pda::oracle_set().0, false),
+            AccountMeta::new_readonly(sysvar::instructions::ID, false),
+        ],
+        true,
+    )
+}
+
+pub fn set_pow_retarget(authority: &Pubkey, retarget: PowRetarget) -> Instruction {
+    build(
+        "set_pow_retarget",
+        (retarget,),
+        role_update_accounts(authority),
+        true,
+    )
+}
+
+pub fn set_proof_stake_policy(authority: &Pubkey, policy: ProofStakePolicy) -> Instruction {
+    build(
+        "set_proof_stake_policy",
+        (policy,),
+        role_update_accounts(authority),
+        true,
+    )
+}
+
+pub fn set_pow_challenge(authority: &Pubkey, window_slots: u64) -> Instruction {
+    build(
+        "set_pow_challenge",
+        (window_slots,),
+        role_update_accounts(authority),
+        true,
+    )
+}
+
+pub fn set_pow_hash_algorithm(authority: &Pubkey, algorithm: u8) -> Instruction {
+    build(
+        "set_pow_hash_algorithm",
+        (algorithm,),
+        role_update_accounts(authority),
+        true,
+    )
+}
+
+pub fn set_proof_attestation(authority: &Pubkey, required: bool) -> Instruction {
+    build(
+        "set_proof_attestation",
+        (required,),
+        role_update_accounts(authority),
+        true,
+    )
+}
+
+pub fn stake(
+    user: &Pubkey,
+    mint: &Pubkey,
+    user_token: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        "stake",
+        (amount,),
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(pda::state().0, false),
+            AccountMeta::new(pda::vault().0, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(*user_token, false),
+            AccountMeta::new(pda::stake_account(user).0, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+        ],
+        true,
+    )
+}
+
+fn withdraw_accounts(
+    user: &Pubkey,
+    mint: &Pubkey,
+    user_token: &Pubkey,
+    token_program: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new(pda::state().0, false),
+        AccountMeta::new(pda::vault().0, false),
+        AccountMeta::new(*mint, false),
+        AccountMeta::new(*user_token, false),
+        AccountMeta::new(pda::stake_account(user).0, false),
+        AccountMeta::new_readonly(*token_program, false),
+    ]
+}
+
+pub fn unstake(
+    user: &Pubkey,
+    mint: &Pubkey,
+    user_token: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    build(
+        "unstake",
+        (amount,),
+        withdraw_accounts(user, mint, user_token, token_program),
+        true,
+    )
+}
+
+pub fn emergency_withdraw(
+    user: &Pubkey,
+    mint: &Pubkey,
+    user_token: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        "emergency_withdraw",
+        (),
+        withdraw_accounts(user, mint, user_token, token_program),
+        true,
+    )
+}
+
+pub fn claim(
+    user: &Pubkey,
+    mint: &Pubkey,
+    user_token: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    build(
+        "claim",
+        (),
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new_readonly(pda::state().0, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(pda::mint_auth().0, false),
+            AccountMeta::new(*user_token, false),
+            AccountMeta::new(pda::stake_account(user).0, false),
+            AccountMeta::new_readonly(*token_program, false),
+        ],
+        true,
+    )
+}
+
+#[cfg(feature = "devnet")]
+pub fn faucet(
+    user: &Pubkey,
+    mint: &Pubkey,
+    user_token: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+    proof: FaucetProof,
+) -> Instruction {
+    build(
+        "faucet",
+        (amount, proof),
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(pda::state().0, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(pda::mint_auth().0, false),
+            AccountMeta::new(*user_token, false),
+            AccountMeta::new(pda::stake_account(user).0, false),
+            AccountMeta::new_readonly(*token_program, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+            AccountMeta::new_readonly(pda::oracle_set().0, false),
+            AccountMeta::new_readonly(sysvar::instructions::ID, false),
+        ],
+        true,
+    )
+}
+
+/// Ed25519 precompile check of one `signature` by `signer` over `message`, with all data inline.
+/// The program reads it back from the instructions sysvar as an oracle attestation.
+pub fn ed25519_verify(signer: &Pubkey, signature: &Signature, message: &[u8]) -> Instruction {
+    ed25519_verify_many(&[(*signer, *signature)], message)
+}
+
+/// `ed25519_verify` for several signers of one `message`, as an oracle set's threshold needs.
+pub fn ed25519_verify_many(signatures: &[(Pubkey, Signature)], message: &[u8]) -> Instruction {
+    // count + padding, one 14-byte offsets record per signature, then each pubkey and signature,
+    // then the message they share.
+    const RECORD_SIZE: usize = 14;
+    const SIGNED_SIZE: usize = 32 + 64;
+    let count = u8::try_from(signatures.len()).expect("signature count fits the precompile");
+    let offset = |at: usize| u16::try_from(at).expect("data fits the precompile");
+    let header = 2 + signatures.len() * RECORD_SIZE;
+    let message_offset = offset(header + signatures.len() * SIGNED_SIZE);
+    let message_size = offset(message.len());
+
+    let mut data = vec![count, 0];
+    for index in 0..signatures.len() {
+        let pubkey_offset = header + index * SIGNED_SIZE;
+        for field in [
+            offset(pubkey_offset + 32),
+            u16::MAX,
+            offset(pubkey_offset),
+            u16::MAX,
+            message_offset,
+            message_size,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+    }
+    for (signer, signature) in signatures {
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(signature.as_ref());
+    }
+    data.extend_from_slice(message);
+    Instruction {
+        program_id: ed25519_program::ID,
+        accounts: Vec::new(),
+        data,
+    }
+}
+
+/// Pass an `attestation` only together with the matching `ed25519_verify` just before this.
+pub fn record_proof(
+    user: &Pubkey,
+    task_id: u64,
+    nonce: u64,
+    attestation: Option<ProofAttestation>,
+) -> Instruction {
+    build(
+        "record_proof",
+        (task_id, nonce, attestation),
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(pda::state().0, false),
+            AccountMeta::new(pda::stake_account(user).0, false),
+            AccountMeta::new(pda::task(task_id).0, false),
+            AccountMeta::new(pda::proof_history(user).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(pda::oracle_set().0, false),
+            AccountMeta::new_readonly(sysvar::instructions::ID, false),
+        ],
+        true,
+    )
+}
+
+/// Appends each proof's task account after the program's own accounts, in `proofs` order.
+pub fn record_proofs(user: &Pubkey, proofs: &[ProofSubmission]) -> Instruction {
+    let mut ix = build(
+        "record_proofs",
+        (proofs.to_vec(),),
+        vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(pda::state().0, false),
+            AccountMeta::new(pda::stake_account(user).0, false),
+            AccountMeta::new(pda::proof_history(user).0, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        true,
+    );
+    ix.accounts.extend(
+        proofs
+            .iter()
+            .map(|proof| AccountMeta::new(pda::task(proof.task_id).0, false)),
+    );
+    ix
+}
+
+/// `record_proofs` behind a compute-budget request sized for the batch under the pool's
+/// `algorithm`, so a full batch is not cut off by the default per-instruction limit.
+pub fn record_proofs_with_budget(
+    user: &Pubkey,
+    proofs: &[ProofSubmission],
+    algorithm: u8,
+) -> Vec<Instruction> {
+    vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(record_proofs_compute_units(
+            algorithm,
+            proofs.len(),
+        )),
+        record_proofs(user, proofs),
+    ]
+}
+
+// Views: simulate these and decode the return data with the matching type in `accounts`.
+
+fn preview_accounts(owner: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new_readonly(*owner, false),
+        AccountMeta::new_readonly(pda::state().0, false),
+        AccountMeta::new_readonly(pda::stake_account(owner).0, false),
+    ]
+}
+
+/// Returns `RewardsPreview`.
+pub fn preview_rewards(owner: &Pubkey) -> Instruction {
+    build("preview_rewards", (), preview_accounts(owner), false)
+}
+
+/// Returns `UnstakePreview`.
+pub fn preview_unstake(owner: &Pubkey, amount: u64) -> Instruction {
+    build("preview_unstake", (amount,), preview_accounts(owner), false)
+}
+
+/// Returns `ProofCheck`. Pass `has_stake_account = false` for wallets that never staked.
+pub fn check_proof(
+    user: &Pubkey,
+    task_id: u64,
+    nonce: u64,
+    has_stake_account: bool,
+) -> Instruction {
+    // Anchor encodes an omitted optional account as the program id.
+    let stake_account = if has_stake_account {
+        pda::stake_account(user).0
+    } else {
+        ID
+    };
+    build(
+        "check_proof",
+        (*user, task_id, nonce),
+        vec![
+            AccountMeta::new_readonly(pda::state().0, false),
+            AccountMeta::new_readonly(pda::task(task_id).0, false),
+            AccountMeta::new_readonly(stake_account, false),
+        ],
+        false,
+    )
+}
+
+pub fn commit_pow_challenge(user: &Pubkey) -> Instruction {
+    build(
+        "commit_pow_challenge",
+        (),
+        vec![
+            AccountMeta::new_readonly(*user, true),
+            AccountMeta::new_readonly(pda::state().0, false),
+            AccountMeta::new(pda::stake_account(user).0, false),
+            AccountMeta::new_readonly(sysvar::slot_hashes::ID, false),
+        ],
+        true,
+    )
+}
+
+/// Returns `PoolInfo`.
+pub fn get_pool_info() -> Instruction {
+    build(
+        "get_pool_info",
+        (),
+        vec![
+            AccountMeta::new_readonly(pda::state().0, false),
+            AccountMeta::new_readonly(pda::vault().0, false),
+        ],
+        false,
+    )
+}
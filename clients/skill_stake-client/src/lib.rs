@@ -0,0 +1,270 @@
+//! Rust client for the `skill_stake` program.
+//!
+//! Mirrors the on-chain seeds, instruction layouts, account layouts and error codes using only
+//! `solana-sdk`, so services do not have to link the Anchor program crate.
+
+pub mod accounts;
+pub mod errors;
+pub mod instructions;
+pub mod pda;
+
+pub use accounts::*;
+pub use errors::SkillStakeError;
+
+use solana_sdk::hash::hashv;
+use solana_sdk::keccak;
+use solana_sdk::pubkey::Pubkey;
+
+/// Program id of the `devnet` build of `programs/skill_stake`.
+pub const DEVNET_ID: Pubkey = solana_sdk::pubkey!("CpxZiQinN5NVPcFQGfAo6LKqHLWFoegSuR9dFyVaPYMu");
+/// Program id of the mainnet build; a placeholder until the program keypair exists.
+pub const MAINNET_ID: Pubkey = solana_sdk::pubkey!("SkiLLStakeMainnet11111111111111111111111111");
+
+/// The id every builder and PDA in this crate uses; the `devnet` feature selects `DEVNET_ID`.
+#[cfg(feature = "devnet")]
+pub const ID: Pubkey = DEVNET_ID;
+#[cfg(not(feature = "devnet"))]
+pub const ID: Pubkey = MAINNET_ID;
+
+pub const STATE_SEED: &[u8] = b"state";
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const MINT_AUTH_SEED: &[u8] = b"mint_auth";
+pub const STAKE_ACCOUNT_SEED: &[u8] = b"stake";
+pub const PENDING_CHANGE_SEED: &[u8] = b"pending_change";
+pub const PROOF_HISTORY_SEED: &[u8] = b"proof_history";
+pub const TASK_SEED: &[u8] = b"task";
+pub const ROLES_SEED: &[u8] = b"roles";
+pub const ORACLE_SET_SEED: &[u8] = b"oracle_set";
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const SECONDS_PER_YEAR: i64 = 31_536_000;
+pub const SECONDS_PER_DAY: i64 = 86_400;
+pub const MAX_POW_DIFFICULTY: u8 = 248;
+pub const MAX_PROOF_HISTORY: usize = 32;
+pub const PROOF_REPLAY_WINDOW: u64 = 256;
+pub const MAX_PROOF_BOOST_BPS: u64 = BPS_DENOMINATOR * 4;
+pub const MAX_POW_CHALLENGE_SLOTS: u64 = 512;
+pub const MAX_PROOF_BATCH: usize = 8;
+/// Compute units `record_proofs` spends outside its per-proof loop: account checks, reward accrual,
+/// the `ProofHistory` write and a possible retarget event. Estimated from the solana 1.18 cost
+/// table with headroom, not metered on the SBF binary.
+pub const RECORD_PROOFS_BASE_COMPUTE_UNITS: u32 = 80_000;
+/// Estimated compute units per proof under keccak or sha256: the hash syscall, the task load and
+/// store, and the `ProofRecorded` self-CPI (invoke, signer-seed derivation and event dispatch).
+pub const PROOF_COMPUTE_UNITS: u32 = 25_000;
+/// Estimated compute units per proof under blake3, which hashes in-program.
+pub const BLAKE3_PROOF_COMPUTE_UNITS: u32 = 60_000;
+pub const MAX_ORACLE_MEMBERS: usize = 8;
+pub const POW_DOMAIN: &[u8] = b"skillstake_pow";
+pub const FAUCET_POW_DOMAIN: &[u8] = b"skillstake_pow_faucet";
+pub const FAUCET_ATTESTATION_DOMAIN: &[u8] = b"skillstake_faucet_attestation";
+pub const PROOF_ATTESTATION_DOMAIN: &[u8] = b"skillstake_proof_attestation";
+pub const POW_CONFIG_DOMAIN: &[u8] = b"skillstake_pow_config";
+pub const RELAYED_POW_CONFIG_DOMAIN: &[u8] = b"skillstake_relayed_pow_config";
+pub const TASK_DOMAIN: &[u8] = b"skillstake_task";
+
+pub const POW_HASH_KECCAK256: u8 = 0;
+pub const POW_HASH_SHA256: u8 = 1;
+pub const POW_HASH_BLAKE3: u8 = 2;
+
+pub const FAUCET_GATE_NONE: u8 = 0;
+pub const FAUCET_GATE_POW: u8 = 1;
+pub const FAUCET_GATE_ORACLE: u8 = 2;
+
+pub const PAUSE_STAKE: u8 = 1 << 0;
+pub const PAUSE_CLAIM: u8 = 1 << 1;
+pub const PAUSE_FAUCET: u8 = 1 << 2;
+pub const PAUSE_RECORD_PROOF: u8 = 1 << 3;
+
+pub const ROLE_FAUCET_MANAGER: u8 = 1 << 0;
+pub const ROLE_PAUSER: u8 = 1 << 1;
+pub const ROLE_APR_MANAGER: u8 = 1 << 2;
+pub const ROLE_POW_MANAGER: u8 = 1 << 3;
+pub const ROLE_TREASURY_MANAGER: u8 = 1 << 4;
+
+/// True when `hash` starts with at least `difficulty` zero bits.
+pub fn meets_difficulty(hash: &[u8; 32], difficulty: u8) -> bool {
+    let difficulty = u32::from(difficulty);
+    let mut zeros = 0;
+    for byte in hash {
+        zeros += byte.leading_zeros();
+        if zeros >= difficulty || *byte != 0 {
+            break;
+        }
+    }
+    zeros >= difficulty
+}
+
+/// Proof-of-work hash `record_proof` checks under the pool's `pow_hash_algorithm`.
+pub fn pow_hash(algorithm: u8, user: &Pubkey, mint: &Pubkey, task_id: u64, nonce: u64) -> [u8; 32] {
+    pow_hashv(
+        algorithm,
+        &[
+            POW_DOMAIN,
+            user.as_ref(),
+            mint.as_ref(),
+            &task_id.to_le_bytes(),
+            &nonce.to_le_bytes(),
+        ],
+    )
+}
+
+/// `pow_hash` under challenge mode; `slot_hash` is the wallet's `pow_challenge_hash`.
+pub fn challenge_pow_hash(
+    algorithm: u8,
+    user: &Pubkey,
+    mint: &Pubkey,
+    task_id: u64,
+    slot_hash: &[u8; 32],
+    nonce: u64,
+) -> [u8; 32] {
+    pow_hashv(
+        algorithm,
+        &[
+            POW_DOMAIN,
+            user.as_ref(),
+            mint.as_ref(),
+            &task_id.to_le_bytes(),
+            slot_hash,
+            &nonce.to_le_bytes(),
+        ],
+    )
+}
+
+fn pow_hashv(algorithm: u8, parts: &[&[u8]]) -> [u8; 32] {
+    match algorithm {
+        POW_HASH_SHA256 => hashv(parts).to_bytes(),
+        POW_HASH_BLAKE3 => solana_sdk::blake3::hashv(parts).to_bytes(),
+        _ => keccak::hashv(parts).0,
+    }
+}
+
+/// Most proofs one `record_proofs` accepts under `algorithm`.
+pub fn max_proof_batch(algorithm: u8) -> usize {
+    if algorithm == POW_HASH_BLAKE3 {
+        MAX_PROOF_BATCH / 2
+    } else {
+        MAX_PROOF_BATCH
+    }
+}
+
+/// Compute-unit limit for a `record_proofs` batch of `proofs` under `algorithm`.
+pub fn record_proofs_compute_units(algorithm: u8, proofs: usize) -> u32 {
+    let per_proof = if algorithm == POW_HASH_BLAKE3 {
+        BLAKE3_PROOF_COMPUTE_UNITS
+    } else {
+        PROOF_COMPUTE_UNITS
+    };
+    RECORD_PROOFS_BASE_COMPUTE_UNITS + per_proof * proofs as u32
+}
+
+/// Faucet proof-of-work hash; `faucet_claimed` is the wallet's claimed total before this claim.
+pub fn faucet_pow_hash(user: &Pubkey, mint: &Pubkey, faucet_claimed: u64, nonce: u64) -> [u8; 32] {
+    keccak::hashv(&[
+        FAUCET_POW_DOMAIN,
+        user.as_ref(),
+        mint.as_ref(),
+        &faucet_claimed.to_le_bytes(),
+        &nonce.to_le_bytes(),
+    ])
+    .0
+}
+
+/// Message the oracle signs for an oracle-gated faucet claim.
+pub fn faucet_attestation_message(
+    user: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    faucet_claimed: u64,
+    expiry: i64,
+) -> Vec<u8> {
+    [
+        FAUCET_ATTESTATION_DOMAIN,
+        user.as_ref(),
+        mint.as_ref(),
+        &amount.to_le_bytes(),
+        &faucet_claimed.to_le_bytes(),
+        &expiry.to_le_bytes(),
+    ]
+    .concat()
+}
+
+/// Message the oracle signs to pay `reward` for `task_id`; see `ProofAttestation`.
+pub fn proof_attestation_message(
+    user: &Pubkey,
+    mint: &Pubkey,
+    task_id: u64,
+    reward: u64,
+    expiry: i64,
+) -> Vec<u8> {
+    [
+        PROOF_ATTESTATION_DOMAIN,
+        user.as_ref(),
+        mint.as_ref(),
+        &task_id.to_le_bytes(),
+        &reward.to_le_bytes(),
+        &expiry.to_le_bytes(),
+    ]
+    .concat()
+}
+
+/// Message the oracle set signs for `set_pow_config`; see `instructions::ed25519_verify_many`.
+pub fn pow_config_message(
+    mint: &Pubkey,
+    pow_difficulty: u8,
+    pow_reward: u64,
+    oracle_nonce: u64,
+) -> Vec<u8> {
+    [
+        POW_CONFIG_DOMAIN,
+        mint.as_ref(),
+        &[pow_difficulty],
+        &pow_reward.to_le_bytes(),
+        &oracle_nonce.to_le_bytes(),
+    ]
+    .concat()
+}
+
+/// Message the oracle, or its oracle set, signs off-chain for `relay_pow_config`.
+pub fn relayed_pow_config_message(
+    mint: &Pubkey,
+    pow_difficulty: u8,
+    pow_reward: u64,
+    oracle_nonce: u64,
+    expiry: i64,
+) -> Vec<u8> {
+    [
+        RELAYED_POW_CONFIG_DOMAIN,
+        mint.as_ref(),
+        &[pow_difficulty],
+        &pow_reward.to_le_bytes(),
+        &oracle_nonce.to_le_bytes(),
+        &expiry.to_le_bytes(),
+    ]
+    .concat()
+}
+
+/// Message the oracle, or its oracle set, signs off-chain for `relay_create_task`.
+pub fn task_message(mint: &Pubkey, task_id: u64, params: &TaskParams) -> Vec<u8> {
+    [
+        TASK_DOMAIN,
+        mint.as_ref(),
+        &task_id.to_le_bytes(),
+        &params.description_hash,
+        &params.reward.to_le_bytes(),
+        &[params.difficulty],
+        &params.opens_at.to_le_bytes(),
+        &params.closes_at.to_le_bytes(),
+        &params.max_solvers.to_le_bytes(),
+    ]
+    .concat()
+}
+
+/// Anchor discriminator: first 8 bytes of `sha256("<namespace>:<name>")`.
+pub fn discriminator(namespace: &str, name: &str) -> [u8; 8] {
+    let hash = hashv(&[namespace.as_bytes(), b":", name.as_bytes()]);
+    let mut out = [0u8; 8];
+    out.copy_from_slice(&hash.to_bytes()[..8]);
+    out
+}
@@ -0,0 +1,49 @@
+//! Program-derived addresses, one helper per seed.
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    EVENT_AUTHORITY_SEED, ID, MINT_AUTH_SEED, ORACLE_SET_SEED, PENDING_CHANGE_SEED,
+    PROOF_HISTORY_SEED, ROLES_SEED, STAKE_ACCOUNT_SEED, STATE_SEED, TASK_SEED, VAULT_SEED,
+};
+
+pub fn state() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STATE_SEED], &ID)
+}
+
+pub fn vault() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VAULT_SEED], &ID)
+}
+
+pub fn mint_auth() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MINT_AUTH_SEED], &ID)
+}
+
+pub fn stake_account(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[STAKE_ACCOUNT_SEED, owner.as_ref()], &ID)
+}
+
+pub fn proof_history(owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROOF_HISTORY_SEED, owner.as_ref()], &ID)
+}
+
+pub fn task(task_id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TASK_SEED, &task_id.to_le_bytes()], &ID)
+}
+
+pub fn pending_change(id: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PENDING_CHANGE_SEED, &id.to_le_bytes()], &ID)
+}
+
+pub fn roles() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ROLES_SEED], &ID)
+}
+
+pub fn oracle_set() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ORACLE_SET_SEED], &ID)
+}
+
+/// Signer PDA used by `emit_cpi!`; every non-view instruction takes it.
+pub fn event_authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], &ID)
+}
@@ -1,33 +1,153 @@
 import express from "express";
+import { Connection, PublicKey } from "@solana/web3.js";
+import { readFileSync } from "node:fs";
+import { timingSafeEqual } from "node:crypto";
+import nacl from "tweetnacl";
 
 const PORT = Number(process.env.PORT ?? 8787);
+// Solana keypair file (64-byte JSON array) of the pool's oracle_authority.
+const KEYPAIR_PATH = process.env.ORACLE_KEYPAIR ?? "oracle-keypair.json";
+// Callers of /attest send it as `Authorization: Bearer <key>`.
+const API_KEY = process.env.ORACLE_API_KEY;
+const RPC_URL = process.env.RPC_URL ?? "https://api.devnet.solana.com";
+const PROGRAM_ID = new PublicKey(
+  process.env.SKILL_STAKE_PROGRAM_ID ?? "CpxZiQinN5NVPcFQGfAo6LKqHLWFoegSuR9dFyVaPYMu"
+);
+// Seconds an attestation stays valid.
+const ATTESTATION_TTL = BigInt(process.env.ATTESTATION_TTL ?? "300");
+
+const PROOF_ATTESTATION_DOMAIN = Buffer.from("skillstake_proof_attestation");
+
+// Byte offsets, discriminator included, of the account fields read below.
+const STATE_MINT_OFFSET = 8 + 32 * 2;
+const STATE_POW_REWARD_OFFSET = 8 + 32 * 4 + 8 * 3;
+const TASK_REWARD_OFFSET = 8 + 8 + 32;
+const TASK_OPENS_AT_OFFSET = TASK_REWARD_OFFSET + 8;
+const TASK_CLOSES_AT_OFFSET = TASK_OPENS_AT_OFFSET + 8;
+const TASK_MAX_SOLVERS_OFFSET = TASK_CLOSES_AT_OFFSET + 8;
+const TASK_SOLVERS_OFFSET = TASK_MAX_SOLVERS_OFFSET + 4;
+
+if (!API_KEY) {
+  throw new Error("ORACLE_API_KEY is not set");
+}
+
+const keypair = nacl.sign.keyPair.fromSecretKey(
+  Uint8Array.from(JSON.parse(readFileSync(KEYPAIR_PATH, "utf8")))
+);
+const oracle = new PublicKey(keypair.publicKey).toBase58();
+const connection = new Connection(RPC_URL, "confirmed");
+const [statePda] = PublicKey.findProgramAddressSync([Buffer.from("state")], PROGRAM_ID);
+
+function u64(value: bigint): Buffer {
+  const out = Buffer.alloc(8);
+  out.writeBigUInt64LE(value);
+  return out;
+}
+
+function i64(value: bigint): Buffer {
+  const out = Buffer.alloc(8);
+  out.writeBigInt64LE(value);
+  return out;
+}
+
+function isAuthorized(header: string | undefined): boolean {
+  const expected = Buffer.from(`Bearer ${API_KEY}`);
+  const actual = Buffer.from(header ?? "");
+  return actual.length === expected.length && timingSafeEqual(actual, expected);
+}
+
+async function programAccount(address: PublicKey): Promise<Buffer | null> {
+  const account = await connection.getAccountInfo(address);
+  return account && account.owner.equals(PROGRAM_ID) ? account.data : null;
+}
+
+// The reward an attestation for `taskId` may carry, or why the task cannot be attested now.
+async function taskReward(taskId: bigint): Promise<{ mint: Buffer; reward: bigint } | string> {
+  const [taskPda] = PublicKey.findProgramAddressSync([Buffer.from("task"), u64(taskId)], PROGRAM_ID);
+  const [state, task] = await Promise.all([programAccount(statePda), programAccount(taskPda)]);
+  if (!state) return "Pool state not found";
+  if (!task) return `Task ${taskId} is not registered`;
+
+  const now = BigInt(Math.floor(Date.now() / 1000));
+  if (now < task.readBigInt64LE(TASK_OPENS_AT_OFFSET)) return `Task ${taskId} is not open yet`;
+  if (now >= task.readBigInt64LE(TASK_CLOSES_AT_OFFSET)) return `Task ${taskId} is closed`;
+  const maxSolvers = task.readUInt32LE(TASK_MAX_SOLVERS_OFFSET);
+  if (maxSolvers > 0 && task.readUInt32LE(TASK_SOLVERS_OFFSET) >= maxSolvers) {
+    return `Task ${taskId} is full`;
+  }
+
+  // A task reward of zero follows the pool's `pow_reward`, as in the program.
+  const reward = task.readBigUInt64LE(TASK_REWARD_OFFSET) || state.readBigUInt64LE(STATE_POW_REWARD_OFFSET);
+  return { mint: state.subarray(STATE_MINT_OFFSET, STATE_MINT_OFFSET + 32), reward };
+}
+
+// Mirrors `proof_attestation_message` in the program.
+function proofAttestationMessage(
+  user: Buffer,
+  mint: Buffer,
+  taskId: bigint,
+  reward: bigint,
+  expiry: bigint
+): Buffer {
+  return Buffer.concat([PROOF_ATTESTATION_DOMAIN, user, mint, u64(taskId), u64(reward), i64(expiry)]);
+}
 
 const app = express();
 app.use(express.json());
 
 app.get("/health", (_req, res) => {
-  res.json({ ok: true, timestamp: Date.now() });
+  res.json({ ok: true, oracle, timestamp: Date.now() });
 });
 
-app.post("/attest", (req, res) => {
+// Signs `record_proof` attestations for authenticated callers, at the reward the registered task
+// pays, while the task is open and has room. Checking that the caller solved the task is left to
+// whoever holds the API key.
+app.post("/attest", async (req, res) => {
+  if (!isAuthorized(req.get("authorization"))) {
+    return res.status(401).json({ error: "Unauthorized" });
+  }
+
   const { user, taskId } = req.body ?? {};
-  if (!user || !taskId) {
+  if (!user || taskId === undefined) {
     return res.status(400).json({
       error: "Missing user or taskId"
     });
   }
 
-  // Placeholder payload for future oracle signature workflow
+  let userKey: PublicKey;
+  let task: bigint;
+  try {
+    userKey = new PublicKey(user);
+    task = BigInt(taskId);
+    u64(task); // throws outside the u64 range
+  } catch (error) {
+    return res.status(400).json({
+      error: error instanceof Error ? error.message : "Invalid request"
+    });
+  }
+
+  let lookup: Awaited<ReturnType<typeof taskReward>>;
+  try {
+    lookup = await taskReward(task);
+  } catch (error) {
+    return res.status(502).json({
+      error: error instanceof Error ? error.message : "RPC request failed"
+    });
+  }
+  if (typeof lookup === "string") {
+    return res.status(409).json({ error: lookup });
+  }
+
+  const expiry = BigInt(Math.floor(Date.now() / 1000)) + ATTESTATION_TTL;
+  const message = proofAttestationMessage(userKey.toBuffer(), lookup.mint, task, lookup.reward, expiry);
+  const signature = nacl.sign.detached(message, keypair.secretKey);
   return res.json({
-    payload: Buffer.from(
-      JSON.stringify({
-        user,
-        taskId,
-        verified: false,
-        issuedAt: Date.now()
-      })
-    ).toString("base64"),
-    sig: "BASE64_SIGNATURE_PENDING"
+    oracle,
+    taskId: task.toString(),
+    reward: lookup.reward.toString(),
+    expiry: expiry.toString(),
+    message: message.toString("base64"),
+    sig: Buffer.from(signature).toString("base64")
   });
 });
 
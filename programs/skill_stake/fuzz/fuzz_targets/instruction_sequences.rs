@@ -0,0 +1,108 @@
+//! Random instruction sequences against the pool's accounting invariants.
+//!
+//! Run with `cargo fuzz run instruction_sequences` from `programs/skill_stake`. Program errors are
+//! expected and ignored; a crash means an invariant broke or the runtime caught an illegal write.
+
+#![no_main]
+
+#[path = "../../tests/common/mod.rs"]
+mod common;
+
+use arbitrary::Arbitrary;
+use common::*;
+use libfuzzer_sys::fuzz_target;
+use skill_stake_client::{instructions, pda};
+use solana_sdk::signature::Signer;
+
+const USERS: usize = 3;
+const MAX_OPS: usize = 64;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Stake { user: u8, amount: u64 },
+    Unstake { user: u8, amount: u64 },
+    Claim { user: u8 },
+    EmergencyWithdraw { user: u8 },
+    Faucet { user: u8, amount: u64 },
+    RecordProof { user: u8, task_id: u64, nonce: u64 },
+    Warp { seconds: u32 },
+    SetApr { apr_bps: u16 },
+}
+
+/// Up to `limit` plus one, so amounts occasionally exceed what is available.
+fn bounded(amount: u64, limit: u64) -> u64 {
+    amount % limit.saturating_add(2)
+}
+
+fn check_invariants(pool: &Pool, users: &[User]) {
+    let state = pool.state();
+    let vault = pool.vault_balance();
+    assert!(
+        vault >= state.total_staked,
+        "vault {vault} < total_staked {}",
+        state.total_staked
+    );
+
+    let mut staked = 0u64;
+    let mut held = 0u64;
+    for user in users {
+        if pool
+            .svm
+            .account(&pda::stake_account(&user.key()).0)
+            .is_some()
+        {
+            staked += pool.stake_account(&user.key()).amount_staked;
+        }
+        held += pool.svm.token_balance(&user.token);
+    }
+    assert_eq!(staked, state.total_staked);
+    assert!(state.faucet_minted <= state.faucet_budget);
+    // Tokens are only ever minted to users, so everything in circulation is held or staked.
+    assert_eq!(pool.svm.mint_supply(&pool.mint), held + vault);
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    let mut pool = Pool::with_config(Config {
+        pow_difficulty: 0,
+        ..Config::default()
+    });
+    let users: Vec<User> = (0..USERS).map(|_| pool.user(1_000 * TOKEN)).collect();
+    let admin = pool.admin.insecure_clone();
+
+    for op in ops.into_iter().take(MAX_OPS) {
+        let _ = match op {
+            Op::Stake { user, amount } => {
+                let user = &users[user as usize % USERS];
+                let balance = pool.svm.token_balance(&user.token);
+                pool.stake(user, bounded(amount, balance))
+            }
+            Op::Unstake { user, amount } => {
+                let user = &users[user as usize % USERS];
+                let staked = pool.state().total_staked;
+                pool.unstake(user, bounded(amount, staked))
+            }
+            Op::Claim { user } => pool.claim(&users[user as usize % USERS]),
+            Op::EmergencyWithdraw { user } => {
+                pool.emergency_withdraw(&users[user as usize % USERS])
+            }
+            Op::Faucet { user, amount } => {
+                let cap = pool.state().faucet_cap;
+                pool.faucet(&users[user as usize % USERS], bounded(amount, cap))
+            }
+            Op::RecordProof {
+                user,
+                task_id,
+                nonce,
+            } => pool.record_proof(&users[user as usize % USERS], task_id, nonce),
+            Op::Warp { seconds } => {
+                pool.svm.warp(seconds as i64);
+                continue;
+            }
+            Op::SetApr { apr_bps } => pool.send(
+                &[instructions::set_apr(&admin.pubkey(), apr_bps as u64)],
+                &admin,
+            ),
+        };
+        check_invariants(&pool, &users);
+    }
+});
@@ -1,20 +1,144 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::{keccak, sysvar};
+use anchor_lang::system_program::{self, Transfer};
+use anchor_lang::Discriminator;
 use anchor_spl::token_interface::{self as token, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked};
 
+#[cfg(feature = "devnet")]
 declare_id!("CpxZiQinN5NVPcFQGfAo6LKqHLWFoegSuR9dFyVaPYMu");
+// Placeholder until the mainnet program keypair exists; `anchor keys sync` rewrites it.
+#[cfg(not(feature = "devnet"))]
+declare_id!("SkiLLStakeMainnet11111111111111111111111111");
 
 pub const STATE_SEED: &[u8] = b"state";
 pub const VAULT_SEED: &[u8] = b"vault";
 pub const MINT_AUTH_SEED: &[u8] = b"mint_auth";
 pub const STAKE_ACCOUNT_SEED: &[u8] = b"stake";
+pub const PENDING_CHANGE_SEED: &[u8] = b"pending_change";
+pub const PROOF_HISTORY_SEED: &[u8] = b"proof_history";
+pub const TASK_SEED: &[u8] = b"task";
 
 pub const SECONDS_PER_YEAR: i64 = 31_536_000; // 365 days
 pub const BPS_DENOMINATOR: u64 = 10_000;
+/// Highest configurable APR; devnet demo pools may go up to 500%.
+#[cfg(feature = "devnet")]
+pub const MAX_APR_BPS: u64 = BPS_DENOMINATOR * 5;
+#[cfg(not(feature = "devnet"))]
+pub const MAX_APR_BPS: u64 = BPS_DENOMINATOR;
 pub const MAX_POW_DIFFICULTY: u8 = 248;
 pub const MAX_PROOF_STORAGE: usize = 64;
+pub const MAX_PROOF_HISTORY: usize = 32;
+/// Tasks this far or further below a user's highest recorded task can no longer be submitted.
+pub const PROOF_REPLAY_WINDOW: u64 = 256;
+/// Largest stake boost on PoW rewards (+400%, i.e. 5x).
+pub const MAX_PROOF_BOOST_BPS: u64 = BPS_DENOMINATOR * 4;
+/// Longest challenge freshness window: the depth of the `SlotHashes` sysvar.
+pub const MAX_POW_CHALLENGE_SLOTS: u64 = 512;
+/// Proofs per `record_proofs`. Not yet metered on the SBF binary; from the solana 1.18 cost table a
+/// batch is estimated at 80k compute units plus 25k per keccak or sha256 proof (the hash syscall,
+/// the task load and store, and the `ProofRecorded` self-CPI) or 60k per blake3 proof, which hashes
+/// in-program. A full batch is then about 280k (320k for the halved blake3 batch), so the cap leaves
+/// the estimates at least a 4x margin under the 1.4M transaction limit. Clients size their compute-unit
+/// request from the batch length with the same figures.
+pub const MAX_PROOF_BATCH: usize = 8;
 pub const POW_DOMAIN: &[u8] = b"skillstake_pow";
+pub const FAUCET_POW_DOMAIN: &[u8] = b"skillstake_pow_faucet";
+pub const FAUCET_ATTESTATION_DOMAIN: &[u8] = b"skillstake_faucet_attestation";
+pub const PROOF_ATTESTATION_DOMAIN: &[u8] = b"skillstake_proof_attestation";
+pub const POW_CONFIG_DOMAIN: &[u8] = b"skillstake_pow_config";
+pub const RELAYED_POW_CONFIG_DOMAIN: &[u8] = b"skillstake_relayed_pow_config";
+pub const TASK_DOMAIN: &[u8] = b"skillstake_task";
+pub const MAX_TIMELOCK_DELAY: i64 = 30 * 86_400; // 30 days
+/// How long after its `eta` a queued change can still be executed; after that it can only be
+/// cancelled.
+pub const TIMELOCK_GRACE_PERIOD: i64 = 14 * 86_400; // 14 days
+pub const SECONDS_PER_DAY: i64 = 86_400;
+
+// `GlobalState::paused` bitflags
+pub const PAUSE_STAKE: u8 = 1 << 0;
+pub const PAUSE_CLAIM: u8 = 1 << 1;
+pub const PAUSE_FAUCET: u8 = 1 << 2;
+pub const PAUSE_RECORD_PROOF: u8 = 1 << 3;
+pub const PAUSE_ALL: u8 = PAUSE_STAKE | PAUSE_CLAIM | PAUSE_FAUCET | PAUSE_RECORD_PROOF;
+
+// `GlobalState::pow_hash_algorithm` values
+pub const POW_HASH_KECCAK256: u8 = 0;
+pub const POW_HASH_SHA256: u8 = 1;
+pub const POW_HASH_BLAKE3: u8 = 2;
+
+// `GlobalState::faucet_gate` modes
+pub const FAUCET_GATE_NONE: u8 = 0;
+pub const FAUCET_GATE_POW: u8 = 1;
+pub const FAUCET_GATE_ORACLE: u8 = 2;
+
+pub const ROLES_SEED: &[u8] = b"roles";
+pub const MAX_ROLE_MEMBERS: usize = 16;
+
+pub const ORACLE_SET_SEED: &[u8] = b"oracle_set";
+pub const MAX_ORACLE_MEMBERS: usize = 8;
+
+// `RoleMember::permissions` bitflags
+pub const ROLE_FAUCET_MANAGER: u8 = 1 << 0;
+pub const ROLE_PAUSER: u8 = 1 << 1;
+pub const ROLE_APR_MANAGER: u8 = 1 << 2;
+pub const ROLE_POW_MANAGER: u8 = 1 << 3;
+pub const ROLE_TREASURY_MANAGER: u8 = 1 << 4;
+pub const ROLE_ALL: u8 =
+    ROLE_FAUCET_MANAGER | ROLE_PAUSER | ROLE_APR_MANAGER | ROLE_POW_MANAGER | ROLE_TREASURY_MANAGER;
+
+/// Appends the devnet-only instructions, the faucet and its settings, to the `#[program]` module
+/// it wraps. Anchor 0.30 reads that module before `#[cfg]` is applied to the items inside it and
+/// would still dispatch a cfg'd instruction, so the choice is made here, once per build.
+#[cfg(feature = "devnet")]
+macro_rules! with_devnet_instructions {
+    (#[program] pub mod $name:ident { $($items:tt)* }) => {
+        #[program]
+        pub mod $name {
+            $($items)*
+
+            pub fn update_faucet_cap(ctx: Context<RoleUpdate>, faucet_cap: u64) -> Result<()> {
+                update_faucet_cap_handler(ctx, faucet_cap)
+            }
+
+            /// Per-wallet cooldown and rolling window plus the global daily budget; zero disables each.
+            pub fn set_faucet_limits(
+                ctx: Context<RoleUpdate>,
+                cooldown: i64,
+                window: i64,
+                window_cap: u64,
+                daily_budget: u64,
+            ) -> Result<()> {
+                set_faucet_limits_handler(ctx, cooldown, window, window_cap, daily_budget)
+            }
+
+            /// Selects what each faucet claim must carry: nothing, a PoW solution or an oracle attestation.
+            pub fn set_faucet_gate(ctx: Context<RoleUpdate>, gate: u8, pow_difficulty: u8) -> Result<()> {
+                set_faucet_gate_handler(ctx, gate, pow_difficulty)
+            }
+
+            /// Raises the lifetime faucet budget. The faucet mints nothing until the first top-up.
+            pub fn top_up_faucet_budget(ctx: Context<RoleUpdate>, amount: u64) -> Result<()> {
+                top_up_faucet_budget_handler(ctx, amount)
+            }
+
+            /// Mints `amount` to the caller within the faucet cap, rate limits and budget. `proof`
+            /// must satisfy the configured `faucet_gate`.
+            pub fn faucet(ctx: Context<Faucet>, amount: u64, proof: FaucetProof) -> Result<()> {
+                faucet_handler(ctx, amount, proof)
+            }
+        }
+    };
+}
+
+/// Mainnet builds have no faucet instructions.
+#[cfg(not(feature = "devnet"))]
+macro_rules! with_devnet_instructions {
+    ($($module:tt)*) => {
+        $($module)*
+    };
+}
 
+with_devnet_instructions! {
 #[program]
 pub mod skill_stake {
     use super::*;
@@ -27,7 +151,7 @@ pub mod skill_stake {
         pow_difficulty: u8,
         oracle_authority: Pubkey,
     ) -> Result<()> {
-        require!(apr_bps <= BPS_DENOMINATOR * 5, SkillStakeError::AprTooHigh);
+        require!(apr_bps <= MAX_APR_BPS, SkillStakeError::AprTooHigh);
         require!(
             pow_difficulty <= MAX_POW_DIFFICULTY,
             SkillStakeError::InvalidPowDifficulty
@@ -51,38 +175,584 @@ pub mod skill_stake {
         state.pow_reward = pow_reward;
         state.pow_difficulty = pow_difficulty;
         state.oracle_nonce = 0;
+        state.timelock_delay = 0;
+        state.change_count = 0;
+        state.paused = 0;
+        state.faucet_cooldown = 0;
+        state.faucet_window = 0;
+        state.faucet_window_cap = 0;
+        state.faucet_daily_budget = 0;
+        state.faucet_day = 0;
+        state.faucet_day_minted = 0;
+        state.faucet_budget = 0;
+        state.faucet_minted = 0;
+        state.faucet_gate = FAUCET_GATE_NONE;
+        state.faucet_pow_difficulty = 0;
+        state.pow_retarget = PowRetarget::default();
+        state.pow_window_start = 0;
+        state.pow_window_proofs = 0;
+        state.require_proof_attestation = false;
+        state.proof_stake_policy = ProofStakePolicy::default();
+        state.pow_challenge_slots = 0;
+        state.pow_hash_algorithm = POW_HASH_KECCAK256;
+        state.version = GlobalState::VERSION;
 
         // Anchor 0.30: bumps are fields, not a map
         state.bump = ctx.bumps.state;
         state.vault_bump = ctx.bumps.vault;
         state.mint_auth_bump = ctx.bumps.mint_auth;
 
+        emit_cpi!(Initialized {
+            admin,
+            oracle_authority: authority,
+            mint: ctx.accounts.mint.key(),
+            vault: ctx.accounts.vault.key(),
+            apr_bps,
+            faucet_cap,
+            pow_reward,
+            pow_difficulty,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 
-    pub fn set_apr(ctx: Context<AdminUpdate>, apr_bps: u64) -> Result<()> {
-        require!(apr_bps <= BPS_DENOMINATOR * 5, SkillStakeError::AprTooHigh);
-        let state = &mut ctx.accounts.state;
+    /// Creates the roles account. Admin implicitly holds every role.
+    pub fn initialize_roles(ctx: Context<InitializeRoles>) -> Result<()> {
+        let state = &ctx.accounts.state;
         require_keys_eq!(state.admin, ctx.accounts.admin.key(), SkillStakeError::Unauthorized);
-        state.apr_bps = apr_bps;
+
+        let roles = &mut ctx.accounts.roles;
+        roles.members = Vec::new();
+        roles.bump = ctx.bumps.roles;
+
+        emit_cpi!(RolesInitialized {
+            admin: ctx.accounts.admin.key(),
+            roles: ctx.accounts.roles.key(),
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 
-    pub fn update_faucet_cap(ctx: Context<AdminUpdate>, faucet_cap: u64) -> Result<()> {
-        let state = &mut ctx.accounts.state;
+    pub fn grant_role(ctx: Context<ManageRoles>, member: Pubkey, permissions: u8) -> Result<()> {
+        require!(
+            permissions != 0 && permissions & !ROLE_ALL == 0,
+            SkillStakeError::InvalidRole
+        );
+        require!(member != Pubkey::default(), SkillStakeError::InvalidRole);
+
+        let state = &ctx.accounts.state;
         require_keys_eq!(state.admin, ctx.accounts.admin.key(), SkillStakeError::Unauthorized);
-        state.faucet_cap = faucet_cap;
+
+        let admin = state.admin;
+        let previous = ctx.accounts.roles.permissions(&member);
+        let new_permissions = ctx.accounts.roles.grant(member, permissions)?;
+
+        emit_cpi!(RoleGranted {
+            admin,
+            member,
+            granted: permissions,
+            permissions_before: previous,
+            permissions_after: new_permissions,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn revoke_role(ctx: Context<ManageRoles>, member: Pubkey, permissions: u8) -> Result<()> {
+        require!(
+            permissions != 0 && permissions & !ROLE_ALL == 0,
+            SkillStakeError::InvalidRole
+        );
+
+        let state = &ctx.accounts.state;
+        require_keys_eq!(state.admin, ctx.accounts.admin.key(), SkillStakeError::Unauthorized);
+
+        let admin = state.admin;
+        let previous = ctx.accounts.roles.permissions(&member);
+        let new_permissions = ctx.accounts.roles.revoke(member, permissions);
+
+        emit_cpi!(RoleRevoked {
+            admin,
+            member,
+            revoked: permissions,
+            permissions_before: previous,
+            permissions_after: new_permissions,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Creates the oracle set. From then on `oracle_authority` signs nothing alone: attestations
+    /// and oracle `set_pow_config` updates need `threshold` distinct members, and only PoW
+    /// managers create tasks.
+    pub fn initialize_oracle_set(
+        ctx: Context<InitializeOracleSet>,
+        members: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<()> {
+        let state = &ctx.accounts.state;
+        require_keys_eq!(state.admin, ctx.accounts.admin.key(), SkillStakeError::Unauthorized);
+        require!(state.timelock_delay == 0, SkillStakeError::TimelockActive);
+
+        let oracle_set = &mut ctx.accounts.oracle_set;
+        oracle_set.members = members;
+        oracle_set.threshold = threshold;
+        oracle_set.bump = ctx.bumps.oracle_set;
+        oracle_set.version = OracleSet::VERSION;
+        require!(oracle_set.is_valid(), SkillStakeError::InvalidOracleSet);
+
+        emit_cpi!(OracleSetInitialized {
+            admin: ctx.accounts.admin.key(),
+            oracle_set: oracle_set.key(),
+            members: oracle_set.members.clone(),
+            threshold,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn add_oracle_member(ctx: Context<ManageOracleSet>, member: Pubkey) -> Result<()> {
+        let state = &ctx.accounts.state;
+        require_keys_eq!(state.admin, ctx.accounts.admin.key(), SkillStakeError::Unauthorized);
+        require!(state.timelock_delay == 0, SkillStakeError::TimelockActive);
+
+        let oracle_set = &mut ctx.accounts.oracle_set;
+        oracle_set.members.push(member);
+        require!(oracle_set.is_valid(), SkillStakeError::InvalidOracleSet);
+
+        emit_cpi!(OracleMemberAdded {
+            admin: ctx.accounts.admin.key(),
+            member,
+            member_count: oracle_set.members.len() as u8,
+            threshold: oracle_set.threshold,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Fails while removing `member` would leave fewer members than the threshold.
+    pub fn remove_oracle_member(ctx: Context<ManageOracleSet>, member: Pubkey) -> Result<()> {
+        let state = &ctx.accounts.state;
+        require_keys_eq!(state.admin, ctx.accounts.admin.key(), SkillStakeError::Unauthorized);
+        require!(state.timelock_delay == 0, SkillStakeError::TimelockActive);
+
+        let oracle_set = &mut ctx.accounts.oracle_set;
+        let count_before = oracle_set.members.len();
+        oracle_set.members.retain(|key| *key != member);
+        require!(
+            oracle_set.members.len() < count_before && oracle_set.is_valid(),
+            SkillStakeError::InvalidOracleSet
+        );
+
+        emit_cpi!(OracleMemberRemoved {
+            admin: ctx.accounts.admin.key(),
+            member,
+            member_count: oracle_set.members.len() as u8,
+            threshold: oracle_set.threshold,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn set_oracle_threshold(ctx: Context<ManageOracleSet>, threshold: u8) -> Result<()> {
+        let state = &ctx.accounts.state;
+        require_keys_eq!(state.admin, ctx.accounts.admin.key(), SkillStakeError::Unauthorized);
+        require!(state.timelock_delay == 0, SkillStakeError::TimelockActive);
+
+        let oracle_set = &mut ctx.accounts.oracle_set;
+        let threshold_before = std::mem::replace(&mut oracle_set.threshold, threshold);
+        require!(oracle_set.is_valid(), SkillStakeError::InvalidOracleSet);
+
+        emit_cpi!(OracleThresholdUpdated {
+            admin: ctx.accounts.admin.key(),
+            threshold_before,
+            threshold_after: threshold,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn set_apr(ctx: Context<RoleUpdate>, apr_bps: u64) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require_role(state, &ctx.accounts.roles, ctx.accounts.authority.key(), ROLE_APR_MANAGER)?;
+        require!(state.timelock_delay == 0, SkillStakeError::TimelockActive);
+
+        let after = ParamChange::Apr { apr_bps };
+        let before = apply_change(state, &after)?;
+
+        emit_cpi!(ParamChanged {
+            authority: ctx.accounts.authority.key(),
+            pending_change_id: None,
+            before,
+            after,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 
     pub fn set_oracle_authority(ctx: Context<AdminUpdate>, new_authority: Pubkey) -> Result<()> {
         let state = &mut ctx.accounts.state;
         require_keys_eq!(state.admin, ctx.accounts.admin.key(), SkillStakeError::Unauthorized);
-        require!(new_authority != Pubkey::default(), SkillStakeError::InvalidOracleAuthority);
-        state.oracle_authority = new_authority;
+        require!(state.timelock_delay == 0, SkillStakeError::TimelockActive);
+
+        let after = ParamChange::OracleAuthority { new_authority };
+        let before = apply_change(state, &after)?;
+
+        emit_cpi!(ParamChanged {
+            authority: ctx.accounts.admin.key(),
+            pending_change_id: None,
+            before,
+            after,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Replaces the pause bitflags; each `PAUSE_*` bit disables one instruction.
+    pub fn set_paused(ctx: Context<RoleUpdate>, paused: u8) -> Result<()> {
+        require!(paused & !PAUSE_ALL == 0, SkillStakeError::InvalidPauseFlags);
+
+        let state = &mut ctx.accounts.state;
+        require_role(state, &ctx.accounts.roles, ctx.accounts.authority.key(), ROLE_PAUSER)?;
+        let paused_before = state.paused;
+        state.paused = paused;
+
+        emit_cpi!(PausedUpdated {
+            authority: ctx.accounts.authority.key(),
+            paused_before,
+            paused_after: paused,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Schedules a parameter change that anyone may execute once `eta` is reached.
+    /// `eta` must be at least `timelock_delay` seconds in the future.
+    pub fn queue_change(ctx: Context<QueueChange>, change: ParamChange, eta: i64) -> Result<()> {
+        let clock = Clock::get()?;
+
+        let state = &mut ctx.accounts.state;
+        authorize_change(state, &ctx.accounts.roles, ctx.accounts.authority.key(), &change)?;
+        validate_change(&change)?;
+
+        let earliest = clock
+            .unix_timestamp
+            .checked_add(state.timelock_delay)
+            .ok_or(SkillStakeError::MathOverflow)?;
+        require!(eta >= earliest, SkillStakeError::TimelockTooShort);
+
+        let id = state.change_count;
+        state.change_count = state
+            .change_count
+            .checked_add(1)
+            .ok_or(SkillStakeError::MathOverflow)?;
+
+        let pending = &mut ctx.accounts.pending_change;
+        pending.id = id;
+        pending.proposer = ctx.accounts.authority.key();
+        pending.change = change.clone();
+        pending.queued_ts = clock.unix_timestamp;
+        pending.eta = eta;
+        pending.bump = ctx.bumps.pending_change;
+
+        emit_cpi!(ChangeQueued {
+            id,
+            proposer: ctx.accounts.authority.key(),
+            change,
+            eta,
+            timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    pub fn execute_change(ctx: Context<ExecuteChange>) -> Result<()> {
+        let clock = Clock::get()?;
+
+        let pending = &ctx.accounts.pending_change;
+        require!(clock.unix_timestamp >= pending.eta, SkillStakeError::TimelockNotElapsed);
+        require!(!pending.is_expired(clock.unix_timestamp), SkillStakeError::ChangeExpired);
+
+        let id = pending.id;
+        let after = pending.change.clone();
+        let before = apply_change(&mut ctx.accounts.state, &after)?;
+
+        emit_cpi!(ParamChanged {
+            authority: ctx.accounts.executor.key(),
+            pending_change_id: Some(id),
+            before,
+            after,
+            timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Admin-only, even for changes a role holder queued, so a compromised role cannot veto the
+    /// admin's queued changes.
+    pub fn cancel_change(ctx: Context<CancelChange>) -> Result<()> {
+        let clock = Clock::get()?;
+
+        let pending = &ctx.accounts.pending_change;
+        // Expired changes can be cancelled again to reclaim their rent.
+        require!(
+            clock.unix_timestamp < pending.eta || pending.is_expired(clock.unix_timestamp),
+            SkillStakeError::TimelockElapsed
+        );
+
+        emit_cpi!(ChangeCancelled {
+            id: pending.id,
+            authority: ctx.accounts.admin.key(),
+            change: pending.change.clone(),
+            eta: pending.eta,
+            timestamp: clock.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Reallocates `GlobalState` to the current `SPACE` and upgrades its layout to `VERSION`.
+    pub fn migrate_state(ctx: Context<MigrateState>) -> Result<()> {
+        let info = ctx.accounts.state.to_account_info();
+        let from = read_layout_version(&info, GlobalState::DISCRIMINATOR, GlobalState::VERSION_OFFSET)?;
+        require!(from < GlobalState::VERSION, SkillStakeError::AccountAlreadyMigrated);
+
+        grow_account(
+            &info,
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.system_program,
+            8 + GlobalState::SPACE,
+        )?;
+
+        {
+            let mut data = info.try_borrow_mut_data()?;
+            let mut state = GlobalState::try_deserialize(&mut &data[..])?;
+            require_keys_eq!(state.admin, ctx.accounts.admin.key(), SkillStakeError::Unauthorized);
+            state.upgrade();
+            state.try_serialize(&mut &mut data[..])?;
+        }
+
+        emit_cpi!(AccountMigrated {
+            account: info.key(),
+            payer: ctx.accounts.admin.key(),
+            version_before: from,
+            version_after: GlobalState::VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Permissionless: the payer funds any extra rent, the owner's balances are untouched.
+    pub fn migrate_stake_account(ctx: Context<MigrateStakeAccount>) -> Result<()> {
+        let info = ctx.accounts.stake_account.to_account_info();
+        let from = read_layout_version(&info, StakeAccount::DISCRIMINATOR, StakeAccount::VERSION_OFFSET)?;
+        require!(from < StakeAccount::VERSION, SkillStakeError::AccountAlreadyMigrated);
+
+        grow_account(
+            &info,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program,
+            8 + StakeAccount::SPACE,
+        )?;
+
+        {
+            let mut data = info.try_borrow_mut_data()?;
+            let mut stake_account = StakeAccount::try_deserialize(&mut &data[..])?;
+            require_keys_eq!(stake_account.owner, ctx.accounts.owner.key(), SkillStakeError::Unauthorized);
+            stake_account.upgrade();
+            stake_account.try_serialize(&mut &mut data[..])?;
+        }
+
+        emit_cpi!(AccountMigrated {
+            account: info.key(),
+            payer: ctx.accounts.payer.key(),
+            version_before: from,
+            version_after: StakeAccount::VERSION,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Enables automatic difficulty retargeting; a zero `window` turns it off.
+    pub fn set_pow_retarget(ctx: Context<RoleUpdate>, retarget: PowRetarget) -> Result<()> {
+        require!(retarget.window >= 0, SkillStakeError::InvalidRetargetConfig);
+        if retarget.window > 0 {
+            require!(
+                retarget.target_proofs > 0
+                    && retarget.max_step > 0
+                    && retarget.min_difficulty <= retarget.max_difficulty
+                    && retarget.max_difficulty <= MAX_POW_DIFFICULTY,
+                SkillStakeError::InvalidRetargetConfig
+            );
+        }
+
+        let state = &mut ctx.accounts.state;
+        require_role(state, &ctx.accounts.roles, ctx.accounts.authority.key(), ROLE_POW_MANAGER)?;
+        // Retargeting moves the difficulty without delay, so a timelocked pool keeps its schedule.
+        require!(state.timelock_delay == 0, SkillStakeError::TimelockActive);
+        let now = Clock::get()?.unix_timestamp;
+
+        let before = std::mem::replace(&mut state.pow_retarget, retarget.clone());
+        state.pow_window_start = now;
+        state.pow_window_proofs = 0;
+
+        emit_cpi!(PowRetargetUpdated {
+            authority: ctx.accounts.authority.key(),
+            before,
+            after: retarget,
+            timestamp: now,
+        });
+        Ok(())
+    }
+
+    /// Makes every `record_proof` carry an oracle attestation, or makes attestations optional again.
+    pub fn set_proof_attestation(ctx: Context<RoleUpdate>, required: bool) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require_role(state, &ctx.accounts.roles, ctx.accounts.authority.key(), ROLE_POW_MANAGER)?;
+
+        let required_before = std::mem::replace(&mut state.require_proof_attestation, required);
+
+        emit_cpi!(ProofAttestationUpdated {
+            authority: ctx.accounts.authority.key(),
+            required_before,
+            required_after: required,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Sets the stake `record_proof` requires and how rewards scale with it.
+    pub fn set_proof_stake_policy(ctx: Context<RoleUpdate>, policy: ProofStakePolicy) -> Result<()> {
+        require!(
+            policy.max_boost_bps <= MAX_PROOF_BOOST_BPS
+                // A boost needs a stake to reach it at and a stake needs a boost.
+                && (policy.full_boost_stake == 0) == (policy.max_boost_bps == 0),
+            SkillStakeError::InvalidProofStakePolicy
+        );
+
+        let state = &mut ctx.accounts.state;
+        require_role(state, &ctx.accounts.roles, ctx.accounts.authority.key(), ROLE_POW_MANAGER)?;
+        // The boost scales every reward, so it is frozen like the other payout settings.
+        require!(state.timelock_delay == 0, SkillStakeError::TimelockActive);
+
+        let before = std::mem::replace(&mut state.proof_stake_policy, policy.clone());
+
+        emit_cpi!(ProofStakePolicyUpdated {
+            authority: ctx.accounts.authority.key(),
+            before,
+            after: policy,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Turns challenge mode on with a freshness window of `window_slots`, or off with zero.
+    pub fn set_pow_challenge(ctx: Context<RoleUpdate>, window_slots: u64) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require_role(state, &ctx.accounts.roles, ctx.accounts.authority.key(), ROLE_POW_MANAGER)?;
+        require!(state.timelock_delay == 0, SkillStakeError::TimelockActive);
+
+        validate_change(&ParamChange::PowChallenge { window_slots })?;
+
+        let window_slots_before = std::mem::replace(&mut state.pow_challenge_slots, window_slots);
+
+        emit_cpi!(PowChallengeUpdated {
+            authority: ctx.accounts.authority.key(),
+            window_slots_before,
+            window_slots_after: window_slots,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Selects the hash `record_proof` mines against, one of the `POW_HASH_*` values.
+    pub fn set_pow_hash_algorithm(ctx: Context<RoleUpdate>, algorithm: u8) -> Result<()> {
+        let state = &mut ctx.accounts.state;
+        require_role(state, &ctx.accounts.roles, ctx.accounts.authority.key(), ROLE_POW_MANAGER)?;
+        require!(state.timelock_delay == 0, SkillStakeError::TimelockActive);
+
+        validate_change(&ParamChange::PowHashAlgorithm { algorithm })?;
+
+        let algorithm_before = std::mem::replace(&mut state.pow_hash_algorithm, algorithm);
+
+        emit_cpi!(PowHashAlgorithmUpdated {
+            authority: ctx.accounts.authority.key(),
+            algorithm_before,
+            algorithm_after: algorithm,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Commits the caller to the newest `SlotHashes` entry. Under challenge mode, proofs must
+    /// hash it in and land within `pow_challenge_slots` of its slot.
+    pub fn commit_pow_challenge(ctx: Context<CommitPowChallenge>) -> Result<()> {
+        let (slot, slot_hash) = newest_slot_hash(&ctx.accounts.slot_hashes)?;
+
+        let stake_account = &mut ctx.accounts.stake_account;
+        require_keys_eq!(stake_account.owner, ctx.accounts.user.key(), SkillStakeError::Unauthorized);
+        let slot_before = std::mem::replace(&mut stake_account.pow_challenge_slot, slot);
+        stake_account.pow_challenge_hash = slot_hash;
+
+        emit_cpi!(PowChallengeCommitted {
+            user: ctx.accounts.user.key(),
+            slot_before,
+            slot,
+            slot_hash,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Registers `task_id` for `record_proof`; callable by a PoW manager, or by the oracle
+    /// authority while there is no oracle set. An oracle set opens tasks with `relay_create_task`.
+    pub fn create_task(ctx: Context<CreateTask>, task_id: u64, params: TaskParams) -> Result<()> {
+        validate_task_params(&params)?;
+
+        let state = &ctx.accounts.state;
+        let authority = ctx.accounts.authority.key();
+        if authority != state.oracle_authority || load_oracle_set(&ctx.accounts.oracle_set)?.is_some() {
+            require_role(state, &ctx.accounts.roles, authority, ROLE_POW_MANAGER)?;
+            // Under a timelock a manager (or an admin who granted themselves the role) may only
+            // open tasks that pay and cost what the pool does.
+            require!(
+                state.timelock_delay == 0 || (params.reward == 0 && params.difficulty == 0),
+                SkillStakeError::TimelockActive
+            );
+        }
+
+        init_task(&mut ctx.accounts.task, task_id, &params, ctx.bumps.task);
+
+        emit_cpi!(TaskCreated {
+            authority,
+            task_id,
+            params,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
+        Ok(())
+    }
+
+    /// `create_task` from a message the oracle, or `threshold` members of its oracle set, signed
+    /// off-chain over `task_message`. Any relayer may submit it and pays the task's rent; since a
+    /// task is created once, the message needs no nonce.
+    pub fn relay_create_task(ctx: Context<RelayCreateTask>, task_id: u64, params: TaskParams) -> Result<()> {
+        validate_task_params(&params)?;
+
+        let state = &ctx.accounts.state;
+        let message = task_message(&state.mint, task_id, &params);
+        verify_oracle_message(state, &ctx.accounts.oracle_set, &ctx.accounts.instructions, &message)?;
+
+        init_task(&mut ctx.accounts.task, task_id, &params, ctx.bumps.task);
+
+        emit_cpi!(TaskCreated {
+            authority: ctx.accounts.relayer.key(),
+            task_id,
+            params,
+            timestamp: Clock::get()?.unix_timestamp,
+        });
         Ok(())
     }
 
+    /// Callable by the oracle, or by a PoW manager while no timelock is set. Once an oracle set
+    /// exists, every update needs the preceding ed25519 instruction with `threshold` member
+    /// signatures over `pow_config_message`, and any wallet may relay it.
     pub fn set_pow_config(
         ctx: Context<OracleUpdate>,
         pow_difficulty: u8,
@@ -96,15 +766,60 @@ pub mod skill_stake {
 
         let state = &mut ctx.accounts.state;
         let authority = ctx.accounts.authority.key();
+        match load_oracle_set(&ctx.accounts.oracle_set)? {
+            Some(oracle_set) => {
+                let message = pow_config_message(&state.mint, pow_difficulty, pow_reward, oracle_nonce);
+                oracle_set.verify(&ctx.accounts.instructions, &message)?;
+            }
+            None if authority == state.oracle_authority => {}
+            None => {
+                require_role(state, &ctx.accounts.roles, authority, ROLE_POW_MANAGER)?;
+                require!(state.timelock_delay == 0, SkillStakeError::TimelockActive);
+            }
+        }
+
+        let event = apply_pow_config(
+            state,
+            authority,
+            pow_difficulty,
+            pow_reward,
+            oracle_nonce,
+            Clock::get()?.unix_timestamp,
+        )?;
+        emit_cpi!(event);
+        Ok(())
+    }
+
+    /// `set_pow_config` from a message the oracle signed off-chain, so the oracle key needs no
+    /// SOL and any relayer can submit it. The message expires at `expiry`, and its nonce must
+    /// still exceed the pool's `oracle_nonce`.
+    pub fn relay_pow_config(
+        ctx: Context<RelayPowConfig>,
+        pow_difficulty: u8,
+        pow_reward: u64,
+        oracle_nonce: u64,
+        expiry: i64,
+    ) -> Result<()> {
         require!(
-            authority == state.oracle_authority || authority == state.admin,
-            SkillStakeError::Unauthorized
+            pow_difficulty <= MAX_POW_DIFFICULTY,
+            SkillStakeError::InvalidPowDifficulty
         );
-        require!(oracle_nonce > state.oracle_nonce, SkillStakeError::StaleOracleUpdate);
+        let now = Clock::get()?.unix_timestamp;
+        require!(now <= expiry, SkillStakeError::AttestationExpired);
 
-        state.pow_difficulty = pow_difficulty;
-        state.pow_reward = pow_reward;
-        state.oracle_nonce = oracle_nonce;
+        let state = &mut ctx.accounts.state;
+        let message = relayed_pow_config_message(&state.mint, pow_difficulty, pow_reward, oracle_nonce, expiry);
+        verify_oracle_message(state, &ctx.accounts.oracle_set, &ctx.accounts.instructions, &message)?;
+
+        let event = apply_pow_config(
+            state,
+            ctx.accounts.relayer.key(),
+            pow_difficulty,
+            pow_reward,
+            oracle_nonce,
+            now,
+        )?;
+        emit_cpi!(event);
         Ok(())
     }
 
@@ -113,15 +828,18 @@ pub mod skill_stake {
         let clock = Clock::get()?;
 
         let state = &mut ctx.accounts.state;
+        require!(!state.is_paused(PAUSE_STAKE), SkillStakeError::InstructionPaused);
         require_keys_eq!(state.mint, ctx.accounts.mint.key(), SkillStakeError::MintMismatch);
 
         let stake_account = &mut ctx.accounts.stake_account;
         if stake_account.owner == Pubkey::default() {
             stake_account.owner = ctx.accounts.user.key();
             stake_account.bump = ctx.bumps.stake_account;
+            stake_account.version = StakeAccount::VERSION;
             stake_account.last_accrued_ts = clock.unix_timestamp;
         } else {
             require_keys_eq!(stake_account.owner, ctx.accounts.user.key(), SkillStakeError::Unauthorized);
+            require!(stake_account.is_current(), SkillStakeError::AccountNotMigrated);
             accrue_rewards(stake_account, state.apr_bps, clock.unix_timestamp)?;
         }
 
@@ -138,17 +856,29 @@ pub mod skill_stake {
             ctx.accounts.mint.decimals,
         )?;
 
+        let amount_staked_before = stake_account.amount_staked;
         stake_account.amount_staked = stake_account
             .amount_staked
             .checked_add(amount)
             .ok_or(SkillStakeError::MathOverflow)?;
         stake_account.last_accrued_ts = clock.unix_timestamp;
 
+        let total_staked_before = state.total_staked;
         state.total_staked = state
             .total_staked
             .checked_add(amount)
             .ok_or(SkillStakeError::MathOverflow)?;
 
+        emit_cpi!(Staked {
+            user: ctx.accounts.user.key(),
+            amount,
+            amount_staked_before,
+            amount_staked_after: stake_account.amount_staked,
+            total_staked_before,
+            total_staked_after: state.total_staked,
+            pending_rewards: stake_account.pending_rewards,
+            timestamp: clock.unix_timestamp,
+        });
         Ok(())
     }
     pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
@@ -186,17 +916,29 @@ pub mod skill_stake {
             ctx.accounts.mint.decimals,
         )?;
 
+        let amount_staked_before = stake_account.amount_staked;
         stake_account.amount_staked = stake_account
             .amount_staked
             .checked_sub(amount)
             .ok_or(SkillStakeError::MathOverflow)?;
         stake_account.last_accrued_ts = clock.unix_timestamp;
 
+        let total_staked_before = state.total_staked;
         state.total_staked = state
             .total_staked
             .checked_sub(amount)
             .ok_or(SkillStakeError::MathOverflow)?;
 
+        emit_cpi!(Unstaked {
+            user: ctx.accounts.user.key(),
+            amount,
+            amount_staked_before,
+            amount_staked_after: stake_account.amount_staked,
+            total_staked_before,
+            total_staked_after: state.total_staked,
+            pending_rewards: stake_account.pending_rewards,
+            timestamp: clock.unix_timestamp,
+        });
         Ok(())
     }
     pub fn claim(ctx: Context<Claim>) -> Result<()> {
@@ -205,6 +947,7 @@ pub mod skill_stake {
         let state = &ctx.accounts.state;
         let stake_account = &mut ctx.accounts.stake_account;
 
+        require!(!state.is_paused(PAUSE_CLAIM), SkillStakeError::InstructionPaused);
         require_keys_eq!(state.mint, ctx.accounts.mint.key(), SkillStakeError::MintMismatch);
         require_keys_eq!(stake_account.owner, ctx.accounts.user.key(), SkillStakeError::Unauthorized);
 
@@ -230,103 +973,399 @@ pub mod skill_stake {
         )?;
 
         stake_account.pending_rewards = 0;
+
+        emit_cpi!(Claimed {
+            user: ctx.accounts.user.key(),
+            amount: rewards,
+            timestamp: clock.unix_timestamp,
+        });
         Ok(())
     }
 
-    pub fn faucet(ctx: Context<Faucet>, amount: u64) -> Result<()> {
-        require!(amount > 0, SkillStakeError::InvalidAmount);
+    /// Returns the full principal and forfeits all pending rewards. Ignores pause flags and works
+    /// on accounts that were never migrated, reading only fields every layout keeps in place.
+    pub fn emergency_withdraw(ctx: Context<EmergencyWithdraw>) -> Result<()> {
+        let clock = Clock::get()?;
 
-        let state = &ctx.accounts.state;
-        require_keys_eq!(state.mint, ctx.accounts.mint.key(), SkillStakeError::MintMismatch);
+        let state_ai = ctx.accounts.state.to_account_info();
+        let stake_ai = ctx.accounts.stake_account.to_account_info();
+        let state_mint = Pubkey::new_from_array(read_fixed(&state_ai, GlobalState::DISCRIMINATOR, GlobalState::MINT_OFFSET)?);
+        let owner = Pubkey::new_from_array(read_fixed(&stake_ai, StakeAccount::DISCRIMINATOR, StakeAccount::OWNER_OFFSET)?);
 
-        let stake_account = &mut ctx.accounts.stake_account;
-        if stake_account.owner == Pubkey::default() {
-            stake_account.owner = ctx.accounts.user.key();
-            stake_account.bump = ctx.bumps.stake_account;
-        } else {
-            require_keys_eq!(stake_account.owner, ctx.accounts.user.key(), SkillStakeError::Unauthorized);
-        }
+        require_keys_eq!(state_mint, ctx.accounts.mint.key(), SkillStakeError::MintMismatch);
+        require_keys_eq!(owner, ctx.accounts.user.key(), SkillStakeError::Unauthorized);
 
-        let new_total = stake_account
-            .faucet_claimed
-            .checked_add(amount)
-            .ok_or(SkillStakeError::MathOverflow)?;
-        require!(new_total <= state.faucet_cap, SkillStakeError::FaucetCapExceeded);
+        let amount = u64::from_le_bytes(read_fixed(&stake_ai, StakeAccount::DISCRIMINATOR, StakeAccount::AMOUNT_STAKED_OFFSET)?);
+        require!(amount > 0, SkillStakeError::InsufficientStake);
 
-        // signer seeds (NO temporaries)
-        let mint_auth_bump_seed = [state.mint_auth_bump];
-        let mint_auth_signer: &[&[u8]] = &[MINT_AUTH_SEED, &mint_auth_bump_seed];
-        let signer: &[&[&[u8]]] = &[mint_auth_signer];
+        let state_bump_seed = [ctx.bumps.state];
+        let state_signer: &[&[u8]] = &[STATE_SEED, &state_bump_seed];
+        let signer_seeds: &[&[&[u8]]] = &[state_signer];
 
-        let cpi_accounts = MintTo {
-            mint: ctx.accounts.mint.to_account_info(),
+        let cpi_accounts = TransferChecked {
+            from: ctx.accounts.vault.to_account_info(),
             to:   ctx.accounts.user_token.to_account_info(),
-            authority: ctx.accounts.mint_auth.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            authority: state_ai.clone(),
         };
         let cpi_program = ctx.accounts.token_program.to_account_info();
-        token::mint_to(
-            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+
+        token::transfer_checked(
+            CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds),
             amount,
+            ctx.accounts.mint.decimals,
         )?;
 
-        stake_account.faucet_claimed = new_total;
+        let forfeited_rewards = u64::from_le_bytes(read_fixed(&stake_ai, StakeAccount::DISCRIMINATOR, StakeAccount::PENDING_REWARDS_OFFSET)?);
+        write_fixed(&stake_ai, StakeAccount::AMOUNT_STAKED_OFFSET, &0u64.to_le_bytes())?;
+        write_fixed(&stake_ai, StakeAccount::PENDING_REWARDS_OFFSET, &0u64.to_le_bytes())?;
+        write_fixed(&stake_ai, StakeAccount::LAST_ACCRUED_TS_OFFSET, &clock.unix_timestamp.to_le_bytes())?;
+
+        let total_staked_before = u64::from_le_bytes(read_fixed(&state_ai, GlobalState::DISCRIMINATOR, GlobalState::TOTAL_STAKED_OFFSET)?);
+        let total_staked_after = total_staked_before
+            .checked_sub(amount)
+            .ok_or(SkillStakeError::MathOverflow)?;
+        write_fixed(&state_ai, GlobalState::TOTAL_STAKED_OFFSET, &total_staked_after.to_le_bytes())?;
+
+        emit_cpi!(EmergencyWithdrawn {
+            user: ctx.accounts.user.key(),
+            amount,
+            forfeited_rewards,
+            total_staked_before,
+            total_staked_after,
+            timestamp: clock.unix_timestamp,
+        });
         Ok(())
     }
-    pub fn record_proof(ctx: Context<RecordProof>, task_id: u64, nonce: u64) -> Result<()> {
+
+    /// Pays the task's reward for a nonce meeting the task's difficulty while the task is open and
+    /// has room for another solver. An oracle `attestation` replaces the task's reward; pools that
+    /// set `require_proof_attestation` accept no proof without one. Provers need the policy's
+    /// `min_stake`, and either reward is boosted by their stake. Under challenge mode the nonce is
+    /// mined over the caller's fresh `commit_pow_challenge`.
+    pub fn record_proof(
+        ctx: Context<RecordProof>,
+        task_id: u64,
+        nonce: u64,
+        attestation: Option<ProofAttestation>,
+    ) -> Result<()> {
         let clock = Clock::get()?;
 
-        let state = &ctx.accounts.state;
+        let state = &mut ctx.accounts.state;
         let stake_account = &mut ctx.accounts.stake_account;
+        let task = &mut ctx.accounts.task;
 
+        require!(!state.is_paused(PAUSE_RECORD_PROOF), SkillStakeError::InstructionPaused);
         require_keys_eq!(stake_account.owner, ctx.accounts.user.key(), SkillStakeError::Unauthorized);
-        require!(task_id > stake_account.last_task_id, SkillStakeError::ProofTaskReplay);
+        check_proof_task(stake_account, task, task_id, clock.unix_timestamp)?;
+        require!(
+            stake_account.amount_staked >= state.proof_stake_policy.min_stake,
+            SkillStakeError::InsufficientProofStake
+        );
 
         accrue_rewards(stake_account, state.apr_bps, clock.unix_timestamp)?;
 
-        let hash = keccak::hashv(&[
-            POW_DOMAIN,
-            ctx.accounts.user.key().as_ref(),
-            state.mint.as_ref(),
-            &task_id.to_le_bytes(),
-            &nonce.to_le_bytes(),
-        ]);
-        require!(meets_difficulty(&hash.0, state.pow_difficulty), SkillStakeError::ProofDifficultyNotMet);
+        let difficulty = task.proof_difficulty(state);
+        let hash = verify_proof_hash(state, stake_account, task_id, nonce, difficulty, clock.slot)?;
+
+        let base_reward = match attestation {
+            Some(ProofAttestation { reward, expiry }) => {
+                require!(clock.unix_timestamp <= expiry, SkillStakeError::AttestationExpired);
+                let message = proof_attestation_message(
+                    &ctx.accounts.user.key(),
+                    &state.mint,
+                    task_id,
+                    reward,
+                    expiry,
+                );
+                verify_oracle_message(state, &ctx.accounts.oracle_set, &ctx.accounts.instructions, &message)?;
+                reward
+            }
+            None => {
+                require!(!state.require_proof_attestation, SkillStakeError::AttestationMissing);
+                task.proof_reward(state)
+            }
+        };
+
+        let last_task_id_before = stake_account.last_task_id;
+        let pending_rewards_before = stake_account.pending_rewards;
+        let record = ProofRecord { task_id, nonce, hash };
+        let entry = credit_proof(state, stake_account, task, record, base_reward, clock.unix_timestamp)?;
+        let reward = entry.reward;
+
+        append_proof_history(
+            &ctx.accounts.proof_history,
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            ctx.bumps.proof_history,
+            vec![entry],
+        )?;
 
-        if state.pow_reward > 0 {
-            stake_account.pending_rewards = stake_account
-                .pending_rewards
-                .checked_add(state.pow_reward)
-                .ok_or(SkillStakeError::MathOverflow)?;
+        emit_cpi!(ProofRecorded {
+            user: ctx.accounts.user.key(),
+            task_id,
+            nonce,
+            hash,
+            reward,
+            last_task_id_before,
+            pending_rewards_before,
+            pending_rewards_after: stake_account.pending_rewards,
+            timestamp: clock.unix_timestamp,
+        });
+
+        if let Some(retargeted) = count_retarget_proofs(state, 1, clock.unix_timestamp) {
+            emit_cpi!(retargeted);
         }
+        Ok(())
+    }
+
+    /// `record_proof` for up to `max_proof_batch` proofs in one transaction, crediting their total
+    /// reward. Each proof's task follows the instruction's accounts, in order, as a writable
+    /// remaining account. Batches carry no attestations, so pools that require one reject them.
+    pub fn record_proofs(ctx: Context<RecordProofs>, proofs: Vec<ProofSubmission>) -> Result<()> {
+        let clock = Clock::get()?;
+
+        let state = &mut ctx.accounts.state;
+        let stake_account = &mut ctx.accounts.stake_account;
+
+        require!(!state.is_paused(PAUSE_RECORD_PROOF), SkillStakeError::InstructionPaused);
+        require_keys_eq!(stake_account.owner, ctx.accounts.user.key(), SkillStakeError::Unauthorized);
+        require!(!state.require_proof_attestation, SkillStakeError::AttestationMissing);
+        require!(
+            !proofs.is_empty()
+                && proofs.len() <= max_proof_batch(state.pow_hash_algorithm)
+                && proofs.len() == ctx.remaining_accounts.len(),
+            SkillStakeError::InvalidProofBatch
+        );
+        require!(
+            stake_account.amount_staked >= state.proof_stake_policy.min_stake,
+            SkillStakeError::InsufficientProofStake
+        );
+
+        accrue_rewards(stake_account, state.apr_bps, clock.unix_timestamp)?;
 
-        let proof_record = ProofRecord { task_id, nonce, hash: hash.0 };
-        let encoded = proof_record.try_to_vec()?;
-        require!(encoded.len() <= MAX_PROOF_STORAGE, SkillStakeError::ProofTooLarge);
+        let mut entries = Vec::with_capacity(proofs.len());
+        for (ProofSubmission { task_id, nonce }, info) in proofs.into_iter().zip(ctx.remaining_accounts) {
+            let mut task = load_batch_task(info, task_id)?;
+            check_proof_task(stake_account, &task, task_id, clock.unix_timestamp)?;
+            let difficulty = task.proof_difficulty(state);
+            let hash = verify_proof_hash(state, stake_account, task_id, nonce, difficulty, clock.slot)?;
+
+            let last_task_id_before = stake_account.last_task_id;
+            let pending_rewards_before = stake_account.pending_rewards;
+            let base_reward = task.proof_reward(state);
+            let record = ProofRecord { task_id, nonce, hash };
+            let entry = credit_proof(state, stake_account, &mut task, record, base_reward, clock.unix_timestamp)?;
+            task.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+            emit_cpi!(ProofRecorded {
+                user: ctx.accounts.user.key(),
+                task_id,
+                nonce,
+                hash,
+                reward: entry.reward,
+                last_task_id_before,
+                pending_rewards_before,
+                pending_rewards_after: stake_account.pending_rewards,
+                timestamp: clock.unix_timestamp,
+            });
+            entries.push(entry);
+        }
 
-        stake_account.last_proof = encoded;
-        stake_account.last_proof_ts = clock.unix_timestamp;
-        stake_account.last_task_id = task_id;
+        let proofs = entries.len() as u64;
+        append_proof_history(
+            &ctx.accounts.proof_history,
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            ctx.bumps.proof_history,
+            entries,
+        )?;
 
+        if let Some(retargeted) = count_retarget_proofs(state, proofs, clock.unix_timestamp) {
+            emit_cpi!(retargeted);
+        }
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(mut)]
-    pub admin: Signer<'info>,
-    #[account(mut)]
-    pub mint: InterfaceAccount<'info, Mint>,
-    #[account(
-        init,
-        payer = admin,
-        space = 8 + GlobalState::SPACE,
-        seeds = [STATE_SEED],
-        bump
-    )]
-    pub state: Account<'info, GlobalState>,
-    /// CHECK: PDA used as mint authority
-    #[account(
+    /// View: pending rewards as `claim` would see them at the current clock.
+    pub fn preview_rewards(ctx: Context<PreviewStake>) -> Result<RewardsPreview> {
+        let clock = Clock::get()?;
+
+        let state = &ctx.accounts.state;
+        let mut stake_account = (*ctx.accounts.stake_account).clone();
+        let accrued = accrued_rewards(
+            stake_account.amount_staked,
+            state.apr_bps,
+            stake_account.last_accrued_ts,
+            clock.unix_timestamp,
+        )?;
+        accrue_rewards(&mut stake_account, state.apr_bps, clock.unix_timestamp)?;
+
+        Ok(RewardsPreview {
+            owner: stake_account.owner,
+            amount_staked: stake_account.amount_staked,
+            accrued_since_last: accrued,
+            pending_rewards: stake_account.pending_rewards,
+            apr_bps: state.apr_bps,
+            timestamp: clock.unix_timestamp,
+        })
+    }
+
+    /// View: runs the `unstake` checks and accrual for `amount` without moving tokens.
+    pub fn preview_unstake(ctx: Context<PreviewStake>, amount: u64) -> Result<UnstakePreview> {
+        require!(amount > 0, SkillStakeError::InvalidAmount);
+        let clock = Clock::get()?;
+
+        let state = &ctx.accounts.state;
+        let mut stake_account = (*ctx.accounts.stake_account).clone();
+        require!(stake_account.amount_staked >= amount, SkillStakeError::InsufficientStake);
+
+        accrue_rewards(&mut stake_account, state.apr_bps, clock.unix_timestamp)?;
+
+        let remaining_staked = stake_account
+            .amount_staked
+            .checked_sub(amount)
+            .ok_or(SkillStakeError::MathOverflow)?;
+        let total_staked_after = state
+            .total_staked
+            .checked_sub(amount)
+            .ok_or(SkillStakeError::MathOverflow)?;
+
+        Ok(UnstakePreview {
+            amount,
+            remaining_staked,
+            pending_rewards: stake_account.pending_rewards,
+            total_staked_after,
+            timestamp: clock.unix_timestamp,
+        })
+    }
+
+    /// View: hashes `(task_id, nonce)` for `user` and reports whether `record_proof` would accept it.
+    pub fn check_proof(
+        ctx: Context<CheckProof>,
+        user: Pubkey,
+        task_id: u64,
+        nonce: u64,
+    ) -> Result<ProofCheck> {
+        let state = &ctx.accounts.state;
+        let is_replay = ctx.accounts.stake_account.as_ref().is_some_and(|stake_account| {
+            stake_account.is_task_expired(task_id) || stake_account.is_task_recorded(task_id)
+        });
+        let amount_staked = ctx
+            .accounts
+            .stake_account
+            .as_ref()
+            .map_or(0, |stake_account| stake_account.amount_staked);
+        let meets_min_stake = amount_staked >= state.proof_stake_policy.min_stake;
+
+        let task = &ctx.accounts.task;
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
+
+        // Under challenge mode, hash with whatever the user committed to, fresh or not.
+        let challenge = match state.pow_challenge_slots {
+            0 => None,
+            window => ctx
+                .accounts
+                .stake_account
+                .as_ref()
+                .and_then(|stake_account| stake_account.pow_challenge(window, clock.slot)),
+        };
+        let is_challenge_fresh =
+            state.pow_challenge_slots == 0 || challenge.is_some_and(|(_, is_fresh)| is_fresh);
+        let algorithm = state.pow_hash_algorithm;
+        let hash = match challenge {
+            Some((slot_hash, _)) => {
+                challenge_pow_hash(algorithm, &user, &state.mint, task_id, &slot_hash, nonce)
+            }
+            None => pow_hash(algorithm, &user, &state.mint, task_id, nonce),
+        };
+        let difficulty = task.proof_difficulty(state);
+        let meets_target = meets_difficulty(&hash, difficulty);
+        let is_open = now >= task.opens_at && now < task.closes_at && !task.is_full();
+        let requires_attestation = state.require_proof_attestation;
+        let reward = if meets_target
+            && !is_replay
+            && is_open
+            && meets_min_stake
+            && is_challenge_fresh
+            && !requires_attestation
+        {
+            boosted_proof_reward(&state.proof_stake_policy, amount_staked, task.proof_reward(state))?
+        } else {
+            0
+        };
+
+        Ok(ProofCheck {
+            hash,
+            difficulty,
+            meets_difficulty: meets_target,
+            is_replay,
+            reward,
+            is_open,
+            meets_min_stake,
+            is_challenge_fresh,
+            requires_attestation,
+        })
+    }
+
+    /// View: global pool configuration and balances.
+    pub fn get_pool_info(ctx: Context<GetPoolInfo>) -> Result<PoolInfo> {
+        let state = &ctx.accounts.state;
+        let today = Clock::get()?.unix_timestamp.div_euclid(SECONDS_PER_DAY);
+        Ok(PoolInfo {
+            admin: state.admin,
+            oracle_authority: state.oracle_authority,
+            mint: state.mint,
+            vault: state.vault,
+            vault_balance: ctx.accounts.vault.amount,
+            apr_bps: state.apr_bps,
+            total_staked: state.total_staked,
+            faucet_cap: state.faucet_cap,
+            pow_reward: state.pow_reward,
+            pow_difficulty: state.pow_difficulty,
+            oracle_nonce: state.oracle_nonce,
+            timelock_delay: state.timelock_delay,
+            paused: state.paused,
+            version: state.version,
+            faucet_cooldown: state.faucet_cooldown,
+            faucet_window: state.faucet_window,
+            faucet_window_cap: state.faucet_window_cap,
+            faucet_daily_budget: state.faucet_daily_budget,
+            faucet_day_minted: if state.faucet_day == today { state.faucet_day_minted } else { 0 },
+            faucet_budget: state.faucet_budget,
+            faucet_minted: state.faucet_minted,
+            faucet_gate: state.faucet_gate,
+            faucet_pow_difficulty: state.faucet_pow_difficulty,
+            pow_retarget: state.pow_retarget.clone(),
+            pow_window_start: state.pow_window_start,
+            pow_window_proofs: state.pow_window_proofs,
+            require_proof_attestation: state.require_proof_attestation,
+            proof_stake_policy: state.proof_stake_policy.clone(),
+            pow_challenge_slots: state.pow_challenge_slots,
+            pow_hash_algorithm: state.pow_hash_algorithm,
+        })
+    }
+}
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + GlobalState::SPACE,
+        seeds = [STATE_SEED],
+        bump
+    )]
+    pub state: Account<'info, GlobalState>,
+    /// CHECK: PDA used as mint authority
+    #[account(
         seeds = [MINT_AUTH_SEED],
         bump
     )]
@@ -345,11 +1384,17 @@ pub struct Initialize<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Stake<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
-    #[account(mut, seeds = [STATE_SEED], bump = state.bump)]
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
     pub state: Account<'info, GlobalState>,
     #[account(
         mut,
@@ -380,11 +1425,17 @@ pub struct Stake<'info> {
     pub rent: Sysvar<'info, Rent>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Unstake<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
-    #[account(mut, seeds = [STATE_SEED], bump = state.bump)]
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
     pub state: Account<'info, GlobalState>,
     #[account(
         mut,
@@ -405,17 +1456,53 @@ pub struct Unstake<'info> {
     #[account(
         mut,
         seeds = [STAKE_ACCOUNT_SEED, user.key().as_ref()],
-        bump = stake_account.bump
+        bump = stake_account.bump,
+        constraint = stake_account.is_current() @ SkillStakeError::AccountNotMigrated
     )]
     pub stake_account: Account<'info, StakeAccount>,
     pub token_program: Interface<'info, TokenInterface>,
 }
 
+#[event_cpi]
+#[derive(Accounts)]
+pub struct EmergencyWithdraw<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    /// CHECK: any `GlobalState` layout; `emergency_withdraw` reads it by fixed offsets
+    #[account(mut, seeds = [STATE_SEED], bump)]
+    pub state: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [VAULT_SEED],
+        bump,
+        token::mint = mint,
+        token::authority = state
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = user
+    )]
+    pub user_token: InterfaceAccount<'info, TokenAccount>,
+    /// CHECK: any `StakeAccount` layout; `emergency_withdraw` reads it by fixed offsets
+    #[account(mut, seeds = [STAKE_ACCOUNT_SEED, user.key().as_ref()], bump)]
+    pub stake_account: UncheckedAccount<'info>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Claim<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
-    #[account(seeds = [STATE_SEED], bump = state.bump)]
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
     pub state: Account<'info, GlobalState>,
     #[account(mut)]
     pub mint: InterfaceAccount<'info, Mint>,
@@ -434,17 +1521,25 @@ pub struct Claim<'info> {
     #[account(
         mut,
         seeds = [STAKE_ACCOUNT_SEED, user.key().as_ref()],
-        bump = stake_account.bump
+        bump = stake_account.bump,
+        constraint = stake_account.is_current() @ SkillStakeError::AccountNotMigrated
     )]
     pub stake_account: Account<'info, StakeAccount>,
     pub token_program: Interface<'info, TokenInterface>,
 }
 
+#[cfg(feature = "devnet")]
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Faucet<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
-    #[account(seeds = [STATE_SEED], bump = state.bump)]
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
     pub state: Account<'info, GlobalState>,
     #[account(mut)]
     pub mint: InterfaceAccount<'info, Mint>,
@@ -464,125 +1559,2162 @@ pub struct Faucet<'info> {
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
+    /// CHECK: the oracle set, empty until `initialize_oracle_set`; see `load_oracle_set`
+    #[account(seeds = [ORACLE_SET_SEED], bump)]
+    pub oracle_set: UncheckedAccount<'info>,
+    /// CHECK: instructions sysvar, read for oracle attestations
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
+#[instruction(task_id: u64)]
 pub struct RecordProof<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
-    #[account(seeds = [STATE_SEED], bump = state.bump)]
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [STAKE_ACCOUNT_SEED, user.key().as_ref()],
+        bump = stake_account.bump,
+        constraint = stake_account.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+    #[account(mut, seeds = [TASK_SEED, &task_id.to_le_bytes()], bump = task.bump)]
+    pub task: Account<'info, Task>,
+    /// CHECK: created by the first proof and grown one entry per proof; see `append_proof_history`
+    #[account(mut, seeds = [PROOF_HISTORY_SEED, user.key().as_ref()], bump)]
+    pub proof_history: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: the oracle set, empty until `initialize_oracle_set`; see `load_oracle_set`
+    #[account(seeds = [ORACLE_SET_SEED], bump)]
+    pub oracle_set: UncheckedAccount<'info>,
+    /// CHECK: instructions sysvar, read for oracle attestations
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RecordProofs<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub state: Account<'info, GlobalState>,
+    #[account(
+        mut,
+        seeds = [STAKE_ACCOUNT_SEED, user.key().as_ref()],
+        bump = stake_account.bump,
+        constraint = stake_account.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+    /// CHECK: see `append_proof_history`
+    #[account(mut, seeds = [PROOF_HISTORY_SEED, user.key().as_ref()], bump)]
+    pub proof_history: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CommitPowChallenge<'info> {
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
     pub state: Account<'info, GlobalState>,
     #[account(
         mut,
         seeds = [STAKE_ACCOUNT_SEED, user.key().as_ref()],
-        bump = stake_account.bump
+        bump = stake_account.bump,
+        constraint = stake_account.is_current() @ SkillStakeError::AccountNotMigrated
     )]
     pub stake_account: Account<'info, StakeAccount>,
+    /// CHECK: slot hashes sysvar, read raw since it is too large to deserialize on-chain
+    #[account(address = sysvar::slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct AdminUpdate<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
-    #[account(mut, seeds = [STATE_SEED], bump = state.bump, has_one = admin)]
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = admin,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
     pub state: Account<'info, GlobalState>,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
-pub struct OracleUpdate<'info> {
+#[instruction(task_id: u64)]
+pub struct CreateTask<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
-    #[account(mut, seeds = [STATE_SEED], bump = state.bump)]
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
     pub state: Account<'info, GlobalState>,
+    /// CHECK: the roles account, empty until `initialize_roles`; see `load_roles`
+    #[account(seeds = [ROLES_SEED], bump)]
+    pub roles: UncheckedAccount<'info>,
+    /// CHECK: the oracle set, empty until `initialize_oracle_set`; see `load_oracle_set`
+    #[account(seeds = [ORACLE_SET_SEED], bump)]
+    pub oracle_set: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Task::SPACE,
+        seeds = [TASK_SEED, &task_id.to_le_bytes()],
+        bump
+    )]
+    pub task: Account<'info, Task>,
+    pub system_program: Program<'info, System>,
 }
 
-#[account]
-pub struct GlobalState {
-    pub admin: Pubkey,
-    pub oracle_authority: Pubkey,
-    pub mint: Pubkey,
-    pub vault: Pubkey,
-    pub apr_bps: u64,
-    pub total_staked: u64,
-    pub faucet_cap: u64,
-    pub pow_reward: u64,
-    pub oracle_nonce: u64,
-    pub pow_difficulty: u8,
-    pub bump: u8,
-    pub vault_bump: u8,
-    pub mint_auth_bump: u8,
-    pub _padding: [u8; 4],
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(task_id: u64)]
+pub struct RelayCreateTask<'info> {
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub state: Account<'info, GlobalState>,
+    /// CHECK: the oracle set, empty until `initialize_oracle_set`; see `load_oracle_set`
+    #[account(seeds = [ORACLE_SET_SEED], bump)]
+    pub oracle_set: UncheckedAccount<'info>,
+    /// CHECK: instructions sysvar, read for the oracle's signatures
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + Task::SPACE,
+        seeds = [TASK_SEED, &task_id.to_le_bytes()],
+        bump
+    )]
+    pub task: Account<'info, Task>,
+    pub system_program: Program<'info, System>,
 }
 
-impl GlobalState {
-    // 32*4 + 8*5 + 1*4 + 4 = 32+32+32+32 + 40 + 4 + 4 = 176 bytes
-    pub const SPACE: usize = 32 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 1 + 1 + 4;
+#[event_cpi]
+#[derive(Accounts)]
+pub struct OracleUpdate<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub state: Account<'info, GlobalState>,
+    /// CHECK: the roles account, empty until `initialize_roles`; see `load_roles`
+    #[account(seeds = [ROLES_SEED], bump)]
+    pub roles: UncheckedAccount<'info>,
+    /// CHECK: the oracle set, empty until `initialize_oracle_set`; see `load_oracle_set`
+    #[account(seeds = [ORACLE_SET_SEED], bump)]
+    pub oracle_set: UncheckedAccount<'info>,
+    /// CHECK: instructions sysvar, read for oracle set signatures
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
 }
 
-#[account]
-pub struct StakeAccount {
-    pub owner: Pubkey,
-    pub amount_staked: u64,
-    pub pending_rewards: u64,
-    pub faucet_claimed: u64,
-    pub last_accrued_ts: i64,
-    pub last_proof_ts: i64,
-    pub last_task_id: u64,
-    pub bump: u8,
-    pub _padding: [u8; 7],
-    pub last_proof: Vec<u8>, // serialized as len (u32) + bytes
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RelayPowConfig<'info> {
+    pub relayer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub state: Account<'info, GlobalState>,
+    /// CHECK: the oracle set, empty until `initialize_oracle_set`; see `load_oracle_set`
+    #[account(seeds = [ORACLE_SET_SEED], bump)]
+    pub oracle_set: UncheckedAccount<'info>,
+    /// CHECK: instructions sysvar, read for the oracle's signatures
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
 }
 
-impl StakeAccount {
-    // 32 + (8*6) + 1 + 7 + 4 + MAX_PROOF_STORAGE
-    pub const SPACE: usize = 32 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 7 + 4 + MAX_PROOF_STORAGE;
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RoleUpdate<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub state: Account<'info, GlobalState>,
+    /// CHECK: the roles account, empty until `initialize_roles`; see `load_roles`
+    #[account(seeds = [ROLES_SEED], bump)]
+    pub roles: UncheckedAccount<'info>,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub struct ProofRecord {
-    pub task_id: u64,
-    pub nonce: u64,
-    pub hash: [u8; 32],
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeRoles<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = admin,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + Roles::SPACE,
+        seeds = [ROLES_SEED],
+        bump
+    )]
+    pub roles: Account<'info, Roles>,
+    pub system_program: Program<'info, System>,
 }
 
-#[error_code]
-pub enum SkillStakeError {
-    #[msg("APR too high for devnet demo settings")]
-    AprTooHigh,
-    #[msg("Operation would overflow maths")]
-    MathOverflow,
-    #[msg("Provided amount must be greater than zero")]
-    InvalidAmount,
-    #[msg("Nothing to claim yet")]
-    NothingToClaim,
-    #[msg("Insufficient staked balance")]
-    InsufficientStake,
-    #[msg("Caller is not authorized for this action")]
-    Unauthorized,
-    #[msg("Missing PDA bump in context")]
-    BumpNotFound,
-    #[msg("Provided mint does not match program state")]
-    MintMismatch,
-    #[msg("Proof payload too large")]
-    ProofTooLarge,
-    #[msg("Faucet cap exceeded for this wallet")]
-    FaucetCapExceeded,
-    #[msg("Oracle authority must be a valid public key")]
-    InvalidOracleAuthority,
-    #[msg("Proof of work difficulty is invalid for this program")]
-    InvalidPowDifficulty,
-    #[msg("Oracle update nonce is stale")]
-    StaleOracleUpdate,
-    #[msg("Proof does not satisfy the difficulty target")]
-    ProofDifficultyNotMet,
-    #[msg("Task identifier has already been used")]
-    ProofTaskReplay,
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ManageRoles<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = admin,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub state: Account<'info, GlobalState>,
+    #[account(mut, seeds = [ROLES_SEED], bump = roles.bump)]
+    pub roles: Account<'info, Roles>,
 }
 
-fn meets_difficulty(hash: &[u8; 32], difficulty: u8) -> bool {
-    if difficulty == 0 {
-        return true;
-    }
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeOracleSet<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = admin,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub state: Account<'info, GlobalState>,
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + OracleSet::SPACE,
+        seeds = [ORACLE_SET_SEED],
+        bump
+    )]
+    pub oracle_set: Account<'info, OracleSet>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ManageOracleSet<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = admin,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub state: Account<'info, GlobalState>,
+    #[account(mut, seeds = [ORACLE_SET_SEED], bump = oracle_set.bump)]
+    pub oracle_set: Account<'info, OracleSet>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct QueueChange<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub state: Account<'info, GlobalState>,
+    /// CHECK: the roles account, empty until `initialize_roles`; see `load_roles`
+    #[account(seeds = [ROLES_SEED], bump)]
+    pub roles: UncheckedAccount<'info>,
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PendingChange::SPACE,
+        seeds = [PENDING_CHANGE_SEED, &state.change_count.to_le_bytes()],
+        bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteChange<'info> {
+    pub executor: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub state: Account<'info, GlobalState>,
+    /// CHECK: receives the rent of the closed pending change
+    #[account(mut, address = pending_change.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [PENDING_CHANGE_SEED, &pending_change.id.to_le_bytes()],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelChange<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        has_one = admin,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub state: Account<'info, GlobalState>,
+    /// CHECK: receives the rent of the closed pending change
+    #[account(mut, address = pending_change.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [PENDING_CHANGE_SEED, &pending_change.id.to_le_bytes()],
+        bump = pending_change.bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+}
+
+#[derive(Accounts)]
+pub struct PreviewStake<'info> {
+    /// CHECK: only used to derive the stake account address
+    pub owner: UncheckedAccount<'info>,
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub state: Account<'info, GlobalState>,
+    #[account(
+        seeds = [STAKE_ACCOUNT_SEED, owner.key().as_ref()],
+        bump = stake_account.bump,
+        constraint = stake_account.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub stake_account: Account<'info, StakeAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(user: Pubkey, task_id: u64)]
+pub struct CheckProof<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub state: Account<'info, GlobalState>,
+    #[account(seeds = [TASK_SEED, &task_id.to_le_bytes()], bump = task.bump)]
+    pub task: Account<'info, Task>,
+    /// Omit for a user who has never staked.
+    #[account(
+        seeds = [STAKE_ACCOUNT_SEED, user.as_ref()],
+        bump = stake_account.bump,
+        constraint = stake_account.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub stake_account: Option<Account<'info, StakeAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct GetPoolInfo<'info> {
+    #[account(
+        seeds = [STATE_SEED],
+        bump = state.bump,
+        constraint = state.is_current() @ SkillStakeError::AccountNotMigrated
+    )]
+    pub state: Account<'info, GlobalState>,
+    #[account(address = state.vault)]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateState<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    /// CHECK: deserialized by hand because older layouts may not fit `GlobalState`
+    #[account(mut, seeds = [STATE_SEED], bump, owner = crate::ID)]
+    pub state: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateStakeAccount<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: only used to derive the stake account address
+    pub owner: UncheckedAccount<'info>,
+    /// CHECK: deserialized by hand because older layouts may not fit `StakeAccount`
+    #[account(
+        mut,
+        seeds = [STAKE_ACCOUNT_SEED, owner.key().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub stake_account: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+pub struct GlobalState {
+    pub admin: Pubkey,
+    pub oracle_authority: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub apr_bps: u64,
+    pub total_staked: u64,
+    pub faucet_cap: u64,
+    pub pow_reward: u64,
+    pub oracle_nonce: u64,
+    pub pow_difficulty: u8,
+    pub bump: u8,
+    pub vault_bump: u8,
+    pub mint_auth_bump: u8,
+    pub version: u8,
+    pub _padding: [u8; 3],
+    pub timelock_delay: i64,
+    pub change_count: u64,
+    pub paused: u8,
+    // v2: faucet rate limits
+    pub faucet_cooldown: i64,
+    pub faucet_window: i64,
+    pub faucet_window_cap: u64,
+    pub faucet_daily_budget: u64,
+    pub faucet_day: i64,
+    pub faucet_day_minted: u64,
+    // v3: lifetime faucet budget; migrated pools start at 0 and need a top-up
+    pub faucet_budget: u64,
+    pub faucet_minted: u64,
+    // v4: sybil gating; migrated pools start ungated
+    pub faucet_gate: u8,
+    pub faucet_pow_difficulty: u8,
+    // v5: difficulty retargeting; migrated pools start with it off
+    pub pow_retarget: PowRetarget,
+    pub pow_window_start: i64,
+    pub pow_window_proofs: u64,
+    // v6: oracle-signed task attestations; migrated pools start with them optional
+    pub require_proof_attestation: bool,
+    // v7: stake requirements for provers; migrated pools start with none
+    pub proof_stake_policy: ProofStakePolicy,
+    // v8: slot hash challenges; migrated pools start with them off
+    pub pow_challenge_slots: u64,
+    // v9: selectable proof hash; migrated pools keep keccak256
+    pub pow_hash_algorithm: u8,
+}
+
+impl GlobalState {
+    // v1 body (193 bytes) + 6 * 8 faucet limit fields + 2 * 8 budget fields + 2 gate bytes
+    // + retarget config and window (35 bytes) + attestation flag + stake policy
+    // + challenge window + hash algorithm = 328 bytes
+    pub const SPACE: usize = 32 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 1 + 1 + 1 + 3 + 8 + 8 + 1
+        + 8 + 8 + 8 + 8 + 8 + 8
+        + 8 + 8
+        + 1 + 1
+        + PowRetarget::SPACE + 8 + 8
+        + 1
+        + ProofStakePolicy::SPACE
+        + 8
+        + 1;
+    pub const VERSION: u8 = 9;
+    // discriminator + 4 pubkeys + 5 u64 + 3 bumps + difficulty; layouts before v1 read 0 here
+    pub const VERSION_OFFSET: usize = 8 + 32 * 4 + 8 * 5 + 4;
+    // Fields every layout keeps in place, for `emergency_withdraw`.
+    pub const MINT_OFFSET: usize = 8 + 32 * 2;
+    pub const TOTAL_STAKED_OFFSET: usize = 8 + 32 * 4 + 8;
+
+    pub fn is_current(&self) -> bool {
+        self.version == Self::VERSION
+    }
+
+    /// Fills in fields added since the stored version. Bytes appended by `realloc` are zeroed,
+    /// so only fields with non-zero defaults need work here.
+    fn upgrade(&mut self) {
+        self.version = Self::VERSION;
+    }
+
+    pub fn is_paused(&self, flag: u8) -> bool {
+        self.paused & flag != 0
+    }
+}
+
+#[account]
+pub struct StakeAccount {
+    pub owner: Pubkey,
+    pub amount_staked: u64,
+    pub pending_rewards: u64,
+    pub faucet_claimed: u64,
+    pub last_accrued_ts: i64,
+    pub last_proof_ts: i64,
+    /// Highest task recorded; `task_bitmap` covers the `PROOF_REPLAY_WINDOW` tasks up to it.
+    pub last_task_id: u64,
+    pub bump: u8,
+    pub version: u8,
+    pub _padding: [u8; 6],
+    pub last_proof: Vec<u8>, // serialized as len (u32) + bytes
+    // v2: faucet rate limits
+    pub last_faucet_ts: i64,
+    pub faucet_window_start: i64,
+    pub faucet_window_claimed: u64,
+    // v3: out-of-order replay protection; bit `task_id % PROOF_REPLAY_WINDOW` marks a recorded task
+    pub task_bitmap: [u64; 4],
+    // v4: the slot hash committed to by `commit_pow_challenge`; all zero before the first commit
+    pub pow_challenge_slot: u64,
+    pub pow_challenge_hash: [u8; 32],
+}
+
+impl StakeAccount {
+    // 32 + (8*6) + 1 + 1 + 6 + 4 + MAX_PROOF_STORAGE + 8*3 + 32 + 8 + 32
+    pub const SPACE: usize =
+        32 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 6 + 4 + MAX_PROOF_STORAGE + 8 + 8 + 8 + 32 + 8 + 32;
+    pub const VERSION: u8 = 4;
+    // discriminator + owner + 6 u64 + bump; layouts before v1 read 0 here
+    pub const VERSION_OFFSET: usize = 8 + 32 + 8 * 6 + 1;
+    // Fields every layout keeps in place, for `emergency_withdraw`.
+    pub const OWNER_OFFSET: usize = 8;
+    pub const AMOUNT_STAKED_OFFSET: usize = 8 + 32;
+    pub const PENDING_REWARDS_OFFSET: usize = 8 + 32 + 8;
+    pub const LAST_ACCRUED_TS_OFFSET: usize = 8 + 32 + 8 * 3;
+
+    pub fn is_current(&self) -> bool {
+        self.version == Self::VERSION
+    }
+
+    /// See `GlobalState::upgrade`.
+    fn upgrade(&mut self) {
+        // Before v3 every task up to `last_task_id` counted as spent.
+        if self.version < 3 && self.last_task_id > 0 {
+            self.task_bitmap = [u64::MAX; 4];
+        }
+        self.version = Self::VERSION;
+    }
+
+    /// True once `task_id` has slid out of the replay window below `last_task_id`.
+    pub fn is_task_expired(&self, task_id: u64) -> bool {
+        self.last_task_id.saturating_sub(task_id) >= PROOF_REPLAY_WINDOW
+    }
+
+    /// True when `task_id` was recorded; only meaningful inside the replay window.
+    pub fn is_task_recorded(&self, task_id: u64) -> bool {
+        if task_id > self.last_task_id {
+            return false;
+        }
+        let (word, bit) = Self::task_bit(task_id);
+        self.task_bitmap[word] & bit != 0
+    }
+
+    /// Marks `task_id` recorded, sliding the window forward when it is a new highest task.
+    pub fn record_task(&mut self, task_id: u64) {
+        if task_id > self.last_task_id {
+            if task_id - self.last_task_id >= PROOF_REPLAY_WINDOW {
+                self.task_bitmap = [0; 4];
+            } else {
+                // Slots of the tasks now entering the window still hold bits from a lap ago.
+                for slot in self.last_task_id + 1..=task_id {
+                    let (word, bit) = Self::task_bit(slot);
+                    self.task_bitmap[word] &= !bit;
+                }
+            }
+            self.last_task_id = task_id;
+        }
+        let (word, bit) = Self::task_bit(task_id);
+        self.task_bitmap[word] |= bit;
+    }
+
+    /// The committed slot hash, and whether `slot` is within `window` slots of its commitment.
+    pub fn pow_challenge(&self, window: u64, slot: u64) -> Option<([u8; 32], bool)> {
+        (self.pow_challenge_hash != [0; 32]).then(|| {
+            let is_fresh = slot.saturating_sub(self.pow_challenge_slot) <= window;
+            (self.pow_challenge_hash, is_fresh)
+        })
+    }
+
+    fn task_bit(task_id: u64) -> (usize, u64) {
+        let slot = task_id % PROOF_REPLAY_WINDOW;
+        ((slot / 64) as usize, 1 << (slot % 64))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ProofRecord {
+    pub task_id: u64,
+    pub nonce: u64,
+    pub hash: [u8; 32],
+}
+
+impl ProofRecord {
+    pub const SPACE: usize = 8 + 8 + 32;
+}
+
+/// The last `MAX_PROOF_HISTORY` proofs of one user. Once full, `head` marks the oldest entry and
+/// the next one to be overwritten.
+#[account]
+pub struct ProofHistory {
+    pub owner: Pubkey,
+    pub bump: u8,
+    pub version: u8,
+    pub head: u16,
+    pub entries: Vec<ProofHistoryEntry>,
+}
+
+impl ProofHistory {
+    pub const VERSION: u8 = 1;
+
+    // owner + bump + version + head + vec length prefix + entries
+    pub const fn space(entries: usize) -> usize {
+        32 + 1 + 1 + 2 + 4 + entries * ProofHistoryEntry::SPACE
+    }
+
+    fn push(&mut self, entry: ProofHistoryEntry) {
+        if self.entries.len() < MAX_PROOF_HISTORY {
+            self.entries.push(entry);
+        } else {
+            self.entries[self.head as usize] = entry;
+            self.head = ((self.head as usize + 1) % MAX_PROOF_HISTORY) as u16;
+        }
+    }
+
+    /// Entries from the oldest to the most recent.
+    pub fn chronological(&self) -> impl Iterator<Item = &ProofHistoryEntry> {
+        let (newer, older) = self.entries.split_at(self.head as usize);
+        older.iter().chain(newer)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct ProofHistoryEntry {
+    pub record: ProofRecord,
+    pub reward: u64,
+    pub timestamp: i64,
+}
+
+impl ProofHistoryEntry {
+    pub const SPACE: usize = ProofRecord::SPACE + 8 + 8;
+}
+
+/// A task `record_proof` pays out for, registered by the oracle under its `task_id`.
+#[account]
+pub struct Task {
+    pub task_id: u64,
+    pub description_hash: [u8; 32],
+    /// Zero follows the pool's current `pow_reward`.
+    pub reward: u64,
+    pub opens_at: i64,
+    pub closes_at: i64,
+    /// Zero means unlimited.
+    pub max_solvers: u32,
+    pub solvers: u32,
+    /// Zero follows the pool's current `pow_difficulty`, including retargets.
+    pub difficulty: u8,
+    pub bump: u8,
+    pub version: u8,
+}
+
+impl Task {
+    pub const SPACE: usize = 8 + 32 + 8 + 8 + 8 + 4 + 4 + 1 + 1 + 1;
+    pub const VERSION: u8 = 1;
+
+    pub fn is_full(&self) -> bool {
+        self.max_solvers != 0 && self.solvers >= self.max_solvers
+    }
+
+    /// Leading zero bits a proof for this task needs right now.
+    pub fn proof_difficulty(&self, state: &GlobalState) -> u8 {
+        match self.difficulty {
+            0 => state.pow_difficulty,
+            difficulty => difficulty,
+        }
+    }
+
+    /// Reward for a proof of this task before any attestation or stake boost.
+    pub fn proof_reward(&self, state: &GlobalState) -> u64 {
+        match self.reward {
+            0 => state.pow_reward,
+            reward => reward,
+        }
+    }
+}
+
+/// Argument to `create_task`. Proofs are accepted from `opens_at` until just before `closes_at`; a
+/// zero `reward` or `difficulty` follows the pool's values.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TaskParams {
+    pub description_hash: [u8; 32],
+    pub reward: u64,
+    pub difficulty: u8,
+    pub opens_at: i64,
+    pub closes_at: i64,
+    pub max_solvers: u32,
+}
+
+/// Parameter updates that must pass through the timelock once `timelock_delay` is non-zero.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum ParamChange {
+    Apr { apr_bps: u64 },
+    FaucetCap { faucet_cap: u64 },
+    OracleAuthority { new_authority: Pubkey },
+    TimelockDelay { delay: i64 },
+    FaucetLimits { cooldown: i64, window: i64, window_cap: u64, daily_budget: u64 },
+    FaucetGate { gate: u8, pow_difficulty: u8 },
+    PowChallenge { window_slots: u64 },
+    PowHashAlgorithm { algorithm: u8 },
+}
+
+impl ParamChange {
+    // enum tag + largest variant (Pubkey, or the four faucet limits)
+    pub const SPACE: usize = 1 + 32;
+}
+
+/// Automatic `pow_difficulty` adjustment towards `target_proofs` accepted per `window` seconds.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PowRetarget {
+    /// Zero disables retargeting.
+    pub window: i64,
+    pub target_proofs: u64,
+    /// Most bits the difficulty moves per retarget.
+    pub max_step: u8,
+    pub min_difficulty: u8,
+    pub max_difficulty: u8,
+}
+
+impl PowRetarget {
+    pub const SPACE: usize = 8 + 8 + 1 + 1 + 1;
+}
+
+/// Stake `record_proof` requires, and the boost on its reward. The boost grows with the square
+/// root of `amount_staked / full_boost_stake` and stops growing at `full_boost_stake`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProofStakePolicy {
+    pub min_stake: u64,
+    /// Zero disables the boost.
+    pub full_boost_stake: u64,
+    /// Extra reward at `full_boost_stake`, in bps of the task or attested reward.
+    pub max_boost_bps: u64,
+}
+
+impl ProofStakePolicy {
+    pub const SPACE: usize = 8 + 8 + 8;
+}
+
+/// Oracle terms for one `record_proof`, signed over `proof_attestation_message` by the
+/// `oracle_authority` in the preceding ed25519 instruction.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofAttestation {
+    pub reward: u64,
+    pub expiry: i64,
+}
+
+/// One `(task_id, nonce)` pair of a `record_proofs` batch.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct ProofSubmission {
+    pub task_id: u64,
+    pub nonce: u64,
+}
+
+/// What a faucet claim presents to pass `GlobalState::faucet_gate`. Ungated pools ignore it.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FaucetProof {
+    None,
+    /// Nonce for `faucet_pow_hash`.
+    Pow { nonce: u64 },
+    /// Expiry of the oracle signature carried by the preceding ed25519 instruction.
+    Attestation { expiry: i64 },
+}
+
+#[account]
+pub struct PendingChange {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub change: ParamChange,
+    pub queued_ts: i64,
+    pub eta: i64,
+    pub bump: u8,
+}
+
+impl PendingChange {
+    pub const SPACE: usize = 8 + 32 + ParamChange::SPACE + 8 + 8 + 1;
+
+    /// Past the grace period after `eta`, the change can no longer be executed.
+    pub fn is_expired(&self, now: i64) -> bool {
+        now > self.eta.saturating_add(TIMELOCK_GRACE_PERIOD)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct RewardsPreview {
+    pub owner: Pubkey,
+    pub amount_staked: u64,
+    pub accrued_since_last: u64,
+    pub pending_rewards: u64,
+    pub apr_bps: u64,
+    pub timestamp: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UnstakePreview {
+    pub amount: u64,
+    pub remaining_staked: u64,
+    pub pending_rewards: u64,
+    pub total_staked_after: u64,
+    pub timestamp: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProofCheck {
+    pub hash: [u8; 32],
+    pub difficulty: u8,
+    pub meets_difficulty: bool,
+    pub is_replay: bool,
+    /// What `record_proof` without an attestation pays, boost included; 0 when it would fail.
+    pub reward: u64,
+    /// Within the task's open window and below its solver limit.
+    pub is_open: bool,
+    /// `user` stakes at least the pool's `min_stake`.
+    pub meets_min_stake: bool,
+    /// Challenge mode is off, or `user` committed to a slot hash recently enough.
+    pub is_challenge_fresh: bool,
+    /// The pool only takes attested proofs, so the reward is whatever the oracle attests.
+    pub requires_attestation: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PoolInfo {
+    pub admin: Pubkey,
+    pub oracle_authority: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub vault_balance: u64,
+    pub apr_bps: u64,
+    pub total_staked: u64,
+    pub faucet_cap: u64,
+    pub pow_reward: u64,
+    pub pow_difficulty: u8,
+    pub oracle_nonce: u64,
+    pub timelock_delay: i64,
+    pub paused: u8,
+    pub version: u8,
+    pub faucet_cooldown: i64,
+    pub faucet_window: i64,
+    pub faucet_window_cap: u64,
+    pub faucet_daily_budget: u64,
+    /// Minted so far in the current UTC day.
+    pub faucet_day_minted: u64,
+    pub faucet_budget: u64,
+    pub faucet_minted: u64,
+    pub faucet_gate: u8,
+    pub faucet_pow_difficulty: u8,
+    pub pow_retarget: PowRetarget,
+    pub pow_window_start: i64,
+    pub pow_window_proofs: u64,
+    pub require_proof_attestation: bool,
+    pub proof_stake_policy: ProofStakePolicy,
+    pub pow_challenge_slots: u64,
+    pub pow_hash_algorithm: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoleMember {
+    pub key: Pubkey,
+    pub permissions: u8,
+}
+
+#[account]
+pub struct Roles {
+    pub members: Vec<RoleMember>,
+    pub bump: u8,
+}
+
+impl Roles {
+    // vec len (u32) + MAX_ROLE_MEMBERS * (32 + 1) + bump
+    pub const SPACE: usize = 4 + MAX_ROLE_MEMBERS * (32 + 1) + 1;
+
+    pub fn permissions(&self, key: &Pubkey) -> u8 {
+        self.members
+            .iter()
+            .find(|m| m.key == *key)
+            .map_or(0, |m| m.permissions)
+    }
+
+    pub fn has(&self, key: &Pubkey, role: u8) -> bool {
+        self.permissions(key) & role == role
+    }
+
+    fn grant(&mut self, key: Pubkey, permissions: u8) -> Result<u8> {
+        if let Some(member) = self.members.iter_mut().find(|m| m.key == key) {
+            member.permissions |= permissions;
+            return Ok(member.permissions);
+        }
+        require!(self.members.len() < MAX_ROLE_MEMBERS, SkillStakeError::RoleCapacityExceeded);
+        self.members.push(RoleMember { key, permissions });
+        Ok(permissions)
+    }
+
+    fn revoke(&mut self, key: Pubkey, permissions: u8) -> u8 {
+        let mut remaining = 0;
+        if let Some(member) = self.members.iter_mut().find(|m| m.key == key) {
+            member.permissions &= !permissions;
+            remaining = member.permissions;
+        }
+        self.members.retain(|m| m.permissions != 0);
+        remaining
+    }
+}
+
+/// Oracle keys that replace `oracle_authority` once created: every oracle-signed message needs
+/// signatures from `threshold` distinct members.
+#[account]
+pub struct OracleSet {
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub bump: u8,
+    pub version: u8,
+}
+
+impl OracleSet {
+    // vec len (u32) + MAX_ORACLE_MEMBERS keys + threshold + bump + version
+    pub const SPACE: usize = 4 + MAX_ORACLE_MEMBERS * 32 + 1 + 1 + 1;
+    pub const VERSION: u8 = 1;
+
+    /// Up to `MAX_ORACLE_MEMBERS` distinct, non-default members and a threshold of 1 to their count.
+    pub fn is_valid(&self) -> bool {
+        let members = &self.members;
+        members.len() <= MAX_ORACLE_MEMBERS
+            && (1..=members.len()).contains(&usize::from(self.threshold))
+            && members
+                .iter()
+                .enumerate()
+                .all(|(i, key)| *key != Pubkey::default() && !members[..i].contains(key))
+    }
+
+    fn verify(&self, instructions: &AccountInfo, message: &[u8]) -> Result<()> {
+        verify_oracle_signatures(instructions, &self.members, self.threshold, message)
+    }
+}
+
+#[event]
+pub struct Initialized {
+    pub admin: Pubkey,
+    pub oracle_authority: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub apr_bps: u64,
+    pub faucet_cap: u64,
+    pub pow_reward: u64,
+    pub pow_difficulty: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RolesInitialized {
+    pub admin: Pubkey,
+    pub roles: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub admin: Pubkey,
+    pub member: Pubkey,
+    pub granted: u8,
+    pub permissions_before: u8,
+    pub permissions_after: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub admin: Pubkey,
+    pub member: Pubkey,
+    pub revoked: u8,
+    pub permissions_before: u8,
+    pub permissions_after: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OracleSetInitialized {
+    pub admin: Pubkey,
+    pub oracle_set: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OracleMemberAdded {
+    pub admin: Pubkey,
+    pub member: Pubkey,
+    pub member_count: u8,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OracleMemberRemoved {
+    pub admin: Pubkey,
+    pub member: Pubkey,
+    pub member_count: u8,
+    pub threshold: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OracleThresholdUpdated {
+    pub admin: Pubkey,
+    pub threshold_before: u8,
+    pub threshold_after: u8,
+    pub timestamp: i64,
+}
+
+/// `before` holds the previous value in the same variant as `after`.
+#[event]
+pub struct ParamChanged {
+    pub authority: Pubkey,
+    pub pending_change_id: Option<u64>,
+    pub before: ParamChange,
+    pub after: ParamChange,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PausedUpdated {
+    pub authority: Pubkey,
+    pub paused_before: u8,
+    pub paused_after: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChangeQueued {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub change: ParamChange,
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ChangeCancelled {
+    pub id: u64,
+    pub authority: Pubkey,
+    pub change: ParamChange,
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub payer: Pubkey,
+    pub version_before: u8,
+    pub version_after: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PowConfigUpdated {
+    pub authority: Pubkey,
+    pub pow_difficulty_before: u8,
+    pub pow_difficulty_after: u8,
+    pub pow_reward_before: u64,
+    pub pow_reward_after: u64,
+    pub oracle_nonce_before: u64,
+    pub oracle_nonce_after: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PowRetargetUpdated {
+    pub authority: Pubkey,
+    pub before: PowRetarget,
+    pub after: PowRetarget,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProofStakePolicyUpdated {
+    pub authority: Pubkey,
+    pub before: ProofStakePolicy,
+    pub after: ProofStakePolicy,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PowChallengeUpdated {
+    pub authority: Pubkey,
+    pub window_slots_before: u64,
+    pub window_slots_after: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PowHashAlgorithmUpdated {
+    pub authority: Pubkey,
+    pub algorithm_before: u8,
+    pub algorithm_after: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PowChallengeCommitted {
+    pub user: Pubkey,
+    pub slot_before: u64,
+    pub slot: u64,
+    pub slot_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TaskCreated {
+    pub authority: Pubkey,
+    pub task_id: u64,
+    pub params: TaskParams,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProofAttestationUpdated {
+    pub authority: Pubkey,
+    pub required_before: bool,
+    pub required_after: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PowDifficultyRetargeted {
+    pub window_start: i64,
+    pub window_proofs: u64,
+    pub elapsed: i64,
+    pub difficulty_before: u8,
+    pub difficulty_after: u8,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Staked {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub amount_staked_before: u64,
+    pub amount_staked_after: u64,
+    pub total_staked_before: u64,
+    pub total_staked_after: u64,
+    pub pending_rewards: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Unstaked {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub amount_staked_before: u64,
+    pub amount_staked_after: u64,
+    pub total_staked_before: u64,
+    pub total_staked_after: u64,
+    pub pending_rewards: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Claimed {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct EmergencyWithdrawn {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub forfeited_rewards: u64,
+    pub total_staked_before: u64,
+    pub total_staked_after: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FaucetClaimed {
+    pub user: Pubkey,
+    pub amount: u64,
+    pub faucet_claimed_before: u64,
+    pub faucet_claimed_after: u64,
+    pub window_claimed_after: u64,
+    pub day_minted_after: u64,
+    pub faucet_minted_after: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FaucetBudgetToppedUp {
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub faucet_budget_before: u64,
+    pub faucet_budget_after: u64,
+    pub faucet_minted: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted by the claim that used up the last of the budget.
+#[event]
+pub struct FaucetBudgetExhausted {
+    pub faucet_budget: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ProofRecorded {
+    pub user: Pubkey,
+    pub task_id: u64,
+    pub nonce: u64,
+    pub hash: [u8; 32],
+    pub reward: u64,
+    pub last_task_id_before: u64,
+    pub pending_rewards_before: u64,
+    pub pending_rewards_after: u64,
+    pub timestamp: i64,
+}
+
+#[error_code]
+pub enum SkillStakeError {
+    #[msg("APR exceeds the maximum for this cluster")]
+    AprTooHigh,
+    #[msg("Operation would overflow maths")]
+    MathOverflow,
+    #[msg("Provided amount must be greater than zero")]
+    InvalidAmount,
+    #[msg("Nothing to claim yet")]
+    NothingToClaim,
+    #[msg("Insufficient staked balance")]
+    InsufficientStake,
+    #[msg("Caller is not authorized for this action")]
+    Unauthorized,
+    #[msg("Missing PDA bump in context")]
+    BumpNotFound,
+    #[msg("Provided mint does not match program state")]
+    MintMismatch,
+    #[msg("Proof payload too large")]
+    ProofTooLarge,
+    #[msg("Faucet cap exceeded for this wallet")]
+    FaucetCapExceeded,
+    #[msg("Oracle authority must be a valid public key")]
+    InvalidOracleAuthority,
+    #[msg("Proof of work difficulty is invalid for this program")]
+    InvalidPowDifficulty,
+    #[msg("Oracle update nonce is stale")]
+    StaleOracleUpdate,
+    #[msg("Proof does not satisfy the difficulty target")]
+    ProofDifficultyNotMet,
+    #[msg("Task identifier has already been used")]
+    ProofTaskReplay,
+    #[msg("Timelock is active; queue this change instead")]
+    TimelockActive,
+    #[msg("Execution time is earlier than the timelock delay allows")]
+    TimelockTooShort,
+    #[msg("Timelock delay has not elapsed yet")]
+    TimelockNotElapsed,
+    #[msg("Timelock delay has already elapsed")]
+    TimelockElapsed,
+    #[msg("Timelock delay is out of range")]
+    InvalidTimelockDelay,
+    #[msg("This instruction is currently paused")]
+    InstructionPaused,
+    #[msg("Unknown pause flag bits")]
+    InvalidPauseFlags,
+    #[msg("Role permissions are invalid")]
+    InvalidRole,
+    #[msg("Roles account is full")]
+    RoleCapacityExceeded,
+    #[msg("Account layout is outdated; run the migration instruction first")]
+    AccountNotMigrated,
+    #[msg("Account layout is already current")]
+    AccountAlreadyMigrated,
+    #[msg("Faucet cooldown has not elapsed for this wallet")]
+    FaucetCooldownActive,
+    #[msg("Faucet rolling window cap exceeded for this wallet")]
+    FaucetWindowCapExceeded,
+    #[msg("Faucet daily budget exhausted")]
+    FaucetDailyBudgetExceeded,
+    #[msg("Faucet limits are invalid")]
+    InvalidFaucetLimits,
+    #[msg("Faucet lifetime budget exceeded; awaiting top-up")]
+    FaucetBudgetExceeded,
+    #[msg("Faucet is not available on this cluster")]
+    FaucetUnavailable,
+    #[msg("Faucet gate mode or difficulty is invalid")]
+    InvalidFaucetGate,
+    #[msg("Faucet proof does not match the configured gate")]
+    FaucetProofMismatch,
+    #[msg("Expected an ed25519 oracle attestation before this instruction")]
+    AttestationMissing,
+    #[msg("Oracle attestation signer or message does not match")]
+    AttestationMismatch,
+    #[msg("Oracle attestation has expired")]
+    AttestationExpired,
+    #[msg("PoW retarget configuration is invalid")]
+    InvalidRetargetConfig,
+    #[msg("Task configuration is invalid")]
+    InvalidTaskConfig,
+    #[msg("Task is not open yet")]
+    TaskNotOpen,
+    #[msg("Task is closed")]
+    TaskClosed,
+    #[msg("Task has reached its solver limit")]
+    TaskFull,
+    #[msg("Task is too far below the highest recorded task")]
+    ProofTaskTooOld,
+    #[msg("Proof stake policy is invalid")]
+    InvalidProofStakePolicy,
+    #[msg("Stake is below the minimum for submitting proofs")]
+    InsufficientProofStake,
+    #[msg("PoW challenge window is invalid")]
+    InvalidPowChallengeConfig,
+    #[msg("Commit to a slot hash before submitting proofs")]
+    PowChallengeMissing,
+    #[msg("Committed slot hash is too old")]
+    PowChallengeExpired,
+    #[msg("Unknown PoW hash algorithm")]
+    InvalidPowHashAlgorithm,
+    #[msg("Proof batch is empty, too large, or does not match its task accounts")]
+    InvalidProofBatch,
+    #[msg("Oracle set members or threshold are invalid")]
+    InvalidOracleSet,
+    #[msg("Fewer distinct oracle members signed than the threshold")]
+    OracleThresholdNotMet,
+    #[msg("Queued change expired before it was executed")]
+    ChangeExpired,
+}
+
+/// Admin passes every role check; anyone else needs all bits of `role`.
+fn require_role(state: &GlobalState, roles: &AccountInfo, key: Pubkey, role: u8) -> Result<()> {
+    if key == state.admin {
+        return Ok(());
+    }
+    require!(
+        load_roles(roles)?.is_some_and(|roles| roles.has(&key, role)),
+        SkillStakeError::Unauthorized
+    );
+    Ok(())
+}
+
+/// The roles account, or `None` before `initialize_roles`, when nobody but the admin holds a role.
+fn load_roles(info: &AccountInfo) -> Result<Option<Roles>> {
+    if info.owner == &system_program::ID {
+        return Ok(None);
+    }
+    require_keys_eq!(*info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+    Ok(Some(Roles::try_deserialize(&mut &info.try_borrow_data()?[..])?))
+}
+
+fn authorize_change(
+    state: &GlobalState,
+    roles: &AccountInfo,
+    key: Pubkey,
+    change: &ParamChange,
+) -> Result<()> {
+    match change {
+        ParamChange::Apr { .. } => require_role(state, roles, key, ROLE_APR_MANAGER),
+        ParamChange::FaucetCap { .. } | ParamChange::FaucetLimits { .. } | ParamChange::FaucetGate { .. } => {
+            require_role(state, roles, key, ROLE_FAUCET_MANAGER)
+        }
+        ParamChange::PowChallenge { .. } | ParamChange::PowHashAlgorithm { .. } => {
+            require_role(state, roles, key, ROLE_POW_MANAGER)
+        }
+        ParamChange::OracleAuthority { .. } | ParamChange::TimelockDelay { .. } => {
+            require_keys_eq!(state.admin, key, SkillStakeError::Unauthorized);
+            Ok(())
+        }
+    }
+}
+
+fn validate_change(change: &ParamChange) -> Result<()> {
+    // Mainnet builds have no faucet, so its settings cannot be queued either.
+    #[cfg(not(feature = "devnet"))]
+    require!(
+        !matches!(
+            change,
+            ParamChange::FaucetCap { .. } | ParamChange::FaucetLimits { .. } | ParamChange::FaucetGate { .. }
+        ),
+        SkillStakeError::FaucetUnavailable
+    );
+    match change {
+        ParamChange::Apr { apr_bps } => {
+            require!(*apr_bps <= MAX_APR_BPS, SkillStakeError::AprTooHigh);
+        }
+        ParamChange::FaucetCap { .. } => {}
+        ParamChange::OracleAuthority { new_authority } => {
+            require!(*new_authority != Pubkey::default(), SkillStakeError::InvalidOracleAuthority);
+        }
+        ParamChange::TimelockDelay { delay } => {
+            require!(
+                (0..=MAX_TIMELOCK_DELAY).contains(delay),
+                SkillStakeError::InvalidTimelockDelay
+            );
+        }
+        ParamChange::FaucetLimits { cooldown, window, window_cap, .. } => {
+            require!(*cooldown >= 0 && *window >= 0, SkillStakeError::InvalidFaucetLimits);
+            // A window needs a cap and a cap needs a window.
+            require!((*window == 0) == (*window_cap == 0), SkillStakeError::InvalidFaucetLimits);
+        }
+        ParamChange::FaucetGate { gate, pow_difficulty } => {
+            require!(
+                *gate <= FAUCET_GATE_ORACLE && *pow_difficulty <= MAX_POW_DIFFICULTY,
+                SkillStakeError::InvalidFaucetGate
+            );
+        }
+        ParamChange::PowChallenge { window_slots } => {
+            require!(
+                *window_slots <= MAX_POW_CHALLENGE_SLOTS,
+                SkillStakeError::InvalidPowChallengeConfig
+            );
+        }
+        ParamChange::PowHashAlgorithm { algorithm } => {
+            require!(*algorithm <= POW_HASH_BLAKE3, SkillStakeError::InvalidPowHashAlgorithm);
+        }
+    }
+    Ok(())
+}
+
+/// Applies `change` and returns the replaced value as the same variant.
+fn apply_change(state: &mut GlobalState, change: &ParamChange) -> Result<ParamChange> {
+    validate_change(change)?;
+    let previous = match *change {
+        ParamChange::Apr { apr_bps } => ParamChange::Apr {
+            apr_bps: std::mem::replace(&mut state.apr_bps, apr_bps),
+        },
+        ParamChange::FaucetCap { faucet_cap } => ParamChange::FaucetCap {
+            faucet_cap: std::mem::replace(&mut state.faucet_cap, faucet_cap),
+        },
+        ParamChange::OracleAuthority { new_authority } => ParamChange::OracleAuthority {
+            new_authority: std::mem::replace(&mut state.oracle_authority, new_authority),
+        },
+        ParamChange::TimelockDelay { delay } => ParamChange::TimelockDelay {
+            delay: std::mem::replace(&mut state.timelock_delay, delay),
+        },
+        ParamChange::FaucetLimits {
+            cooldown,
+            window,
+            window_cap,
+            daily_budget,
+        } => ParamChange::FaucetLimits {
+            cooldown: std::mem::replace(&mut state.faucet_cooldown, cooldown),
+            window: std::mem::replace(&mut state.faucet_window, window),
+            window_cap: std::mem::replace(&mut state.faucet_window_cap, window_cap),
+            daily_budget: std::mem::replace(&mut state.faucet_daily_budget, daily_budget),
+        },
+        ParamChange::FaucetGate { gate, pow_difficulty } => ParamChange::FaucetGate {
+            gate: std::mem::replace(&mut state.faucet_gate, gate),
+            pow_difficulty: std::mem::replace(&mut state.faucet_pow_difficulty, pow_difficulty),
+        },
+        ParamChange::PowChallenge { window_slots } => ParamChange::PowChallenge {
+            window_slots: std::mem::replace(&mut state.pow_challenge_slots, window_slots),
+        },
+        ParamChange::PowHashAlgorithm { algorithm } => ParamChange::PowHashAlgorithm {
+            algorithm: std::mem::replace(&mut state.pow_hash_algorithm, algorithm),
+        },
+    };
+    Ok(previous)
+}
+
+#[cfg(feature = "devnet")]
+fn update_faucet_cap_handler(ctx: Context<RoleUpdate>, faucet_cap: u64) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    require_role(state, &ctx.accounts.roles, ctx.accounts.authority.key(), ROLE_FAUCET_MANAGER)?;
+    require!(state.timelock_delay == 0, SkillStakeError::TimelockActive);
+
+    let after = ParamChange::FaucetCap { faucet_cap };
+    let before = apply_change(state, &after)?;
+
+    emit_cpi!(ParamChanged {
+        authority: ctx.accounts.authority.key(),
+        pending_change_id: None,
+        before,
+        after,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[cfg(feature = "devnet")]
+fn set_faucet_limits_handler(
+    ctx: Context<RoleUpdate>,
+    cooldown: i64,
+    window: i64,
+    window_cap: u64,
+    daily_budget: u64,
+) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    require_role(state, &ctx.accounts.roles, ctx.accounts.authority.key(), ROLE_FAUCET_MANAGER)?;
+    require!(state.timelock_delay == 0, SkillStakeError::TimelockActive);
+
+    let after = ParamChange::FaucetLimits {
+        cooldown,
+        window,
+        window_cap,
+        daily_budget,
+    };
+    let before = apply_change(state, &after)?;
+
+    emit_cpi!(ParamChanged {
+        authority: ctx.accounts.authority.key(),
+        pending_change_id: None,
+        before,
+        after,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[cfg(feature = "devnet")]
+fn set_faucet_gate_handler(ctx: Context<RoleUpdate>, gate: u8, pow_difficulty: u8) -> Result<()> {
+    let state = &mut ctx.accounts.state;
+    require_role(state, &ctx.accounts.roles, ctx.accounts.authority.key(), ROLE_FAUCET_MANAGER)?;
+    require!(state.timelock_delay == 0, SkillStakeError::TimelockActive);
+
+    let after = ParamChange::FaucetGate { gate, pow_difficulty };
+    let before = apply_change(state, &after)?;
+
+    emit_cpi!(ParamChanged {
+        authority: ctx.accounts.authority.key(),
+        pending_change_id: None,
+        before,
+        after,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[cfg(feature = "devnet")]
+fn top_up_faucet_budget_handler(ctx: Context<RoleUpdate>, amount: u64) -> Result<()> {
+    require!(amount > 0, SkillStakeError::InvalidAmount);
+
+    let state = &mut ctx.accounts.state;
+    require_role(state, &ctx.accounts.roles, ctx.accounts.authority.key(), ROLE_TREASURY_MANAGER)?;
+
+    let faucet_budget_before = state.faucet_budget;
+    state.faucet_budget = state
+        .faucet_budget
+        .checked_add(amount)
+        .ok_or(SkillStakeError::MathOverflow)?;
+
+    emit_cpi!(FaucetBudgetToppedUp {
+        authority: ctx.accounts.authority.key(),
+        amount,
+        faucet_budget_before,
+        faucet_budget_after: state.faucet_budget,
+        faucet_minted: state.faucet_minted,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[cfg(feature = "devnet")]
+fn faucet_handler(ctx: Context<Faucet>, amount: u64, proof: FaucetProof) -> Result<()> {
+    require!(amount > 0, SkillStakeError::InvalidAmount);
+    let now = Clock::get()?.unix_timestamp;
+
+    let state = &mut ctx.accounts.state;
+    require!(!state.is_paused(PAUSE_FAUCET), SkillStakeError::InstructionPaused);
+    require_keys_eq!(state.mint, ctx.accounts.mint.key(), SkillStakeError::MintMismatch);
+
+    let stake_account = &mut ctx.accounts.stake_account;
+    if stake_account.owner == Pubkey::default() {
+        stake_account.owner = ctx.accounts.user.key();
+        stake_account.bump = ctx.bumps.stake_account;
+        stake_account.version = StakeAccount::VERSION;
+    } else {
+        require_keys_eq!(stake_account.owner, ctx.accounts.user.key(), SkillStakeError::Unauthorized);
+        require!(stake_account.is_current(), SkillStakeError::AccountNotMigrated);
+    }
+
+    let new_total = stake_account
+        .faucet_claimed
+        .checked_add(amount)
+        .ok_or(SkillStakeError::MathOverflow)?;
+    require!(new_total <= state.faucet_cap, SkillStakeError::FaucetCapExceeded);
+
+    let attested = check_faucet_gate(
+        state,
+        &stake_account.owner,
+        amount,
+        stake_account.faucet_claimed,
+        proof,
+        now,
+    )?;
+    if let Some(message) = attested {
+        verify_oracle_message(state, &ctx.accounts.oracle_set, &ctx.accounts.instructions, &message)?;
+    }
+
+    if state.faucet_cooldown > 0 && stake_account.last_faucet_ts != 0 {
+        let ready_at = stake_account
+            .last_faucet_ts
+            .checked_add(state.faucet_cooldown)
+            .ok_or(SkillStakeError::MathOverflow)?;
+        require!(now >= ready_at, SkillStakeError::FaucetCooldownActive);
+    }
+
+    // The window opens at the first claim after the previous one closed.
+    if state.faucet_window > 0 {
+        let window_end = stake_account
+            .faucet_window_start
+            .checked_add(state.faucet_window)
+            .ok_or(SkillStakeError::MathOverflow)?;
+        if now >= window_end {
+            stake_account.faucet_window_start = now;
+            stake_account.faucet_window_claimed = 0;
+        }
+        stake_account.faucet_window_claimed = stake_account
+            .faucet_window_claimed
+            .checked_add(amount)
+            .ok_or(SkillStakeError::MathOverflow)?;
+        require!(
+            stake_account.faucet_window_claimed <= state.faucet_window_cap,
+            SkillStakeError::FaucetWindowCapExceeded
+        );
+    }
+
+    // Minted amounts are tracked per UTC day even while no budget is set.
+    let day = now.div_euclid(SECONDS_PER_DAY);
+    if state.faucet_day != day {
+        state.faucet_day = day;
+        state.faucet_day_minted = 0;
+    }
+    state.faucet_day_minted = state
+        .faucet_day_minted
+        .checked_add(amount)
+        .ok_or(SkillStakeError::MathOverflow)?;
+    require!(
+        state.faucet_daily_budget == 0 || state.faucet_day_minted <= state.faucet_daily_budget,
+        SkillStakeError::FaucetDailyBudgetExceeded
+    );
+
+    state.faucet_minted = state
+        .faucet_minted
+        .checked_add(amount)
+        .ok_or(SkillStakeError::MathOverflow)?;
+    require!(
+        state.faucet_minted <= state.faucet_budget,
+        SkillStakeError::FaucetBudgetExceeded
+    );
+
+    // signer seeds (NO temporaries)
+    let mint_auth_bump_seed = [state.mint_auth_bump];
+    let mint_auth_signer: &[&[u8]] = &[MINT_AUTH_SEED, &mint_auth_bump_seed];
+    let signer: &[&[&[u8]]] = &[mint_auth_signer];
+
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.mint.to_account_info(),
+        to:   ctx.accounts.user_token.to_account_info(),
+        authority: ctx.accounts.mint_auth.to_account_info(),
+    };
+    let cpi_program = ctx.accounts.token_program.to_account_info();
+    token::mint_to(
+        CpiContext::new_with_signer(cpi_program, cpi_accounts, signer),
+        amount,
+    )?;
+
+    let faucet_claimed_before = stake_account.faucet_claimed;
+    stake_account.faucet_claimed = new_total;
+    stake_account.last_faucet_ts = now;
+
+    emit_cpi!(FaucetClaimed {
+        user: ctx.accounts.user.key(),
+        amount,
+        faucet_claimed_before,
+        faucet_claimed_after: new_total,
+        window_claimed_after: stake_account.faucet_window_claimed,
+        day_minted_after: state.faucet_day_minted,
+        faucet_minted_after: state.faucet_minted,
+        timestamp: now,
+    });
+    // The faucet shuts itself off here until the budget is topped up.
+    if state.faucet_minted == state.faucet_budget {
+        emit_cpi!(FaucetBudgetExhausted {
+            faucet_budget: state.faucet_budget,
+            timestamp: now,
+        });
+    }
+    Ok(())
+}
+
+/// Checks `proof` against the configured gate. Proofs commit to the wallet's claimed total, which
+/// every claim raises, so each one is good for a single claim. Returns the message an oracle
+/// attestation must be signed over, for the caller to verify.
+#[cfg(feature = "devnet")]
+fn check_faucet_gate(
+    state: &GlobalState,
+    user: &Pubkey,
+    amount: u64,
+    faucet_claimed: u64,
+    proof: FaucetProof,
+    now: i64,
+) -> Result<Option<Vec<u8>>> {
+    match (state.faucet_gate, proof) {
+        (FAUCET_GATE_NONE, _) => Ok(None),
+        (FAUCET_GATE_POW, FaucetProof::Pow { nonce }) => {
+            let hash = faucet_pow_hash(user, &state.mint, faucet_claimed, nonce);
+            require!(
+                meets_difficulty(&hash, state.faucet_pow_difficulty),
+                SkillStakeError::ProofDifficultyNotMet
+            );
+            Ok(None)
+        }
+        (FAUCET_GATE_ORACLE, FaucetProof::Attestation { expiry }) => {
+            require!(now <= expiry, SkillStakeError::AttestationExpired);
+            let message = faucet_attestation_message(user, &state.mint, amount, faucet_claimed, expiry);
+            Ok(Some(message))
+        }
+        _ => err!(SkillStakeError::FaucetProofMismatch),
+    }
+}
+
+/// Moves the pool to a new PoW difficulty and reward once `oracle_nonce` exceeds the last one.
+fn apply_pow_config(
+    state: &mut GlobalState,
+    authority: Pubkey,
+    pow_difficulty: u8,
+    pow_reward: u64,
+    oracle_nonce: u64,
+    now: i64,
+) -> Result<PowConfigUpdated> {
+    require!(oracle_nonce > state.oracle_nonce, SkillStakeError::StaleOracleUpdate);
+
+    let event = PowConfigUpdated {
+        authority,
+        pow_difficulty_before: state.pow_difficulty,
+        pow_difficulty_after: pow_difficulty,
+        pow_reward_before: state.pow_reward,
+        pow_reward_after: pow_reward,
+        oracle_nonce_before: state.oracle_nonce,
+        oracle_nonce_after: oracle_nonce,
+        timestamp: now,
+    };
+
+    state.pow_difficulty = pow_difficulty;
+    state.pow_reward = pow_reward;
+    state.oracle_nonce = oracle_nonce;
+    // An override holds for a full retarget window before the rate is measured again.
+    state.pow_window_start = now;
+    state.pow_window_proofs = 0;
+    Ok(event)
+}
+
+/// Checks `message` against the oracle set once it exists, otherwise against `oracle_authority`.
+fn verify_oracle_message(
+    state: &GlobalState,
+    oracle_set: &AccountInfo,
+    instructions: &AccountInfo,
+    message: &[u8],
+) -> Result<()> {
+    match load_oracle_set(oracle_set)? {
+        Some(oracle_set) => oracle_set.verify(instructions, message),
+        None => verify_oracle_signatures(instructions, &[state.oracle_authority], 1, message),
+    }
+}
+
+fn validate_task_params(params: &TaskParams) -> Result<()> {
+    require!(
+        params.difficulty <= MAX_POW_DIFFICULTY && params.closes_at > params.opens_at,
+        SkillStakeError::InvalidTaskConfig
+    );
+    Ok(())
+}
+
+fn init_task(task: &mut Task, task_id: u64, params: &TaskParams, bump: u8) {
+    task.task_id = task_id;
+    task.description_hash = params.description_hash;
+    task.reward = params.reward;
+    task.opens_at = params.opens_at;
+    task.closes_at = params.closes_at;
+    task.max_solvers = params.max_solvers;
+    task.solvers = 0;
+    task.difficulty = params.difficulty;
+    task.bump = bump;
+    task.version = Task::VERSION;
+}
+
+/// The oracle set behind its PDA, or `None` before `initialize_oracle_set`.
+fn load_oracle_set(info: &AccountInfo) -> Result<Option<OracleSet>> {
+    if info.owner == &system_program::ID {
+        return Ok(None);
+    }
+    require_keys_eq!(*info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+    Ok(Some(OracleSet::try_deserialize(&mut &info.try_borrow_data()?[..])?))
+}
+
+/// Requires the instructions right before this one to be ed25519 precompile checks of `message` in
+/// which at least `threshold` distinct `signers`, and nobody else, signed it. The signatures may
+/// share one precompile instruction or be split across several consecutive ones. The runtime has
+/// already verified every signature.
+fn verify_oracle_signatures(
+    instructions: &AccountInfo,
+    signers: &[Pubkey],
+    threshold: u8,
+    message: &[u8],
+) -> Result<()> {
+    use anchor_lang::solana_program::ed25519_program;
+    use sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+
+    let current = load_current_index_checked(instructions)?;
+    let mut signed: Vec<&Pubkey> = Vec::new();
+    for index in (0..current).rev() {
+        let ix = load_instruction_at_checked(usize::from(index), instructions)?;
+        if ix.program_id != ed25519_program::ID {
+            break;
+        }
+        collect_ed25519_signers(&ix.data, signers, message, &mut signed)?;
+    }
+    require!(!signed.is_empty(), SkillStakeError::AttestationMissing);
+    require!(signed.len() >= usize::from(threshold), SkillStakeError::OracleThresholdNotMet);
+    Ok(())
+}
+
+/// Adds the `signers` behind each signature in one ed25519 precompile instruction to `signed`.
+/// Every signature must be over `message` by one of `signers`.
+fn collect_ed25519_signers<'a>(
+    data: &[u8],
+    signers: &'a [Pubkey],
+    message: &[u8],
+    signed: &mut Vec<&'a Pubkey>,
+) -> Result<()> {
+    // Precompile data: signature count, padding, then one offsets record per signature.
+    const ED25519_OFFSETS_START: usize = 2;
+    const ED25519_OFFSETS_SIZE: usize = 14;
+
+    let count = usize::from(data.first().copied().unwrap_or(0));
+    require!(
+        count > 0 && data.len() >= ED25519_OFFSETS_START + count * ED25519_OFFSETS_SIZE,
+        SkillStakeError::AttestationMismatch
+    );
+    let slice = |offset: u16, len: usize| data.get(usize::from(offset)..usize::from(offset) + len);
+    for record in 0..count {
+        let field = |i: usize| {
+            let at = ED25519_OFFSETS_START + record * ED25519_OFFSETS_SIZE + 2 * i;
+            u16::from_le_bytes([data[at], data[at + 1]])
+        };
+        // (signature_offset, signature_ix, pubkey_offset, pubkey_ix, message_offset, message_size, message_ix)
+        // Each index must be u16::MAX, i.e. the precompile instruction itself; otherwise the verified
+        // bytes could live somewhere other than where they are read here.
+        require!(
+            field(1) == u16::MAX && field(3) == u16::MAX && field(6) == u16::MAX,
+            SkillStakeError::AttestationMismatch
+        );
+        require!(
+            slice(field(4), usize::from(field(5))) == Some(message),
+            SkillStakeError::AttestationMismatch
+        );
+        let signer = signers
+            .iter()
+            .find(|signer| slice(field(2), 32) == Some(signer.as_ref()))
+            .ok_or(SkillStakeError::AttestationMismatch)?;
+        if !signed.contains(&signer) {
+            signed.push(signer);
+        }
+    }
+    Ok(())
+}
+
+/// Newest `(slot, hash)` entry of the `SlotHashes` sysvar.
+fn newest_slot_hash(slot_hashes: &AccountInfo) -> Result<(u64, [u8; 32])> {
+    // bincode: u64 entry count, then (slot: u64, hash: [u8; 32]) pairs, newest first.
+    let data = slot_hashes.try_borrow_data()?;
+    let entry = data
+        .get(8..48)
+        .filter(|_| data[..8] != [0; 8])
+        .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+    let (slot, hash) = entry.split_at(8);
+    let mut slot_bytes = [0; 8];
+    slot_bytes.copy_from_slice(slot);
+    let mut slot_hash = [0; 32];
+    slot_hash.copy_from_slice(hash);
+    Ok((u64::from_le_bytes(slot_bytes), slot_hash))
+}
+
+fn read_layout_version(info: &AccountInfo, discriminator: [u8; 8], offset: usize) -> Result<u8> {
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() > offset && data[..8] == discriminator,
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    Ok(data[offset])
+}
+
+/// `N` bytes at `offset` of a program account with `discriminator`, whatever its layout version.
+fn read_fixed<const N: usize>(info: &AccountInfo, discriminator: [u8; 8], offset: usize) -> Result<[u8; N]> {
+    require_keys_eq!(*info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+    let data = info.try_borrow_data()?;
+    require!(
+        data.len() >= offset + N && data[..8] == discriminator,
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    let mut bytes = [0; N];
+    bytes.copy_from_slice(&data[offset..offset + N]);
+    Ok(bytes)
+}
+
+/// Overwrites `bytes` at `offset`; the counterpart of `read_fixed`.
+fn write_fixed(info: &AccountInfo, offset: usize, bytes: &[u8]) -> Result<()> {
+    let mut data = info.try_borrow_mut_data()?;
+    data.get_mut(offset..offset + bytes.len())
+        .ok_or(ErrorCode::AccountDidNotDeserialize)?
+        .copy_from_slice(bytes);
+    Ok(())
+}
+
+/// Rejects a proof for `task_id` that is replayed, too old, or outside the task's limits.
+fn check_proof_task(stake_account: &StakeAccount, task: &Task, task_id: u64, now: i64) -> Result<()> {
+    require!(!stake_account.is_task_expired(task_id), SkillStakeError::ProofTaskTooOld);
+    require!(!stake_account.is_task_recorded(task_id), SkillStakeError::ProofTaskReplay);
+    require!(now >= task.opens_at, SkillStakeError::TaskNotOpen);
+    require!(now < task.closes_at, SkillStakeError::TaskClosed);
+    require!(!task.is_full(), SkillStakeError::TaskFull);
+    Ok(())
+}
+
+/// The proof's hash under the pool's algorithm, mined over the owner's fresh challenge when
+/// challenge mode is on; it must meet `difficulty`.
+fn verify_proof_hash(
+    state: &GlobalState,
+    stake_account: &StakeAccount,
+    task_id: u64,
+    nonce: u64,
+    difficulty: u8,
+    slot: u64,
+) -> Result<[u8; 32]> {
+    let user = &stake_account.owner;
+    let hash = if state.pow_challenge_slots > 0 {
+        let (slot_hash, is_fresh) = stake_account
+            .pow_challenge(state.pow_challenge_slots, slot)
+            .ok_or(SkillStakeError::PowChallengeMissing)?;
+        require!(is_fresh, SkillStakeError::PowChallengeExpired);
+        challenge_pow_hash(state.pow_hash_algorithm, user, &state.mint, task_id, &slot_hash, nonce)
+    } else {
+        pow_hash(state.pow_hash_algorithm, user, &state.mint, task_id, nonce)
+    };
+    require!(meets_difficulty(&hash, difficulty), SkillStakeError::ProofDifficultyNotMet);
+    Ok(hash)
+}
+
+/// Credits the stake-boosted `base_reward` of a verified proof and marks its task solved; returns
+/// the entry for the proof history.
+fn credit_proof(
+    state: &GlobalState,
+    stake_account: &mut StakeAccount,
+    task: &mut Task,
+    record: ProofRecord,
+    base_reward: u64,
+    now: i64,
+) -> Result<ProofHistoryEntry> {
+    let reward = boosted_proof_reward(&state.proof_stake_policy, stake_account.amount_staked, base_reward)?;
+    task.solvers = task.solvers.checked_add(1).ok_or(SkillStakeError::MathOverflow)?;
+
+    if reward > 0 {
+        stake_account.pending_rewards = stake_account
+            .pending_rewards
+            .checked_add(reward)
+            .ok_or(SkillStakeError::MathOverflow)?;
+    }
+
+    let encoded = record.try_to_vec()?;
+    require!(encoded.len() <= MAX_PROOF_STORAGE, SkillStakeError::ProofTooLarge);
+    stake_account.last_proof = encoded;
+    stake_account.last_proof_ts = now;
+    stake_account.record_task(record.task_id);
+
+    Ok(ProofHistoryEntry { record, reward, timestamp: now })
+}
+
+/// Counts `proofs` towards the retarget window. The first proof after the window ends closes it,
+/// counting towards the measured rate; returns the retarget to emit.
+fn count_retarget_proofs(state: &mut GlobalState, proofs: u64, now: i64) -> Option<PowDifficultyRetargeted> {
+    if state.pow_retarget.window == 0 {
+        return None;
+    }
+    state.pow_window_proofs = state.pow_window_proofs.saturating_add(proofs);
+    let elapsed = now.saturating_sub(state.pow_window_start);
+    if elapsed < state.pow_retarget.window {
+        return None;
+    }
+    let difficulty_before = state.pow_difficulty;
+    state.pow_difficulty = retarget_difficulty(
+        &state.pow_retarget,
+        difficulty_before,
+        state.pow_window_proofs,
+        elapsed,
+    );
+    let retargeted = PowDifficultyRetargeted {
+        window_start: state.pow_window_start,
+        window_proofs: state.pow_window_proofs,
+        elapsed,
+        difficulty_before,
+        difficulty_after: state.pow_difficulty,
+        timestamp: now,
+    };
+    state.pow_window_start = now;
+    state.pow_window_proofs = 0;
+    Some(retargeted)
+}
+
+/// The registered `Task` behind a `record_proofs` remaining account. Only `create_task` makes
+/// program-owned `Task` accounts, at the PDA of the id it stores, so a matching `task_id` pins the
+/// address.
+fn load_batch_task(info: &AccountInfo, task_id: u64) -> Result<Task> {
+    require_keys_neq!(*info.owner, system_program::ID, ErrorCode::AccountNotInitialized);
+    require_keys_eq!(*info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+    require!(info.is_writable, ErrorCode::ConstraintMut);
+    let task = Task::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    require!(task.task_id == task_id, ErrorCode::ConstraintSeeds);
+    Ok(task)
+}
+
+/// Records `entries` in `user`'s `ProofHistory`, creating the account on the first proof and
+/// growing it until it holds `MAX_PROOF_HISTORY` entries.
+fn append_proof_history<'info>(
+    info: &AccountInfo<'info>,
+    user: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    bump: u8,
+    entries: Vec<ProofHistoryEntry>,
+) -> Result<()> {
+    let payer = user.to_account_info();
+    let mut history = if info.owner == &system_program::ID {
+        // Allocate and assign rather than create_account, which fails on a pre-funded address.
+        let seeds: &[&[u8]] = &[PROOF_HISTORY_SEED, user.key.as_ref(), &[bump]];
+        let len = 8 + ProofHistory::space(0);
+        let shortfall = Rent::get()?.minimum_balance(len).saturating_sub(info.lamports());
+        if shortfall > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    Transfer {
+                        from: payer.clone(),
+                        to: info.clone(),
+                    },
+                ),
+                shortfall,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                system_program::Allocate { account_to_allocate: info.clone() },
+                &[seeds],
+            ),
+            len as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program.to_account_info(),
+                system_program::Assign { account_to_assign: info.clone() },
+                &[seeds],
+            ),
+            &crate::ID,
+        )?;
+        ProofHistory {
+            owner: user.key(),
+            bump,
+            version: ProofHistory::VERSION,
+            head: 0,
+            entries: Vec::new(),
+        }
+    } else {
+        require_keys_eq!(*info.owner, crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+        ProofHistory::try_deserialize(&mut &info.try_borrow_data()?[..])?
+    };
+
+    let len = (history.entries.len() + entries.len()).min(MAX_PROOF_HISTORY);
+    if history.entries.len() < len {
+        grow_account(info, &payer, system_program, 8 + ProofHistory::space(len))?;
+    }
+    for entry in entries {
+        history.push(entry);
+    }
+    history.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+/// Tops up rent from `payer` and reallocs `info` to `new_len`; never shrinks.
+fn grow_account<'info>(
+    info: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    new_len: usize,
+) -> Result<()> {
+    if info.data_len() >= new_len {
+        return Ok(());
+    }
+
+    let required = Rent::get()?.minimum_balance(new_len);
+    let shortfall = required.saturating_sub(info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                Transfer {
+                    from: payer.clone(),
+                    to: info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    info.realloc(new_len, true)?;
+    Ok(())
+}
+
+/// True when `hash` starts with at least `difficulty` zero bits.
+pub fn meets_difficulty(hash: &[u8; 32], difficulty: u8) -> bool {
+    if difficulty == 0 {
+        return true;
+    }
     let mut remaining = difficulty;
     for byte in hash.iter() {
         let zeros = byte.leading_zeros() as u8;
@@ -597,18 +3729,229 @@ fn meets_difficulty(hash: &[u8; 32], difficulty: u8) -> bool {
     false
 }
 
-fn accrue_rewards(
-    stake_account: &mut Account<StakeAccount>,
-    apr_bps: u64,
-    now_ts: i64,
-) -> Result<()> {
-    let elapsed = now_ts.checked_sub(stake_account.last_accrued_ts).unwrap_or_default();
-    if elapsed <= 0 || stake_account.amount_staked == 0 {
-        stake_account.last_accrued_ts = now_ts;
-        return Ok(());
+/// Proof-of-work hash for `nonce`, bound to the miner, the pool mint and the task.
+pub fn pow_hash(algorithm: u8, user: &Pubkey, mint: &Pubkey, task_id: u64, nonce: u64) -> [u8; 32] {
+    pow_hashv(
+        algorithm,
+        &[
+            POW_DOMAIN,
+            user.as_ref(),
+            mint.as_ref(),
+            &task_id.to_le_bytes(),
+            &nonce.to_le_bytes(),
+        ],
+    )
+}
+
+/// Proof-of-work hash under challenge mode: `pow_hash` with the committed slot hash mixed in.
+pub fn challenge_pow_hash(
+    algorithm: u8,
+    user: &Pubkey,
+    mint: &Pubkey,
+    task_id: u64,
+    slot_hash: &[u8; 32],
+    nonce: u64,
+) -> [u8; 32] {
+    pow_hashv(
+        algorithm,
+        &[
+            POW_DOMAIN,
+            user.as_ref(),
+            mint.as_ref(),
+            &task_id.to_le_bytes(),
+            slot_hash,
+            &nonce.to_le_bytes(),
+        ],
+    )
+}
+
+/// `parts` concatenated and hashed with `algorithm`; unknown values fall back to keccak256.
+pub fn pow_hashv(algorithm: u8, parts: &[&[u8]]) -> [u8; 32] {
+    match algorithm {
+        POW_HASH_SHA256 => anchor_lang::solana_program::hash::hashv(parts).to_bytes(),
+        POW_HASH_BLAKE3 => {
+            let mut hasher = blake3::Hasher::new();
+            for part in parts {
+                hasher.update(part);
+            }
+            *hasher.finalize().as_bytes()
+        }
+        _ => keccak::hashv(parts).0,
+    }
+}
+
+/// `MAX_PROOF_BATCH`, halved for blake3, which runs in-program rather than as a syscall.
+pub fn max_proof_batch(algorithm: u8) -> usize {
+    if algorithm == POW_HASH_BLAKE3 {
+        MAX_PROOF_BATCH / 2
+    } else {
+        MAX_PROOF_BATCH
+    }
+}
+
+/// Difficulty after `proofs` were accepted over `elapsed` seconds. Each doubling (halving) of the
+/// rate against `target_proofs` per `window` adds (removes) one bit, up to `max_step` bits, and the
+/// result is clamped to `min_difficulty..=max_difficulty`.
+pub fn retarget_difficulty(retarget: &PowRetarget, difficulty: u8, proofs: u64, elapsed: i64) -> u8 {
+    // Compare proofs * window against target_proofs * elapsed; both products fit in u128.
+    let actual = u128::from(proofs) * retarget.window.max(0) as u128;
+    let expected = u128::from(retarget.target_proofs) * elapsed.max(0) as u128;
+    let (mut low, high) = if actual >= expected { (expected, actual) } else { (actual, expected) };
+    let mut step: u8 = 0;
+    while step < retarget.max_step {
+        match low.checked_mul(2) {
+            Some(doubled) if doubled <= high && high > 0 => {
+                low = doubled;
+                step += 1;
+            }
+            _ => break,
+        }
+    }
+    let adjusted = if actual >= expected {
+        difficulty.saturating_add(step)
+    } else {
+        difficulty.saturating_sub(step)
+    };
+    adjusted.max(retarget.min_difficulty).min(retarget.max_difficulty)
+}
+
+/// `reward` plus the `policy` boost for `amount_staked`.
+pub fn boosted_proof_reward(policy: &ProofStakePolicy, amount_staked: u64, reward: u64) -> Result<u64> {
+    if policy.full_boost_stake == 0 {
+        return Ok(reward);
+    }
+    // sqrt(stake / full) == sqrt(stake * full) / full, and stake * full fits in u128.
+    let full = u128::from(policy.full_boost_stake);
+    let stake = u128::from(amount_staked).min(full);
+    let boost_bps = u128::from(policy.max_boost_bps) * isqrt(stake * full) / full;
+    let boosted = u128::from(reward) * (u128::from(BPS_DENOMINATOR) + boost_bps)
+        / u128::from(BPS_DENOMINATOR);
+    u64::try_from(boosted).map_err(|_| error!(SkillStakeError::MathOverflow))
+}
+
+/// Floor of the square root of `n`.
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    // Newton's method from a power of two at or above the root.
+    let mut x = 1u128 << (128 - n.leading_zeros()).div_ceil(2);
+    loop {
+        let next = (x + n / x) / 2;
+        if next >= x {
+            return x;
+        }
+        x = next;
+    }
+}
+
+/// Faucet proof-of-work hash for `nonce`, bound to the claimant, the pool mint and their claimed total.
+pub fn faucet_pow_hash(user: &Pubkey, mint: &Pubkey, faucet_claimed: u64, nonce: u64) -> [u8; 32] {
+    keccak::hashv(&[
+        FAUCET_POW_DOMAIN,
+        user.as_ref(),
+        mint.as_ref(),
+        &faucet_claimed.to_le_bytes(),
+        &nonce.to_le_bytes(),
+    ])
+    .0
+}
+
+/// Message the oracle signs to let `user` claim `amount` until `expiry`, once per claimed total.
+pub fn faucet_attestation_message(
+    user: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    faucet_claimed: u64,
+    expiry: i64,
+) -> Vec<u8> {
+    [
+        FAUCET_ATTESTATION_DOMAIN,
+        user.as_ref(),
+        mint.as_ref(),
+        &amount.to_le_bytes(),
+        &faucet_claimed.to_le_bytes(),
+        &expiry.to_le_bytes(),
+    ]
+    .concat()
+}
+
+/// Message the oracle signs to pay `reward` for `task_id` when recorded by `user` before `expiry`.
+pub fn proof_attestation_message(
+    user: &Pubkey,
+    mint: &Pubkey,
+    task_id: u64,
+    reward: u64,
+    expiry: i64,
+) -> Vec<u8> {
+    [
+        PROOF_ATTESTATION_DOMAIN,
+        user.as_ref(),
+        mint.as_ref(),
+        &task_id.to_le_bytes(),
+        &reward.to_le_bytes(),
+        &expiry.to_le_bytes(),
+    ]
+    .concat()
+}
+
+/// Message the oracle set signs to move the pool to `pow_difficulty` and `pow_reward`; the nonce
+/// must exceed the pool's `oracle_nonce`, so each message applies once.
+pub fn pow_config_message(mint: &Pubkey, pow_difficulty: u8, pow_reward: u64, oracle_nonce: u64) -> Vec<u8> {
+    [
+        POW_CONFIG_DOMAIN,
+        mint.as_ref(),
+        &[pow_difficulty],
+        &pow_reward.to_le_bytes(),
+        &oracle_nonce.to_le_bytes(),
+    ]
+    .concat()
+}
+
+/// Message the oracle, or its oracle set, signs off-chain for `relay_pow_config`. Unlike
+/// `pow_config_message` it expires, so a relayer cannot hold it back indefinitely.
+pub fn relayed_pow_config_message(
+    mint: &Pubkey,
+    pow_difficulty: u8,
+    pow_reward: u64,
+    oracle_nonce: u64,
+    expiry: i64,
+) -> Vec<u8> {
+    [
+        RELAYED_POW_CONFIG_DOMAIN,
+        mint.as_ref(),
+        &[pow_difficulty],
+        &pow_reward.to_le_bytes(),
+        &oracle_nonce.to_le_bytes(),
+        &expiry.to_le_bytes(),
+    ]
+    .concat()
+}
+
+/// Message the oracle, or its oracle set, signs off-chain for `relay_create_task`.
+pub fn task_message(mint: &Pubkey, task_id: u64, params: &TaskParams) -> Vec<u8> {
+    [
+        TASK_DOMAIN,
+        mint.as_ref(),
+        &task_id.to_le_bytes(),
+        &params.description_hash,
+        &params.reward.to_le_bytes(),
+        &[params.difficulty],
+        &params.opens_at.to_le_bytes(),
+        &params.closes_at.to_le_bytes(),
+        &params.max_solvers.to_le_bytes(),
+    ]
+    .concat()
+}
+
+/// Rewards earned by `amount_staked` at `apr_bps` between `last_accrued_ts` and `now_ts`.
+pub fn accrued_rewards(amount_staked: u64, apr_bps: u64, last_accrued_ts: i64, now_ts: i64) -> Result<u64> {
+    let elapsed = now_ts.saturating_sub(last_accrued_ts);
+    if elapsed <= 0 || amount_staked == 0 {
+        return Ok(0);
     }
 
-    let stake_amount = stake_account.amount_staked as u128;
+    let stake_amount = amount_staked as u128;
     let apr = apr_bps as u128;
     let elapsed_u = elapsed as u128;
 
@@ -621,9 +3964,21 @@ fn accrue_rewards(
         .ok_or(SkillStakeError::MathOverflow)?;
     let newly_accrued = numerator.checked_div(denominator).ok_or(SkillStakeError::MathOverflow)?;
 
+    Ok(u64::try_from(newly_accrued).map_err(|_| SkillStakeError::MathOverflow)?)
+}
+
+/// Adds rewards accrued up to `now_ts` to `pending_rewards` and moves the accrual clock.
+pub fn accrue_rewards(stake_account: &mut StakeAccount, apr_bps: u64, now_ts: i64) -> Result<()> {
+    let newly_accrued = accrued_rewards(
+        stake_account.amount_staked,
+        apr_bps,
+        stake_account.last_accrued_ts,
+        now_ts,
+    )?;
+
     stake_account.pending_rewards = stake_account
         .pending_rewards
-        .checked_add(newly_accrued as u64)
+        .checked_add(newly_accrued)
         .ok_or(SkillStakeError::MathOverflow)?;
     stake_account.last_accrued_ts = now_ts;
 
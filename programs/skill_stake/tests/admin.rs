@@ -0,0 +1,534 @@
+//! Initialization, roles, parameter updates, pausing and oracle configuration.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use skill_stake::{
+    relayed_pow_config_message, Initialized, ParamChange, ParamChanged, PausedUpdated,
+    PowConfigUpdated, RoleGranted, RoleRevoked, SkillStakeError, PAUSE_ALL, PAUSE_CLAIM,
+    PAUSE_FAUCET, PAUSE_RECORD_PROOF, PAUSE_STAKE, ROLE_ALL, ROLE_APR_MANAGER, ROLE_FAUCET_MANAGER,
+    ROLE_PAUSER, ROLE_POW_MANAGER,
+};
+use skill_stake_client::{instructions, pda};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+#[test]
+fn initialize_sets_state_and_emits_event() {
+    let config = Config::default();
+    let mut pool = Pool::uninitialized(&config);
+    let initialize = pool.initialize_ix(&config);
+    let meta = pool.send_admin(&[initialize]).unwrap();
+
+    let state = pool.state();
+    assert_eq!(state.admin, pool.admin.pubkey());
+    assert_eq!(state.oracle_authority, pool.admin.pubkey());
+    assert_eq!(state.mint, pool.mint);
+    assert_eq!(state.vault, pda::vault().0);
+    assert_eq!(state.apr_bps, config.apr_bps);
+    assert_eq!(state.faucet_cap, config.faucet_cap);
+    assert_eq!(state.pow_reward, config.pow_reward);
+    assert_eq!(state.pow_difficulty, config.pow_difficulty);
+    assert_eq!(state.total_staked, 0);
+    assert_eq!(state.version, skill_stake::GlobalState::VERSION);
+    assert_eq!(pool.vault_balance(), 0);
+
+    let event: Initialized = meta.event();
+    assert_eq!(event.admin, pool.admin.pubkey());
+    assert_eq!(event.vault, pda::vault().0);
+    assert_eq!(event.timestamp, pool.svm.now());
+}
+
+#[test]
+fn initialize_uses_explicit_oracle_authority() {
+    let oracle = Pubkey::new_unique();
+    let pool = Pool::with_config(Config {
+        oracle_authority: Some(oracle),
+        ..Config::default()
+    });
+    assert_eq!(pool.state().oracle_authority, oracle);
+}
+
+#[test]
+fn initialize_rejects_apr_above_limit() {
+    let config = Config {
+        apr_bps: 5 * skill_stake::BPS_DENOMINATOR + 1,
+        ..Config::default()
+    };
+    let mut pool = Pool::uninitialized(&config);
+    let initialize = pool.initialize_ix(&config);
+    assert_error(pool.send_admin(&[initialize]), SkillStakeError::AprTooHigh);
+}
+
+#[test]
+fn initialize_rejects_difficulty_above_limit() {
+    let config = Config {
+        pow_difficulty: skill_stake::MAX_POW_DIFFICULTY + 1,
+        ..Config::default()
+    };
+    let mut pool = Pool::uninitialized(&config);
+    let initialize = pool.initialize_ix(&config);
+    assert_error(
+        pool.send_admin(&[initialize]),
+        SkillStakeError::InvalidPowDifficulty,
+    );
+}
+
+#[test]
+fn initialize_twice_fails() {
+    let config = Config::default();
+    let mut pool = Pool::with_config(Config::default());
+    let initialize = pool.initialize_ix(&config);
+    assert!(pool.send_admin(&[initialize]).is_err());
+}
+
+#[test]
+fn initialize_roles_requires_admin() {
+    let config = Config::default();
+    let mut pool = Pool::uninitialized(&config);
+    let initialize = pool.initialize_ix(&config);
+    pool.send_admin(&[initialize]).unwrap();
+
+    let outsider = pool.outsider();
+    let result = pool.send(
+        &[instructions::initialize_roles(&outsider.pubkey())],
+        &outsider,
+    );
+    assert_anchor_error(result, ErrorCode::ConstraintHasOne);
+
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::initialize_roles(&admin)])
+        .unwrap();
+    assert!(pool.roles().members.is_empty());
+}
+
+#[test]
+fn privileged_updates_work_before_initialize_roles() {
+    let oracle = Keypair::new();
+    let config = Config {
+        oracle_authority: Some(oracle.pubkey()),
+        ..Config::default()
+    };
+    let mut pool = Pool::uninitialized(&config);
+    let initialize = pool.initialize_ix(&config);
+    pool.send_admin(&[initialize]).unwrap();
+    assert!(pool.svm.account(&pda::roles().0).is_none());
+
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[
+        instructions::set_apr(&admin, 2_000),
+        instructions::update_faucet_cap(&admin, 5 * TOKEN),
+    ])
+    .unwrap();
+    pool.svm.airdrop(&oracle.pubkey(), SOL);
+    pool.send(
+        &[instructions::set_pow_config(&oracle.pubkey(), 12, TOKEN, 1)],
+        &oracle,
+    )
+    .unwrap();
+    let new_oracle = Pubkey::new_unique();
+    pool.send_admin(&[instructions::set_oracle_authority(&admin, &new_oracle)])
+        .unwrap();
+
+    let state = pool.state();
+    assert_eq!((state.apr_bps, state.faucet_cap), (2_000, 5 * TOKEN));
+    assert_eq!(state.pow_difficulty, 12);
+    assert_eq!(state.oracle_authority, new_oracle);
+
+    // Without a roles account nobody but the admin holds a role.
+    let outsider = pool.outsider();
+    let result = pool.send(&[instructions::set_apr(&outsider.pubkey(), 0)], &outsider);
+    assert_error(result, SkillStakeError::Unauthorized);
+}
+
+#[test]
+fn grant_and_revoke_roles() {
+    let mut pool = Pool::new();
+    let admin = pool.admin.pubkey();
+    let member = Pubkey::new_unique();
+
+    let meta = pool
+        .send_admin(&[instructions::grant_role(&admin, &member, ROLE_PAUSER)])
+        .unwrap();
+    let event: RoleGranted = meta.event();
+    assert_eq!(
+        (event.permissions_before, event.permissions_after),
+        (0, ROLE_PAUSER)
+    );
+
+    pool.send_admin(&[instructions::grant_role(&admin, &member, ROLE_APR_MANAGER)])
+        .unwrap();
+    assert_eq!(
+        pool.roles().permissions(&member),
+        ROLE_PAUSER | ROLE_APR_MANAGER
+    );
+
+    let meta = pool
+        .send_admin(&[instructions::revoke_role(&admin, &member, ROLE_PAUSER)])
+        .unwrap();
+    let event: RoleRevoked = meta.event();
+    assert_eq!(event.permissions_after, ROLE_APR_MANAGER);
+
+    pool.send_admin(&[instructions::revoke_role(&admin, &member, ROLE_ALL)])
+        .unwrap();
+    assert!(pool.roles().members.is_empty());
+}
+
+#[test]
+fn grant_role_rejects_invalid_permissions() {
+    let mut pool = Pool::new();
+    let admin = pool.admin.pubkey();
+    let member = Pubkey::new_unique();
+
+    let result = pool.send_admin(&[instructions::grant_role(&admin, &member, 0)]);
+    assert_error(result, SkillStakeError::InvalidRole);
+    let result = pool.send_admin(&[instructions::grant_role(&admin, &member, ROLE_ALL + 1)]);
+    assert_error(result, SkillStakeError::InvalidRole);
+    let result = pool.send_admin(&[instructions::grant_role(
+        &admin,
+        &Pubkey::default(),
+        ROLE_PAUSER,
+    )]);
+    assert_error(result, SkillStakeError::InvalidRole);
+    let result = pool.send_admin(&[instructions::revoke_role(&admin, &member, 0)]);
+    assert_error(result, SkillStakeError::InvalidRole);
+}
+
+#[test]
+fn roles_account_has_fixed_capacity() {
+    let mut pool = Pool::new();
+    let admin = pool.admin.pubkey();
+    for _ in 0..skill_stake::MAX_ROLE_MEMBERS {
+        pool.send_admin(&[instructions::grant_role(
+            &admin,
+            &Pubkey::new_unique(),
+            ROLE_PAUSER,
+        )])
+        .unwrap();
+    }
+    let result = pool.send_admin(&[instructions::grant_role(
+        &admin,
+        &Pubkey::new_unique(),
+        ROLE_PAUSER,
+    )]);
+    assert_error(result, SkillStakeError::RoleCapacityExceeded);
+}
+
+#[test]
+fn only_admin_manages_roles() {
+    let mut pool = Pool::new();
+    let outsider = pool.outsider();
+    let member = Pubkey::new_unique();
+
+    let result = pool.send(
+        &[instructions::grant_role(
+            &outsider.pubkey(),
+            &member,
+            ROLE_PAUSER,
+        )],
+        &outsider,
+    );
+    assert_anchor_error(result, ErrorCode::ConstraintHasOne);
+    let result = pool.send(
+        &[instructions::revoke_role(
+            &outsider.pubkey(),
+            &member,
+            ROLE_PAUSER,
+        )],
+        &outsider,
+    );
+    assert_anchor_error(result, ErrorCode::ConstraintHasOne);
+}
+
+#[test]
+fn set_apr_by_admin_and_apr_manager() {
+    let mut pool = Pool::new();
+    let admin = pool.admin.pubkey();
+
+    let meta = pool
+        .send_admin(&[instructions::set_apr(&admin, 2_500)])
+        .unwrap();
+    let event: ParamChanged = meta.event();
+    assert_eq!(event.before, ParamChange::Apr { apr_bps: 1_000 });
+    assert_eq!(event.after, ParamChange::Apr { apr_bps: 2_500 });
+    assert_eq!(event.pending_change_id, None);
+
+    let manager = pool.role_member(ROLE_APR_MANAGER);
+    pool.send(&[instructions::set_apr(&manager.pubkey(), 3_000)], &manager)
+        .unwrap();
+    assert_eq!(pool.state().apr_bps, 3_000);
+}
+
+#[test]
+fn set_apr_rejects_unauthorized_and_out_of_range() {
+    let mut pool = Pool::new();
+    let admin = pool.admin.pubkey();
+
+    let result = pool.send_admin(&[instructions::set_apr(&admin, 50_001)]);
+    assert_error(result, SkillStakeError::AprTooHigh);
+
+    let outsider = pool.outsider();
+    let result = pool.send(&[instructions::set_apr(&outsider.pubkey(), 100)], &outsider);
+    assert_error(result, SkillStakeError::Unauthorized);
+
+    // A different role does not imply the APR manager role.
+    let pauser = pool.role_member(ROLE_PAUSER);
+    let result = pool.send(&[instructions::set_apr(&pauser.pubkey(), 100)], &pauser);
+    assert_error(result, SkillStakeError::Unauthorized);
+}
+
+#[test]
+fn update_faucet_cap_requires_faucet_manager() {
+    let mut pool = Pool::new();
+    let manager = pool.role_member(ROLE_FAUCET_MANAGER);
+    pool.send(
+        &[instructions::update_faucet_cap(
+            &manager.pubkey(),
+            7 * TOKEN,
+        )],
+        &manager,
+    )
+    .unwrap();
+    assert_eq!(pool.state().faucet_cap, 7 * TOKEN);
+
+    let outsider = pool.outsider();
+    let result = pool.send(
+        &[instructions::update_faucet_cap(&outsider.pubkey(), 1)],
+        &outsider,
+    );
+    assert_error(result, SkillStakeError::Unauthorized);
+}
+
+#[test]
+fn set_oracle_authority_is_admin_only() {
+    let mut pool = Pool::new();
+    let admin = pool.admin.pubkey();
+    let oracle = Pubkey::new_unique();
+
+    pool.send_admin(&[instructions::set_oracle_authority(&admin, &oracle)])
+        .unwrap();
+    assert_eq!(pool.state().oracle_authority, oracle);
+
+    let result = pool.send_admin(&[instructions::set_oracle_authority(
+        &admin,
+        &Pubkey::default(),
+    )]);
+    assert_error(result, SkillStakeError::InvalidOracleAuthority);
+
+    let outsider = pool.outsider();
+    let result = pool.send(
+        &[instructions::set_oracle_authority(
+            &outsider.pubkey(),
+            &outsider.pubkey(),
+        )],
+        &outsider,
+    );
+    assert_anchor_error(result, ErrorCode::ConstraintHasOne);
+}
+
+#[test]
+fn pause_flags_block_their_instructions() {
+    let mut pool = Pool::with_config(Config {
+        pow_difficulty: 0,
+        ..Config::default()
+    });
+    let user = pool.user(100 * TOKEN);
+    pool.stake(&user, 10 * TOKEN).unwrap();
+    pool.svm.warp(86_400);
+
+    let pauser = pool.role_member(ROLE_PAUSER);
+    let meta = pool
+        .send(
+            &[instructions::set_paused(&pauser.pubkey(), PAUSE_ALL)],
+            &pauser,
+        )
+        .unwrap();
+    let event: PausedUpdated = meta.event();
+    assert_eq!((event.paused_before, event.paused_after), (0, PAUSE_ALL));
+
+    assert_error(pool.stake(&user, TOKEN), SkillStakeError::InstructionPaused);
+    assert_error(pool.claim(&user), SkillStakeError::InstructionPaused);
+    assert_error(
+        pool.faucet(&user, TOKEN),
+        SkillStakeError::InstructionPaused,
+    );
+    assert_error(
+        pool.record_proof(&user, 1, 0),
+        SkillStakeError::InstructionPaused,
+    );
+
+    // Exits stay open while paused.
+    pool.unstake(&user, TOKEN).unwrap();
+    pool.emergency_withdraw(&user).unwrap();
+    assert_eq!(pool.state().total_staked, 0);
+}
+
+#[test]
+fn pause_flags_are_independent() {
+    let mut pool = Pool::with_config(Config {
+        pow_difficulty: 0,
+        ..Config::default()
+    });
+    let user = pool.user(100 * TOKEN);
+    let admin = pool.admin.pubkey();
+
+    for flag in [PAUSE_STAKE, PAUSE_CLAIM, PAUSE_FAUCET, PAUSE_RECORD_PROOF] {
+        pool.send_admin(&[instructions::set_paused(&admin, PAUSE_ALL & !flag)])
+            .unwrap();
+        pool.svm.warp(86_400);
+        match flag {
+            PAUSE_STAKE => drop(pool.stake(&user, TOKEN).unwrap()),
+            PAUSE_FAUCET => drop(pool.faucet(&user, TOKEN).unwrap()),
+            PAUSE_RECORD_PROOF => drop(pool.record_proof(&user, 1, 0).unwrap()),
+            _ => drop(pool.claim(&user).unwrap()),
+        }
+    }
+}
+
+#[test]
+fn set_paused_rejects_unknown_bits_and_outsiders() {
+    let mut pool = Pool::new();
+    let admin = pool.admin.pubkey();
+    let result = pool.send_admin(&[instructions::set_paused(&admin, PAUSE_ALL + 1)]);
+    assert_error(result, SkillStakeError::InvalidPauseFlags);
+
+    let outsider = pool.outsider();
+    let result = pool.send(
+        &[instructions::set_paused(&outsider.pubkey(), PAUSE_STAKE)],
+        &outsider,
+    );
+    assert_error(result, SkillStakeError::Unauthorized);
+}
+
+#[test]
+fn set_pow_config_by_oracle_and_pow_manager() {
+    let oracle = Keypair::new();
+    let mut pool = Pool::with_config(Config {
+        oracle_authority: Some(oracle.pubkey()),
+        ..Config::default()
+    });
+    pool.svm.airdrop(&oracle.pubkey(), SOL);
+
+    let meta = pool
+        .send(
+            &[instructions::set_pow_config(
+                &oracle.pubkey(),
+                12,
+                5 * TOKEN,
+                1,
+            )],
+            &oracle,
+        )
+        .unwrap();
+    let event: PowConfigUpdated = meta.event();
+    assert_eq!(
+        (event.pow_difficulty_before, event.pow_difficulty_after),
+        (8, 12)
+    );
+    assert_eq!(
+        (event.oracle_nonce_before, event.oracle_nonce_after),
+        (0, 1)
+    );
+
+    let manager = pool.role_member(ROLE_POW_MANAGER);
+    pool.send(
+        &[instructions::set_pow_config(&manager.pubkey(), 4, TOKEN, 2)],
+        &manager,
+    )
+    .unwrap();
+    let state = pool.state();
+    assert_eq!(
+        (state.pow_difficulty, state.pow_reward, state.oracle_nonce),
+        (4, TOKEN, 2)
+    );
+}
+
+#[test]
+fn set_pow_config_rejects_stale_nonce_bad_difficulty_and_outsiders() {
+    let mut pool = Pool::new();
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::set_pow_config(&admin, 8, TOKEN, 5)])
+        .unwrap();
+
+    let result = pool.send_admin(&[instructions::set_pow_config(&admin, 8, TOKEN, 5)]);
+    assert_error(result, SkillStakeError::StaleOracleUpdate);
+    let result = pool.send_admin(&[instructions::set_pow_config(&admin, 8, TOKEN, 4)]);
+    assert_error(result, SkillStakeError::StaleOracleUpdate);
+
+    let result = pool.send_admin(&[instructions::set_pow_config(
+        &admin,
+        skill_stake::MAX_POW_DIFFICULTY + 1,
+        TOKEN,
+        6,
+    )]);
+    assert_error(result, SkillStakeError::InvalidPowDifficulty);
+
+    let outsider = pool.outsider();
+    let result = pool.send(
+        &[instructions::set_pow_config(
+            &outsider.pubkey(),
+            8,
+            TOKEN,
+            6,
+        )],
+        &outsider,
+    );
+    assert_error(result, SkillStakeError::Unauthorized);
+}
+
+#[test]
+fn relay_pow_config_applies_oracle_signed_messages() {
+    let oracle = Keypair::new();
+    let mut pool = Pool::with_config(Config {
+        oracle_authority: Some(oracle.pubkey()),
+        ..Config::default()
+    });
+    let relayer = pool.outsider();
+    let expiry = pool.svm.now() + 60;
+    let message = relayed_pow_config_message(&pool.mint, 12, 5 * TOKEN, 1, expiry);
+    let attest =
+        instructions::ed25519_verify(&oracle.pubkey(), &oracle.sign_message(&message), &message);
+    let relay =
+        |nonce| instructions::relay_pow_config(&relayer.pubkey(), 12, 5 * TOKEN, nonce, expiry);
+
+    assert_error(
+        pool.send(&[relay(1)], &relayer),
+        SkillStakeError::AttestationMissing,
+    );
+    // The signature binds the nonce, and only the oracle's counts.
+    assert_error(
+        pool.send(&[attest.clone(), relay(2)], &relayer),
+        SkillStakeError::AttestationMismatch,
+    );
+    let forged =
+        instructions::ed25519_verify(&relayer.pubkey(), &relayer.sign_message(&message), &message);
+    assert_error(
+        pool.send(&[forged, relay(1)], &relayer),
+        SkillStakeError::AttestationMismatch,
+    );
+
+    let event: PowConfigUpdated = pool
+        .send(&[attest.clone(), relay(1)], &relayer)
+        .unwrap()
+        .event();
+    assert_eq!(event.authority, relayer.pubkey());
+    assert_eq!(
+        (event.pow_difficulty_after, event.pow_reward_after),
+        (12, 5 * TOKEN)
+    );
+    assert_eq!(pool.state().oracle_nonce, 1);
+    assert_error(
+        pool.send(&[attest, relay(1)], &relayer),
+        SkillStakeError::StaleOracleUpdate,
+    );
+
+    let message = relayed_pow_config_message(&pool.mint, 12, 5 * TOKEN, 2, expiry);
+    let attest =
+        instructions::ed25519_verify(&oracle.pubkey(), &oracle.sign_message(&message), &message);
+    pool.svm.warp(61);
+    assert_error(
+        pool.send(&[attest, relay(2)], &relayer),
+        SkillStakeError::AttestationExpired,
+    );
+}
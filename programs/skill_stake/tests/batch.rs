@@ -0,0 +1,261 @@
+//! `record_proofs`: several proofs, and their total reward, in one transaction.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use skill_stake::{
+    PowDifficultyRetargeted, ProofHistory, ProofRecorded, SkillStakeError, MAX_PROOF_BATCH,
+    POW_HASH_BLAKE3, POW_HASH_KECCAK256,
+};
+use skill_stake_client::{
+    instructions, pda, record_proofs_compute_units, PowRetarget, ProofSubmission,
+};
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::signature::Signer;
+
+fn setup(difficulty: u8) -> (Pool, User) {
+    let mut pool = Pool::with_config(Config {
+        apr_bps: 0,
+        pow_difficulty: difficulty,
+        ..Config::default()
+    });
+    let user = pool.user(TOKEN);
+    pool.stake(&user, TOKEN).unwrap();
+    (pool, user)
+}
+
+/// Proofs for `task_ids` meeting `difficulty`.
+fn mine_batch(
+    pool: &Pool,
+    user: &User,
+    task_ids: impl IntoIterator<Item = u64>,
+    difficulty: u8,
+) -> Vec<ProofSubmission> {
+    task_ids
+        .into_iter()
+        .map(|task_id| ProofSubmission {
+            task_id,
+            nonce: mine(&user.key(), &pool.mint, task_id, difficulty),
+        })
+        .collect()
+}
+
+#[test]
+fn batches_credit_every_proof_in_one_transaction() {
+    let (mut pool, user) = setup(6);
+    let reward = pool.state().pow_reward;
+    let proofs = mine_batch(&pool, &user, [3, 1, 2], 6);
+
+    let events: Vec<ProofRecorded> = pool.record_proofs(&user, &proofs).unwrap().events();
+    assert_eq!(events.len(), 3);
+    for (event, proof) in events.iter().zip(&proofs) {
+        assert_eq!((event.task_id, event.nonce), (proof.task_id, proof.nonce));
+        assert_eq!(event.reward, reward);
+    }
+    assert_eq!(events[1].pending_rewards_before, reward);
+    assert_eq!(events[2].last_task_id_before, 3);
+
+    let account = pool.stake_account(&user.key());
+    assert_eq!(account.pending_rewards, 3 * reward);
+    assert_eq!(account.last_task_id, 3);
+    assert!((1..=3).all(|task_id| account.is_task_recorded(task_id)));
+    assert!((1..=3).all(|task_id| pool.task(task_id).solvers == 1));
+
+    let history = pool.proof_history(&user.key());
+    let tasks: Vec<u64> = history
+        .chronological()
+        .map(|entry| entry.record.task_id)
+        .collect();
+    assert_eq!(tasks, [3, 1, 2]);
+    let address = pda::proof_history(&user.key()).0;
+    assert_eq!(
+        pool.svm.account(&address).unwrap().data.len(),
+        8 + ProofHistory::space(3)
+    );
+}
+
+#[test]
+fn batch_size_is_bounded_by_the_hash_algorithm() {
+    let (mut pool, user) = setup(0);
+    let batch = |tasks: std::ops::Range<u64>| -> Vec<ProofSubmission> {
+        tasks
+            .map(|task_id| ProofSubmission { task_id, nonce: 0 })
+            .collect()
+    };
+
+    assert_error(
+        pool.record_proofs(&user, &[]),
+        SkillStakeError::InvalidProofBatch,
+    );
+    let max = MAX_PROOF_BATCH as u64;
+    assert_error(
+        pool.record_proofs(&user, &batch(1..max + 2)),
+        SkillStakeError::InvalidProofBatch,
+    );
+    pool.record_proofs(&user, &batch(1..max + 1)).unwrap();
+
+    // Blake3 runs in-program, so its batches are half as long.
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::set_pow_hash_algorithm(
+        &admin,
+        POW_HASH_BLAKE3,
+    )])
+    .unwrap();
+    assert_eq!(
+        skill_stake::max_proof_batch(POW_HASH_BLAKE3),
+        MAX_PROOF_BATCH / 2
+    );
+    // A full batch's compute-unit request fits a transaction under either algorithm.
+    for algorithm in [POW_HASH_KECCAK256, POW_HASH_BLAKE3] {
+        let units = record_proofs_compute_units(algorithm, skill_stake::max_proof_batch(algorithm));
+        assert!(units <= 1_400_000);
+    }
+    let start = max + 1;
+    let half = max / 2;
+    assert_error(
+        pool.record_proofs(&user, &batch(start..start + half + 1)),
+        SkillStakeError::InvalidProofBatch,
+    );
+    pool.record_proofs(&user, &batch(start..start + half))
+        .unwrap();
+}
+
+#[test]
+fn one_bad_proof_rejects_the_whole_batch() {
+    let (mut pool, user) = setup(6);
+    pool.record_proof(&user, 1, mine(&user.key(), &pool.mint, 1, 6))
+        .unwrap();
+    let pending = pool.stake_account(&user.key()).pending_rewards;
+
+    let fresh = mine_batch(&pool, &user, [2, 3], 6);
+    let weak = ProofSubmission {
+        task_id: 4,
+        nonce: mine_exact(&user.key(), &pool.mint, 4, 5),
+    };
+    let replayed = mine_batch(&pool, &user, [1], 6)[0];
+    for (bad, expected) in [
+        (weak, SkillStakeError::ProofDifficultyNotMet),
+        (replayed, SkillStakeError::ProofTaskReplay),
+        // The same task twice within one batch is a replay too.
+        (fresh[0], SkillStakeError::ProofTaskReplay),
+    ] {
+        assert_error(
+            pool.record_proofs(&user, &[fresh[0], fresh[1], bad]),
+            expected,
+        );
+    }
+
+    let account = pool.stake_account(&user.key());
+    assert_eq!(
+        (account.pending_rewards, account.last_task_id),
+        (pending, 1)
+    );
+    assert_eq!(pool.task(2).solvers, 0);
+}
+
+#[test]
+fn task_accounts_must_match_the_batch() {
+    let (mut pool, user) = setup(0);
+    let proofs = [
+        ProofSubmission {
+            task_id: 1,
+            nonce: 0,
+        },
+        ProofSubmission {
+            task_id: 2,
+            nonce: 0,
+        },
+    ];
+    pool.ensure_task(1);
+    pool.ensure_task(2);
+    let ix = instructions::record_proofs(&user.key(), &proofs);
+    let tasks = ix.accounts.len() - 2;
+
+    let mut missing = ix.clone();
+    missing.accounts.pop();
+    assert_error(
+        pool.send(&[missing], &user.keypair),
+        SkillStakeError::InvalidProofBatch,
+    );
+
+    let mut swapped = ix.clone();
+    swapped.accounts.swap(tasks, tasks + 1);
+    assert_anchor_error(
+        pool.send(&[swapped], &user.keypair),
+        ErrorCode::ConstraintSeeds,
+    );
+
+    let mut readonly = ix.clone();
+    readonly.accounts[tasks] = AccountMeta::new_readonly(pda::task(1).0, false);
+    assert_anchor_error(
+        pool.send(&[readonly], &user.keypair),
+        ErrorCode::ConstraintMut,
+    );
+
+    let mut unregistered = ix.clone();
+    unregistered.accounts[tasks + 1] = AccountMeta::new(pda::task(3).0, false);
+    assert_anchor_error(
+        pool.send(&[unregistered], &user.keypair),
+        ErrorCode::AccountNotInitialized,
+    );
+
+    pool.send(&[ix], &user.keypair).unwrap();
+}
+
+#[test]
+fn pools_requiring_attestations_reject_batches() {
+    let (mut pool, user) = setup(0);
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::set_proof_attestation(&admin, true)])
+        .unwrap();
+    assert_error(
+        pool.record_proofs(
+            &user,
+            &[ProofSubmission {
+                task_id: 1,
+                nonce: 0,
+            }],
+        ),
+        SkillStakeError::AttestationMissing,
+    );
+}
+
+#[test]
+fn batches_count_every_proof_towards_the_retarget() {
+    let (mut pool, user) = setup(1);
+    let admin = pool.admin.pubkey();
+    let retarget = PowRetarget {
+        window: 3_600,
+        target_proofs: 2,
+        max_step: 2,
+        min_difficulty: 1,
+        max_difficulty: 8,
+    };
+    pool.send_admin(&[instructions::set_pow_retarget(&admin, retarget)])
+        .unwrap();
+
+    let proofs = mine_batch(&pool, &user, 1..=4, 1);
+    let meta = pool.record_proofs(&user, &proofs).unwrap();
+    assert!(meta.events::<PowDifficultyRetargeted>().is_empty());
+    assert_eq!(pool.state().pow_window_proofs, 4);
+
+    // The batch closing the window counts in full: eight proofs against a target of two.
+    pool.svm.warp(3_600);
+    let proofs = mine_batch(&pool, &user, 5..=8, 1);
+    let event: PowDifficultyRetargeted = pool.record_proofs(&user, &proofs).unwrap().event();
+    assert_eq!(event.window_proofs, 8);
+    assert_eq!((event.difficulty_before, event.difficulty_after), (1, 3));
+
+    // Later batches must meet the retargeted difficulty.
+    let weak = ProofSubmission {
+        task_id: 9,
+        nonce: mine_exact(&user.key(), &pool.mint, 9, 1),
+    };
+    assert_error(
+        pool.record_proofs(&user, &[weak]),
+        SkillStakeError::ProofDifficultyNotMet,
+    );
+    let proofs = mine_batch(&pool, &user, [9], 3);
+    pool.record_proofs(&user, &proofs).unwrap();
+}
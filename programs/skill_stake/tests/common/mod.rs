@@ -0,0 +1,378 @@
+//! Shared fixtures for the integration tests: an initialized pool and funded users.
+
+#![allow(dead_code)]
+
+pub mod svm;
+
+use anchor_lang::error::ErrorCode;
+use skill_stake::{
+    GlobalState, OracleSet, ProofHistory, Roles, SkillStakeError, StakeAccount, Task,
+};
+use skill_stake_client::{instructions, pda, FaucetProof, ProofSubmission, TaskParams};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::keccak;
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+pub use svm::{Svm, TxError, TxMeta};
+
+pub const SOL: u64 = 1_000_000_000;
+pub const DECIMALS: u8 = 9;
+pub const TOKEN: u64 = 10u64.pow(DECIMALS as u32);
+
+pub struct Config {
+    pub apr_bps: u64,
+    pub faucet_cap: u64,
+    /// Topped up right after `initialize` when non-zero.
+    pub faucet_budget: u64,
+    pub pow_reward: u64,
+    pub pow_difficulty: u8,
+    pub oracle_authority: Option<Pubkey>,
+    pub token_program: Pubkey,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            apr_bps: 1_000,
+            faucet_cap: 100 * TOKEN,
+            faucet_budget: 1_000_000 * TOKEN,
+            pow_reward: TOKEN,
+            pow_difficulty: 8,
+            oracle_authority: None,
+            token_program: spl_token::ID,
+        }
+    }
+}
+
+pub struct User {
+    pub keypair: Keypair,
+    pub token: Pubkey,
+}
+
+impl User {
+    pub fn key(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+}
+
+/// An initialized pool (state, vault, roles) over a mint whose authority is the program PDA.
+pub struct Pool {
+    pub svm: Svm,
+    pub admin: Keypair,
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+}
+
+impl Pool {
+    pub fn new() -> Self {
+        Self::with_config(Config::default())
+    }
+
+    pub fn with_config(config: Config) -> Self {
+        let mut pool = Self::uninitialized(&config);
+        let admin = pool.admin.pubkey();
+        let initialize = pool.initialize_ix(&config);
+        let mut ixs = vec![initialize, instructions::initialize_roles(&admin)];
+        if config.faucet_budget > 0 {
+            ixs.push(instructions::top_up_faucet_budget(
+                &admin,
+                config.faucet_budget,
+            ));
+        }
+        pool.send_admin(&ixs).expect("pool initializes");
+        pool
+    }
+
+    /// A funded admin and the mint, before `initialize`.
+    pub fn uninitialized(config: &Config) -> Self {
+        let mut svm = Svm::new();
+        let admin = Keypair::new();
+        svm.airdrop(&admin.pubkey(), 100 * SOL);
+        let mint = svm.create_mint(&config.token_program, &pda::mint_auth().0, DECIMALS);
+        Self {
+            svm,
+            admin,
+            mint,
+            token_program: config.token_program,
+        }
+    }
+
+    pub fn initialize_ix(&self, config: &Config) -> Instruction {
+        instructions::initialize(
+            &self.admin.pubkey(),
+            &self.mint,
+            &self.token_program,
+            config.apr_bps,
+            config.faucet_cap,
+            config.pow_reward,
+            config.pow_difficulty,
+            &config.oracle_authority.unwrap_or_default(),
+        )
+    }
+
+    /// A wallet with SOL for rent and `tokens` in a fresh token account.
+    pub fn user(&mut self, tokens: u64) -> User {
+        let keypair = Keypair::new();
+        self.svm.airdrop(&keypair.pubkey(), 10 * SOL);
+        let token = self.svm.create_token_account(
+            &self.token_program,
+            &self.mint,
+            &keypair.pubkey(),
+            tokens,
+        );
+        User { keypair, token }
+    }
+
+    pub fn send(
+        &mut self,
+        instructions: &[Instruction],
+        signer: &Keypair,
+    ) -> Result<TxMeta, TxError> {
+        self.svm.send(instructions, &[signer])
+    }
+
+    pub fn send_admin(&mut self, instructions: &[Instruction]) -> Result<TxMeta, TxError> {
+        let admin = self.admin.insecure_clone();
+        self.svm.send(instructions, &[&admin])
+    }
+
+    pub fn state(&self) -> GlobalState {
+        self.svm.anchor_account(&pda::state().0)
+    }
+
+    pub fn roles(&self) -> Roles {
+        self.svm.anchor_account(&pda::roles().0)
+    }
+
+    pub fn oracle_set(&self) -> OracleSet {
+        self.svm.anchor_account(&pda::oracle_set().0)
+    }
+
+    pub fn stake_account(&self, owner: &Pubkey) -> StakeAccount {
+        self.svm.anchor_account(&pda::stake_account(owner).0)
+    }
+
+    pub fn task(&self, task_id: u64) -> Task {
+        self.svm.anchor_account(&pda::task(task_id).0)
+    }
+
+    pub fn proof_history(&self, owner: &Pubkey) -> ProofHistory {
+        self.svm.anchor_account(&pda::proof_history(owner).0)
+    }
+
+    pub fn vault_balance(&self) -> u64 {
+        self.svm.token_balance(&pda::vault().0)
+    }
+
+    pub fn stake(&mut self, user: &User, amount: u64) -> Result<TxMeta, TxError> {
+        let ix = instructions::stake(
+            &user.key(),
+            &self.mint,
+            &user.token,
+            &self.token_program,
+            amount,
+        );
+        self.send(&[ix], &user.keypair)
+    }
+
+    pub fn unstake(&mut self, user: &User, amount: u64) -> Result<TxMeta, TxError> {
+        let ix = instructions::unstake(
+            &user.key(),
+            &self.mint,
+            &user.token,
+            &self.token_program,
+            amount,
+        );
+        self.send(&[ix], &user.keypair)
+    }
+
+    pub fn claim(&mut self, user: &User) -> Result<TxMeta, TxError> {
+        let ix = instructions::claim(&user.key(), &self.mint, &user.token, &self.token_program);
+        self.send(&[ix], &user.keypair)
+    }
+
+    pub fn emergency_withdraw(&mut self, user: &User) -> Result<TxMeta, TxError> {
+        let ix = instructions::emergency_withdraw(
+            &user.key(),
+            &self.mint,
+            &user.token,
+            &self.token_program,
+        );
+        self.send(&[ix], &user.keypair)
+    }
+
+    pub fn faucet(&mut self, user: &User, amount: u64) -> Result<TxMeta, TxError> {
+        let ix = self.faucet_ix(user, amount, FaucetProof::None);
+        self.send(&[ix], &user.keypair)
+    }
+
+    pub fn faucet_ix(&self, user: &User, amount: u64, proof: FaucetProof) -> Instruction {
+        instructions::faucet(
+            &user.key(),
+            &self.mint,
+            &user.token,
+            &self.token_program,
+            amount,
+            proof,
+        )
+    }
+
+    /// A task that is open now and follows the pool's reward and difficulty.
+    pub fn default_task(&self) -> TaskParams {
+        TaskParams {
+            description_hash: [0; 32],
+            reward: 0,
+            difficulty: 0,
+            opens_at: self.svm.now(),
+            closes_at: i64::MAX,
+            max_solvers: 0,
+        }
+    }
+
+    pub fn create_task(&mut self, task_id: u64, params: TaskParams) -> Result<TxMeta, TxError> {
+        let admin = self.admin.pubkey();
+        self.send_admin(&[instructions::create_task(&admin, task_id, params)])
+    }
+
+    /// Registers `task_id` with `default_task` unless it already exists.
+    pub fn ensure_task(&mut self, task_id: u64) {
+        if self.svm.account(&pda::task(task_id).0).is_none() {
+            let params = self.default_task();
+            self.create_task(task_id, params).expect("task created");
+        }
+    }
+
+    /// Registers the task first if needed; see `ensure_task`.
+    pub fn record_proof(
+        &mut self,
+        user: &User,
+        task_id: u64,
+        nonce: u64,
+    ) -> Result<TxMeta, TxError> {
+        self.ensure_task(task_id);
+        self.send(
+            &[instructions::record_proof(
+                &user.key(),
+                task_id,
+                nonce,
+                None,
+            )],
+            &user.keypair,
+        )
+    }
+
+    /// Registers each proof's task first if needed; see `ensure_task`.
+    pub fn record_proofs(
+        &mut self,
+        user: &User,
+        proofs: &[ProofSubmission],
+    ) -> Result<TxMeta, TxError> {
+        for proof in proofs {
+            self.ensure_task(proof.task_id);
+        }
+        let algorithm = self.state().pow_hash_algorithm;
+        self.send(
+            &instructions::record_proofs_with_budget(&user.key(), proofs, algorithm),
+            &user.keypair,
+        )
+    }
+
+    /// Grants `permissions` to a fresh funded wallet.
+    pub fn role_member(&mut self, permissions: u8) -> Keypair {
+        let member = Keypair::new();
+        self.svm.airdrop(&member.pubkey(), 10 * SOL);
+        let admin = self.admin.pubkey();
+        self.send_admin(&[instructions::grant_role(
+            &admin,
+            &member.pubkey(),
+            permissions,
+        )])
+        .expect("role granted");
+        member
+    }
+
+    /// A funded wallet with no roles.
+    pub fn outsider(&mut self) -> Keypair {
+        let outsider = Keypair::new();
+        self.svm.airdrop(&outsider.pubkey(), 10 * SOL);
+        outsider
+    }
+}
+
+/// Same hash as the program's `pow_hash`.
+pub fn pow_hash(user: &Pubkey, mint: &Pubkey, task_id: u64, nonce: u64) -> [u8; 32] {
+    keccak::hashv(&[
+        skill_stake::POW_DOMAIN,
+        user.as_ref(),
+        mint.as_ref(),
+        &task_id.to_le_bytes(),
+        &nonce.to_le_bytes(),
+    ])
+    .0
+}
+
+/// Same hash as the program's `challenge_pow_hash`.
+pub fn challenge_pow_hash(
+    user: &Pubkey,
+    mint: &Pubkey,
+    task_id: u64,
+    slot_hash: &[u8; 32],
+    nonce: u64,
+) -> [u8; 32] {
+    keccak::hashv(&[
+        skill_stake::POW_DOMAIN,
+        user.as_ref(),
+        mint.as_ref(),
+        &task_id.to_le_bytes(),
+        slot_hash,
+        &nonce.to_le_bytes(),
+    ])
+    .0
+}
+
+pub fn leading_zero_bits(hash: &[u8; 32]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+/// First nonce whose hash has at least `difficulty` leading zero bits.
+pub fn mine(user: &Pubkey, mint: &Pubkey, task_id: u64, difficulty: u8) -> u64 {
+    (0..)
+        .find(|nonce| {
+            leading_zero_bits(&pow_hash(user, mint, task_id, *nonce)) >= difficulty as u32
+        })
+        .unwrap()
+}
+
+/// First nonce whose hash has exactly `bits` leading zero bits, i.e. fails any higher target.
+pub fn mine_exact(user: &Pubkey, mint: &Pubkey, task_id: u64, bits: u8) -> u64 {
+    (0..)
+        .find(|nonce| leading_zero_bits(&pow_hash(user, mint, task_id, *nonce)) == bits as u32)
+        .unwrap()
+}
+
+pub fn assert_error(result: Result<TxMeta, TxError>, expected: SkillStakeError) {
+    assert_code(result, expected.into());
+}
+
+pub fn assert_anchor_error(result: Result<TxMeta, TxError>, expected: ErrorCode) {
+    assert_code(result, expected.into());
+}
+
+fn assert_code(result: Result<TxMeta, TxError>, code: u32) {
+    match result {
+        Ok(meta) => panic!(
+            "expected error {code}, transaction succeeded: {:#?}",
+            meta.logs
+        ),
+        Err(err) => assert_eq!(err.error, ProgramError::Custom(code), "{err:?}"),
+    }
+}
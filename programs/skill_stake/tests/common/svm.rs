@@ -0,0 +1,899 @@
+//! Minimal in-process runtime.
+//!
+//! `solana-program-test` 1.18 pins a yanked `solana_rbpf` release and cannot be resolved offline,
+//! so the program runs natively instead. Accounts are serialized in the loader's input format and
+//! handed to the real entrypoints; `SyscallStubs` routes CPIs to a native system program, SPL Token,
+//! Token-2022 and the program itself, and serves the clock, rent and return data. The slot hashes
+//! sysvar is derived from the slot number. Ed25519 precompile
+//! instructions are verified up front and the instructions sysvar is rebuilt for every transaction.
+//!
+//! The runtime's account rules (read-only accounts, ownership, lamport conservation) are enforced
+//! with panics so a violation fails the test instead of being reported as a program error.
+//!
+//! This is a native-execution approximation of the runtime, not a substitute for it: the program
+//! is compiled for the host, nothing runs the SBF binary, and compute units are not metered.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::sync::Once;
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator};
+use solana_sdk::account::Account;
+use solana_sdk::account_info::AccountInfo;
+use solana_sdk::clock::{Clock, Slot};
+use solana_sdk::ed25519_instruction;
+use solana_sdk::entrypoint::{
+    deserialize, ProgramResult, BPF_ALIGN_OF_U128, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER,
+    SUCCESS,
+};
+use solana_sdk::feature_set::FeatureSet;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::program_stubs::{self, SyscallStubs};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::slot_hashes::{self, SlotHashes};
+use solana_sdk::system_instruction::{SystemError, SystemInstruction};
+use solana_sdk::sysvar::instructions::{
+    self as instructions_sysvar, BorrowedAccountMeta, BorrowedInstruction,
+};
+use solana_sdk::{
+    bpf_loader, bpf_loader_upgradeable, compute_budget, ed25519_program, native_loader,
+    system_program, sysvar,
+};
+
+/// Unix timestamp of the genesis clock.
+pub const GENESIS_TS: i64 = 1_700_000_000;
+
+const SLOTS_PER_SECOND: u64 = 2;
+
+/// Per-thread execution state read by the syscall stubs. Test threads never share a runtime.
+#[derive(Default)]
+struct Context {
+    clock: Clock,
+    frames: Vec<Frame>,
+    logs: Vec<String>,
+    return_data: Option<(Pubkey, Vec<u8>)>,
+    inner: Vec<(Pubkey, Vec<u8>)>,
+}
+
+thread_local! {
+    static CONTEXT: RefCell<Context> = RefCell::new(Context::default());
+}
+
+fn with_context<R>(f: impl FnOnce(&mut Context) -> R) -> R {
+    CONTEXT.with(|context| f(&mut context.borrow_mut()))
+}
+
+struct Stubs;
+
+impl SyscallStubs for Stubs {
+    fn sol_log(&self, message: &str) {
+        with_context(|context| context.logs.push(format!("Program log: {message}")));
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        invoke_signed(instruction, account_infos, signers_seeds)
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = with_context(|context| context.clock.clone());
+        unsafe { std::ptr::write(var_addr as *mut Clock, clock) };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { std::ptr::write(var_addr as *mut Rent, Rent::default()) };
+        SUCCESS
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        with_context(|context| context.return_data.clone())
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        with_context(|context| {
+            let program_id = context.program_id();
+            context.return_data = (!data.is_empty()).then(|| (program_id, data.to_vec()));
+        });
+    }
+
+    fn sol_get_stack_height(&self) -> u64 {
+        with_context(|context| context.frames.len() as u64)
+    }
+}
+
+/// One program invocation: the accounts as the runtime last verified them.
+struct Frame {
+    program_id: Pubkey,
+    accounts: Vec<Keyed>,
+}
+
+impl Context {
+    fn program_id(&self) -> Pubkey {
+        self.frames
+            .last()
+            .expect("no program is executing")
+            .program_id
+    }
+}
+
+/// An account as seen by one instruction.
+#[derive(Clone)]
+struct Keyed {
+    key: Pubkey,
+    is_signer: bool,
+    is_writable: bool,
+    account: Account,
+}
+
+/// Offsets of one serialized account inside the input buffer.
+struct Layout {
+    owner: usize,
+    lamports: usize,
+    data_len: usize,
+    data: usize,
+}
+
+fn serialize(
+    program_id: &Pubkey,
+    accounts: &[Keyed],
+    data: &[u8],
+) -> (Vec<u64>, Vec<usize>, Vec<Layout>) {
+    let mut bytes = Vec::new();
+    let mut slots = Vec::with_capacity(accounts.len());
+    let mut layouts = Vec::new();
+
+    bytes.extend_from_slice(&(accounts.len() as u64).to_le_bytes());
+    for (index, keyed) in accounts.iter().enumerate() {
+        if let Some(first) = accounts[..index]
+            .iter()
+            .position(|other| other.key == keyed.key)
+        {
+            bytes.push(first as u8);
+            bytes.extend_from_slice(&[0; 7]);
+            slots.push(slots[first]);
+            continue;
+        }
+
+        slots.push(layouts.len());
+        bytes.push(NON_DUP_MARKER);
+        bytes.push(keyed.is_signer as u8);
+        bytes.push(keyed.is_writable as u8);
+        bytes.push(keyed.account.executable as u8);
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(keyed.key.as_ref());
+        let owner = bytes.len();
+        bytes.extend_from_slice(keyed.account.owner.as_ref());
+        let lamports = bytes.len();
+        bytes.extend_from_slice(&keyed.account.lamports.to_le_bytes());
+        let data_len = bytes.len();
+        bytes.extend_from_slice(&(keyed.account.data.len() as u64).to_le_bytes());
+        let data_start = bytes.len();
+        bytes.extend_from_slice(&keyed.account.data);
+        bytes.resize(bytes.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+        bytes.resize(bytes.len().next_multiple_of(BPF_ALIGN_OF_U128), 0);
+        bytes.extend_from_slice(&keyed.account.rent_epoch.to_le_bytes());
+        layouts.push(Layout {
+            owner,
+            lamports,
+            data_len,
+            data: data_start,
+        });
+    }
+    bytes.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(data);
+    bytes.extend_from_slice(program_id.as_ref());
+
+    // `deserialize` expects 8-byte alignment.
+    let mut buffer = vec![0u64; bytes.len().div_ceil(8)];
+    unsafe {
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), buffer.as_mut_ptr() as *mut u8, bytes.len());
+    }
+    (buffer, slots, layouts)
+}
+
+fn read_back(
+    buffer: &[u64],
+    accounts: &[Keyed],
+    slots: &[usize],
+    layouts: &[Layout],
+) -> Vec<Account> {
+    let bytes =
+        unsafe { std::slice::from_raw_parts(buffer.as_ptr() as *const u8, buffer.len() * 8) };
+    let u64_at = |offset: usize| u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+
+    accounts
+        .iter()
+        .zip(slots)
+        .map(|(keyed, &slot)| {
+            let layout = &layouts[slot];
+            let data_len = u64_at(layout.data_len) as usize;
+            Account {
+                lamports: u64_at(layout.lamports),
+                data: bytes[layout.data..layout.data + data_len].to_vec(),
+                owner: Pubkey::try_from(&bytes[layout.owner..layout.owner + 32]).unwrap(),
+                executable: keyed.account.executable,
+                rent_epoch: keyed.account.rent_epoch,
+            }
+        })
+        .collect()
+}
+
+/// Runs `program_id` over `accounts` and returns their post-instruction state.
+fn execute(
+    program_id: &Pubkey,
+    accounts: &[Keyed],
+    data: &[u8],
+) -> Result<Vec<Account>, ProgramError> {
+    let (mut buffer, slots, layouts) = serialize(program_id, accounts, data);
+
+    let mut unique: Vec<Keyed> = Vec::new();
+    for keyed in accounts {
+        if !unique.iter().any(|other| other.key == keyed.key) {
+            unique.push(keyed.clone());
+        }
+    }
+    with_context(|context| {
+        context.frames.push(Frame {
+            program_id: *program_id,
+            accounts: unique,
+        })
+    });
+    let result = unsafe {
+        let (program_id, infos, input) = deserialize(buffer.as_mut_ptr() as *mut u8);
+        dispatch(program_id, &infos, input)
+    };
+    let frame = with_context(|context| context.frames.pop()).unwrap();
+    result?;
+
+    let post = read_back(&buffer, accounts, &slots, &layouts);
+    let mut lamports_before = 0u128;
+    let mut lamports_after = 0u128;
+    for keyed in &frame.accounts {
+        let index = accounts
+            .iter()
+            .position(|other| other.key == keyed.key)
+            .unwrap();
+        verify(program_id, keyed, &post[index]);
+        lamports_before += keyed.account.lamports as u128;
+        lamports_after += post[index].lamports as u128;
+    }
+    assert_eq!(
+        lamports_before, lamports_after,
+        "{program_id} did not conserve lamports"
+    );
+    Ok(post)
+}
+
+fn dispatch<'info>(
+    program_id: &Pubkey,
+    accounts: &'info [AccountInfo<'info>],
+    data: &[u8],
+) -> ProgramResult {
+    if *program_id == skill_stake::ID {
+        skill_stake::entry(program_id, accounts, data)
+    } else if *program_id == spl_token::ID {
+        spl_token::processor::Processor::process(program_id, accounts, data)
+    } else if *program_id == spl_token_2022::ID {
+        spl_token_2022::processor::Processor::process(program_id, accounts, data)
+    } else if *program_id == system_program::ID {
+        process_system(accounts, data)
+    } else {
+        Err(ProgramError::IncorrectProgramId)
+    }
+}
+
+/// The runtime's rules for what `program_id` may have changed since `before` was recorded.
+fn verify(program_id: &Pubkey, before: &Keyed, after: &Account) {
+    let (key, pre) = (before.key, &before.account);
+    let changed =
+        pre.lamports != after.lamports || pre.data != after.data || pre.owner != after.owner;
+    assert!(
+        !changed || before.is_writable,
+        "{program_id} modified read-only account {key}"
+    );
+    assert!(
+        pre.data == after.data || pre.owner == *program_id,
+        "{program_id} modified data of {key} owned by {}",
+        pre.owner
+    );
+    assert!(
+        pre.owner == after.owner || pre.owner == *program_id,
+        "{program_id} reassigned {key} owned by {}",
+        pre.owner
+    );
+    assert!(
+        after.lamports >= pre.lamports || pre.owner == *program_id,
+        "{program_id} debited {key} owned by {}",
+        pre.owner
+    );
+}
+
+fn invoke_signed(
+    instruction: &Instruction,
+    account_infos: &[AccountInfo],
+    signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    let caller = with_context(|context| context.program_id());
+    let pda_signers = signers_seeds
+        .iter()
+        .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| ProgramError::InvalidSeeds)?;
+
+    let find = |key: &Pubkey| {
+        account_infos
+            .iter()
+            .find(|info| info.key == key)
+            .ok_or(ProgramError::NotEnoughAccountKeys)
+    };
+
+    let mut accounts = Vec::with_capacity(instruction.accounts.len());
+    for meta in &instruction.accounts {
+        let info = find(&meta.pubkey)?;
+        if meta.is_signer && !info.is_signer && !pda_signers.contains(&meta.pubkey) {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if meta.is_writable && !info.is_writable {
+            return Err(ProgramError::InvalidArgument);
+        }
+        accounts.push(Keyed {
+            key: meta.pubkey,
+            is_signer: meta.is_signer,
+            is_writable: meta.is_writable,
+            account: Account {
+                lamports: info.lamports(),
+                data: info.try_borrow_data()?.to_vec(),
+                owner: *info.owner,
+                executable: info.executable,
+                rent_epoch: info.rent_epoch,
+            },
+        });
+    }
+
+    // The caller's changes so far are checked now; the callee's are attributed to the callee.
+    with_context(|context| {
+        let frame = context.frames.last().unwrap();
+        for keyed in &accounts {
+            if let Some(before) = frame.accounts.iter().find(|other| other.key == keyed.key) {
+                verify(&caller, before, &keyed.account);
+            }
+        }
+        context
+            .inner
+            .push((instruction.program_id, instruction.data.clone()));
+    });
+    let post = execute(&instruction.program_id, &accounts, &instruction.data)?;
+    with_context(|context| {
+        let frame = context.frames.last_mut().unwrap();
+        for (keyed, after) in accounts.iter().zip(&post) {
+            if let Some(before) = frame
+                .accounts
+                .iter_mut()
+                .find(|other| other.key == keyed.key)
+            {
+                before.account = after.clone();
+            }
+        }
+    });
+
+    for (keyed, after) in accounts.iter().zip(post) {
+        if !keyed.is_writable {
+            continue;
+        }
+        let info = find(&keyed.key)?;
+        if info.lamports() != after.lamports {
+            **info.try_borrow_mut_lamports()? = after.lamports;
+        }
+        if info.data_len() != after.data.len() {
+            info.realloc(after.data.len(), false)?;
+        }
+        if info.try_borrow_data()?[..] != after.data[..] {
+            info.try_borrow_mut_data()?.copy_from_slice(&after.data);
+        }
+        if *info.owner != after.owner {
+            info.assign(&after.owner);
+        }
+    }
+    Ok(())
+}
+
+/// The subset of the system program the program and Anchor's `init` use.
+fn process_system(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let instruction: SystemInstruction =
+        bincode::deserialize(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    match instruction {
+        SystemInstruction::CreateAccount {
+            lamports,
+            space,
+            owner,
+        } => {
+            if accounts[1].lamports() > 0 {
+                return Err(ProgramError::Custom(
+                    SystemError::AccountAlreadyInUse as u32,
+                ));
+            }
+            system_transfer(&accounts[0], &accounts[1], lamports)?;
+            system_allocate(&accounts[1], space)?;
+            system_assign(&accounts[1], &owner)
+        }
+        SystemInstruction::Transfer { lamports } => {
+            system_transfer(&accounts[0], &accounts[1], lamports)
+        }
+        SystemInstruction::Allocate { space } => system_allocate(&accounts[0], space),
+        SystemInstruction::Assign { owner } => system_assign(&accounts[0], &owner),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn system_transfer(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if !from.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !from.data_is_empty() || *from.owner != system_program::ID {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let remaining = from
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::Custom(
+            SystemError::ResultWithNegativeLamports as u32,
+        ))?;
+    **from.try_borrow_mut_lamports()? = remaining;
+    **to.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
+
+fn system_allocate(account: &AccountInfo, space: u64) -> ProgramResult {
+    if !account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !account.data_is_empty() || *account.owner != system_program::ID {
+        return Err(ProgramError::Custom(
+            SystemError::AccountAlreadyInUse as u32,
+        ));
+    }
+    account.realloc(space as usize, true)
+}
+
+fn system_assign(account: &AccountInfo, owner: &Pubkey) -> ProgramResult {
+    if account.owner == owner {
+        return Ok(());
+    }
+    if !account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    account.assign(owner);
+    Ok(())
+}
+
+/// Stand-in bank hash of `slot`, as listed in the slot hashes sysvar.
+pub fn slot_hash(slot: Slot) -> Hash {
+    Hash::new_from_array(solana_sdk::keccak::hashv(&[b"slot", &slot.to_le_bytes()]).0)
+}
+
+/// Instructions sysvar contents for `instructions`, with room for the current index.
+fn instructions_sysvar_data(instructions: &[Instruction]) -> Vec<u8> {
+    let borrowed: Vec<BorrowedInstruction> = instructions
+        .iter()
+        .map(|instruction| BorrowedInstruction {
+            program_id: &instruction.program_id,
+            accounts: instruction
+                .accounts
+                .iter()
+                .map(|meta| BorrowedAccountMeta {
+                    pubkey: &meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: &instruction.data,
+        })
+        .collect();
+    instructions_sysvar::construct_instructions_data(&borrowed)
+}
+
+fn rent_exempt(data: Vec<u8>, owner: &Pubkey) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: *owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn install_stubs() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        program_stubs::set_syscall_stubs(Box::new(Stubs));
+    });
+}
+
+/// A failed transaction: the failing instruction, its error and the logs up to that point.
+pub struct TxError {
+    pub index: usize,
+    pub error: ProgramError,
+    pub logs: Vec<String>,
+}
+
+impl std::fmt::Debug for TxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "instruction {} failed: {:?}", self.index, self.error)?;
+        for line in &self.logs {
+            writeln!(f, "  {line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of a successful transaction.
+#[derive(Debug)]
+pub struct TxMeta {
+    pub logs: Vec<String>,
+    /// Return data left by the last instruction.
+    pub return_data: Option<Vec<u8>>,
+    /// `emit_cpi!` payloads (discriminator + Borsh body), in emission order.
+    pub events: Vec<Vec<u8>>,
+}
+
+impl TxMeta {
+    pub fn events<E: AnchorDeserialize + Discriminator>(&self) -> Vec<E> {
+        self.events
+            .iter()
+            .filter(|data| data[..8] == E::DISCRIMINATOR)
+            .map(|data| E::deserialize(&mut &data[8..]).expect("event decodes"))
+            .collect()
+    }
+
+    /// The single event of type `E`.
+    pub fn event<E: AnchorDeserialize + Discriminator>(&self) -> E {
+        let mut events = self.events::<E>();
+        assert_eq!(
+            events.len(),
+            1,
+            "expected exactly one event, logs: {:#?}",
+            self.logs
+        );
+        events.remove(0)
+    }
+
+    pub fn return_value<T: AnchorDeserialize>(&self) -> T {
+        let data = self
+            .return_data
+            .as_ref()
+            .expect("instruction set return data");
+        T::deserialize(&mut data.as_slice()).expect("return data decodes")
+    }
+}
+
+pub struct Svm {
+    accounts: HashMap<Pubkey, Account>,
+    clock: Clock,
+}
+
+impl Default for Svm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Svm {
+    pub fn new() -> Self {
+        install_stubs();
+
+        let mut svm = Self {
+            accounts: HashMap::new(),
+            clock: Clock {
+                slot: 1,
+                epoch_start_timestamp: GENESIS_TS,
+                unix_timestamp: GENESIS_TS,
+                ..Clock::default()
+            },
+        };
+        svm.add_program(skill_stake::ID, bpf_loader_upgradeable::ID);
+        svm.add_program(spl_token::ID, bpf_loader::ID);
+        svm.add_program(spl_token_2022::ID, bpf_loader::ID);
+        svm.add_program(system_program::ID, native_loader::ID);
+        svm.set_sysvar(
+            &sysvar::rent::ID,
+            bincode::serialize(&Rent::default()).unwrap(),
+        );
+        svm.sync_clock();
+        svm
+    }
+
+    fn add_program(&mut self, program_id: Pubkey, loader: Pubkey) {
+        self.accounts.insert(
+            program_id,
+            Account {
+                lamports: 1,
+                data: Vec::new(),
+                owner: loader,
+                executable: true,
+                rent_epoch: 0,
+            },
+        );
+    }
+
+    fn set_sysvar(&mut self, key: &Pubkey, data: Vec<u8>) {
+        self.set_account(key, rent_exempt(data, &sysvar::ID));
+    }
+
+    fn sync_clock(&mut self) {
+        self.set_sysvar(&sysvar::clock::ID, bincode::serialize(&self.clock).unwrap());
+        let recent = (self
+            .clock
+            .slot
+            .saturating_sub(slot_hashes::MAX_ENTRIES as u64)
+            ..self.clock.slot)
+            .map(|slot| (slot, slot_hash(slot)))
+            .collect::<Vec<_>>();
+        self.set_sysvar(
+            &sysvar::slot_hashes::ID,
+            bincode::serialize(&SlotHashes::new(&recent)).unwrap(),
+        );
+    }
+
+    pub fn now(&self) -> i64 {
+        self.clock.unix_timestamp
+    }
+
+    pub fn slot(&self) -> Slot {
+        self.clock.slot
+    }
+
+    /// Moves the clock forward (or back, for negative `seconds`).
+    pub fn warp(&mut self, seconds: i64) {
+        self.clock.unix_timestamp += seconds;
+        self.clock.slot += seconds.max(0) as u64 * SLOTS_PER_SECOND;
+        self.sync_clock();
+    }
+
+    pub fn set_timestamp(&mut self, unix_timestamp: i64) {
+        self.warp(unix_timestamp - self.clock.unix_timestamp);
+    }
+
+    pub fn account(&self, key: &Pubkey) -> Option<&Account> {
+        self.accounts.get(key)
+    }
+
+    pub fn set_account(&mut self, key: &Pubkey, account: Account) {
+        self.accounts.insert(*key, account);
+    }
+
+    pub fn lamports(&self, key: &Pubkey) -> u64 {
+        self.account(key).map_or(0, |account| account.lamports)
+    }
+
+    pub fn airdrop(&mut self, key: &Pubkey, lamports: u64) {
+        self.accounts
+            .entry(*key)
+            .or_insert_with(|| Account::new(0, 0, &system_program::ID))
+            .lamports += lamports;
+    }
+
+    /// Decodes an Anchor account owned by the program.
+    pub fn anchor_account<T: AccountDeserialize>(&self, key: &Pubkey) -> T {
+        let account = self
+            .account(key)
+            .unwrap_or_else(|| panic!("{key} does not exist"));
+        T::try_deserialize(&mut account.data.as_slice()).expect("account decodes")
+    }
+
+    pub fn create_mint(
+        &mut self,
+        token_program: &Pubkey,
+        authority: &Pubkey,
+        decimals: u8,
+    ) -> Pubkey {
+        let mint = Pubkey::new_unique();
+        let state = spl_token::state::Mint {
+            mint_authority: Some(*authority).into(),
+            supply: 0,
+            decimals,
+            is_initialized: true,
+            freeze_authority: None.into(),
+        };
+        self.set_packed(&mint, token_program, state);
+        mint
+    }
+
+    /// Creates a token account holding freshly minted `amount`.
+    pub fn create_token_account(
+        &mut self,
+        token_program: &Pubkey,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+    ) -> Pubkey {
+        let address = Pubkey::new_unique();
+        let state = spl_token::state::Account {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            state: spl_token::state::AccountState::Initialized,
+            ..spl_token::state::Account::default()
+        };
+        self.set_packed(&address, token_program, state);
+
+        let mut mint_state = self.unpack::<spl_token::state::Mint>(mint);
+        mint_state.supply += amount;
+        self.set_packed(mint, token_program, mint_state);
+        address
+    }
+
+    fn set_packed<T: Pack>(&mut self, key: &Pubkey, owner: &Pubkey, state: T) {
+        let mut data = vec![0; T::LEN];
+        state.pack_into_slice(&mut data);
+        self.set_account(key, rent_exempt(data, owner));
+    }
+
+    fn unpack<T: Pack + solana_sdk::program_pack::IsInitialized>(&self, key: &Pubkey) -> T {
+        let account = self
+            .account(key)
+            .unwrap_or_else(|| panic!("{key} does not exist"));
+        T::unpack(&account.data[..T::LEN]).expect("token state unpacks")
+    }
+
+    pub fn token_balance(&self, key: &Pubkey) -> u64 {
+        self.unpack::<spl_token::state::Account>(key).amount
+    }
+
+    pub fn mint_supply(&self, mint: &Pubkey) -> u64 {
+        self.unpack::<spl_token::state::Mint>(mint).supply
+    }
+
+    /// Executes `instructions` atomically. Every `is_signer` account must be among `signers`.
+    pub fn send(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<TxMeta, TxError> {
+        let snapshot = self.accounts.clone();
+        let result = self.process(instructions, signers);
+        if result.is_err() {
+            self.accounts = snapshot;
+        }
+        result
+    }
+
+    /// Executes `instructions` and discards every account change.
+    pub fn simulate(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<TxMeta, TxError> {
+        let snapshot = self.accounts.clone();
+        let result = self.process(instructions, signers);
+        self.accounts = snapshot;
+        result
+    }
+
+    fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<TxMeta, TxError> {
+        let signer_keys: HashSet<Pubkey> = signers.iter().map(|signer| signer.pubkey()).collect();
+        with_context(|context| {
+            *context = Context {
+                clock: self.clock.clone(),
+                ..Context::default()
+            }
+        });
+
+        // The runtime checks precompile signatures before any program runs.
+        let datas: Vec<&[u8]> = instructions.iter().map(|ix| ix.data.as_slice()).collect();
+        for (index, instruction) in instructions.iter().enumerate() {
+            if instruction.program_id == ed25519_program::ID {
+                ed25519_instruction::verify(&instruction.data, &datas, &FeatureSet::all_enabled())
+                    .map_err(|error| TxError {
+                        index,
+                        error: ProgramError::Custom(error as u32),
+                        logs: Vec::new(),
+                    })?;
+            }
+        }
+        let mut instructions_data = instructions_sysvar_data(instructions);
+
+        let mut return_data = None;
+        for (index, instruction) in instructions.iter().enumerate() {
+            // Compute-budget requests only size the meter, which this runtime does not model.
+            if instruction.program_id == ed25519_program::ID
+                || instruction.program_id == compute_budget::ID
+            {
+                continue;
+            }
+            instructions_sysvar::store_current_index(&mut instructions_data, index as u16);
+            self.set_sysvar(&instructions_sysvar::ID, instructions_data.clone());
+            let accounts = self.load(instruction, &signer_keys);
+            with_context(|context| context.return_data = None);
+
+            match execute(&instruction.program_id, &accounts, &instruction.data) {
+                Ok(post) => {
+                    for (keyed, account) in accounts.iter().zip(post) {
+                        if keyed.is_writable {
+                            self.accounts.insert(keyed.key, account);
+                        }
+                    }
+                    return_data =
+                        with_context(|context| context.return_data.take()).map(|(_, data)| data);
+                }
+                Err(error) => {
+                    return Err(TxError {
+                        index,
+                        error,
+                        logs: with_context(|context| std::mem::take(&mut context.logs)),
+                    });
+                }
+            }
+        }
+
+        // Closed accounts (zero lamports) disappear at the end of the transaction.
+        self.accounts.retain(|_, account| account.lamports > 0);
+        self.accounts.remove(&instructions_sysvar::ID);
+
+        let (logs, inner) = with_context(|context| {
+            (
+                std::mem::take(&mut context.logs),
+                std::mem::take(&mut context.inner),
+            )
+        });
+        let events = inner
+            .into_iter()
+            .filter(|(program_id, data)| {
+                *program_id == skill_stake::ID && data.starts_with(&EVENT_IX_TAG_LE)
+            })
+            .map(|(_, data)| data[8..].to_vec())
+            .collect();
+
+        Ok(TxMeta {
+            logs,
+            return_data,
+            events,
+        })
+    }
+
+    fn load(&self, instruction: &Instruction, signers: &HashSet<Pubkey>) -> Vec<Keyed> {
+        instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                // A key listed twice carries the union of its privileges.
+                let metas = instruction
+                    .accounts
+                    .iter()
+                    .filter(|m| m.pubkey == meta.pubkey);
+                let (is_signer, is_writable) = metas.fold((false, false), |(s, w), m| {
+                    (s || m.is_signer, w || m.is_writable)
+                });
+                assert!(
+                    !is_signer || signers.contains(&meta.pubkey),
+                    "missing signature for {}",
+                    meta.pubkey
+                );
+                Keyed {
+                    key: meta.pubkey,
+                    is_signer,
+                    is_writable,
+                    account: self
+                        .accounts
+                        .get(&meta.pubkey)
+                        .cloned()
+                        .unwrap_or_else(|| Account::new(0, 0, &system_program::ID)),
+                }
+            })
+            .collect()
+    }
+}
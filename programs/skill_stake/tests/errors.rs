@@ -0,0 +1,81 @@
+//! The client's error table and program id mirror the program's.
+
+use skill_stake::SkillStakeError as ProgramError;
+use skill_stake_client::SkillStakeError as ClientError;
+
+const ALL: [ProgramError; 53] = [
+    ProgramError::AprTooHigh,
+    ProgramError::MathOverflow,
+    ProgramError::InvalidAmount,
+    ProgramError::NothingToClaim,
+    ProgramError::InsufficientStake,
+    ProgramError::Unauthorized,
+    // Never raised: bumps come from `ctx.bumps`.
+    ProgramError::BumpNotFound,
+    ProgramError::MintMismatch,
+    // Never raised: an encoded `ProofRecord` is always smaller than `MAX_PROOF_STORAGE`.
+    ProgramError::ProofTooLarge,
+    ProgramError::FaucetCapExceeded,
+    ProgramError::InvalidOracleAuthority,
+    ProgramError::InvalidPowDifficulty,
+    ProgramError::StaleOracleUpdate,
+    ProgramError::ProofDifficultyNotMet,
+    ProgramError::ProofTaskReplay,
+    ProgramError::TimelockActive,
+    ProgramError::TimelockTooShort,
+    ProgramError::TimelockNotElapsed,
+    ProgramError::TimelockElapsed,
+    ProgramError::InvalidTimelockDelay,
+    ProgramError::InstructionPaused,
+    ProgramError::InvalidPauseFlags,
+    ProgramError::InvalidRole,
+    ProgramError::RoleCapacityExceeded,
+    ProgramError::AccountNotMigrated,
+    ProgramError::AccountAlreadyMigrated,
+    ProgramError::FaucetCooldownActive,
+    ProgramError::FaucetWindowCapExceeded,
+    ProgramError::FaucetDailyBudgetExceeded,
+    ProgramError::InvalidFaucetLimits,
+    ProgramError::FaucetBudgetExceeded,
+    // Only raised by mainnet builds; the tests build with `devnet`.
+    ProgramError::FaucetUnavailable,
+    ProgramError::InvalidFaucetGate,
+    ProgramError::FaucetProofMismatch,
+    ProgramError::AttestationMissing,
+    ProgramError::AttestationMismatch,
+    ProgramError::AttestationExpired,
+    ProgramError::InvalidRetargetConfig,
+    ProgramError::InvalidTaskConfig,
+    ProgramError::TaskNotOpen,
+    ProgramError::TaskClosed,
+    ProgramError::TaskFull,
+    ProgramError::ProofTaskTooOld,
+    ProgramError::InvalidProofStakePolicy,
+    ProgramError::InsufficientProofStake,
+    ProgramError::InvalidPowChallengeConfig,
+    ProgramError::PowChallengeMissing,
+    ProgramError::PowChallengeExpired,
+    ProgramError::InvalidPowHashAlgorithm,
+    ProgramError::InvalidProofBatch,
+    ProgramError::InvalidOracleSet,
+    ProgramError::OracleThresholdNotMet,
+    ProgramError::ChangeExpired,
+];
+
+#[test]
+fn client_codes_and_messages_match_the_program() {
+    for (index, error) in ALL.into_iter().enumerate() {
+        let code = u32::from(error);
+        assert_eq!(code, 6000 + index as u32);
+        let client = ClientError::from_code(code).unwrap_or_else(|| panic!("{code} missing"));
+        assert_eq!(client.code(), code);
+        assert_eq!(client.to_string(), error.to_string(), "{code}");
+    }
+    assert_eq!(ClientError::from_code(6000 + ALL.len() as u32), None);
+}
+
+#[test]
+fn client_targets_the_devnet_build() {
+    assert_eq!(skill_stake_client::ID, skill_stake::ID);
+    assert_eq!(skill_stake_client::DEVNET_ID, skill_stake::ID);
+}
@@ -0,0 +1,494 @@
+//! Faucet minting, the per-wallet lifetime cap, the rate limits, the global budget and the gates.
+
+mod common;
+
+use common::*;
+use skill_stake::{
+    faucet_attestation_message, faucet_pow_hash, meets_difficulty, FaucetBudgetExhausted,
+    FaucetBudgetToppedUp, FaucetClaimed, ParamChange as ProgramParamChange, ParamChanged,
+    SkillStakeError, FAUCET_GATE_NONE, FAUCET_GATE_ORACLE, FAUCET_GATE_POW, MAX_POW_DIFFICULTY,
+    ROLE_FAUCET_MANAGER, ROLE_TREASURY_MANAGER, SECONDS_PER_DAY,
+};
+use skill_stake_client::{instructions, FaucetProof};
+use solana_sdk::ed25519_instruction::PUBKEY_SERIALIZED_SIZE;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+#[test]
+fn faucet_mints_and_creates_stake_account() {
+    let mut pool = Pool::new();
+    let user = pool.user(0);
+
+    let event: FaucetClaimed = pool.faucet(&user, 5 * TOKEN).unwrap().event();
+    assert_eq!(
+        (event.faucet_claimed_before, event.faucet_claimed_after),
+        (0, 5 * TOKEN)
+    );
+    assert_eq!(pool.svm.token_balance(&user.token), 5 * TOKEN);
+    assert_eq!(pool.svm.mint_supply(&pool.mint), 5 * TOKEN);
+
+    let stake_account = pool.stake_account(&user.key());
+    assert_eq!(stake_account.owner, user.key());
+    assert_eq!(stake_account.faucet_claimed, 5 * TOKEN);
+    assert_eq!(stake_account.amount_staked, 0);
+}
+
+#[test]
+fn faucet_cap_is_per_wallet_and_inclusive() {
+    let mut pool = Pool::with_config(Config {
+        faucet_cap: 10 * TOKEN,
+        ..Config::default()
+    });
+    let alice = pool.user(0);
+    let bob = pool.user(0);
+
+    pool.faucet(&alice, 4 * TOKEN).unwrap();
+    pool.faucet(&alice, 6 * TOKEN).unwrap();
+    assert_error(pool.faucet(&alice, 1), SkillStakeError::FaucetCapExceeded);
+    assert_eq!(pool.stake_account(&alice.key()).faucet_claimed, 10 * TOKEN);
+
+    assert_error(
+        pool.faucet(&bob, 10 * TOKEN + 1),
+        SkillStakeError::FaucetCapExceeded,
+    );
+    pool.faucet(&bob, 10 * TOKEN).unwrap();
+}
+
+#[test]
+fn raising_the_cap_reopens_the_faucet() {
+    let mut pool = Pool::with_config(Config {
+        faucet_cap: TOKEN,
+        ..Config::default()
+    });
+    let user = pool.user(0);
+    pool.faucet(&user, TOKEN).unwrap();
+    assert_error(
+        pool.faucet(&user, TOKEN),
+        SkillStakeError::FaucetCapExceeded,
+    );
+
+    let manager = pool.role_member(ROLE_FAUCET_MANAGER);
+    pool.send(
+        &[instructions::update_faucet_cap(
+            &manager.pubkey(),
+            3 * TOKEN,
+        )],
+        &manager,
+    )
+    .unwrap();
+    pool.faucet(&user, 2 * TOKEN).unwrap();
+    assert_error(pool.faucet(&user, 1), SkillStakeError::FaucetCapExceeded);
+}
+
+#[test]
+fn faucet_amount_overflow_is_reported() {
+    let mut pool = Pool::with_config(Config {
+        faucet_cap: u64::MAX,
+        ..Config::default()
+    });
+    let user = pool.user(0);
+    pool.faucet(&user, 1).unwrap();
+    assert_error(pool.faucet(&user, u64::MAX), SkillStakeError::MathOverflow);
+}
+
+#[test]
+fn faucet_tokens_can_be_staked() {
+    let mut pool = Pool::new();
+    let user = pool.user(0);
+    pool.faucet(&user, 10 * TOKEN).unwrap();
+    pool.stake(&user, 10 * TOKEN).unwrap();
+
+    let stake_account = pool.stake_account(&user.key());
+    assert_eq!(stake_account.amount_staked, 10 * TOKEN);
+    assert_eq!(stake_account.faucet_claimed, 10 * TOKEN);
+}
+
+fn set_limits(pool: &mut Pool, cooldown: i64, window: i64, window_cap: u64, daily_budget: u64) {
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::set_faucet_limits(
+        &admin,
+        cooldown,
+        window,
+        window_cap,
+        daily_budget,
+    )])
+    .unwrap();
+}
+
+#[test]
+fn cooldown_spaces_out_claims() {
+    let mut pool = Pool::new();
+    set_limits(&mut pool, 3_600, 0, 0, 0);
+    let user = pool.user(0);
+
+    pool.faucet(&user, TOKEN).unwrap();
+    assert_error(
+        pool.faucet(&user, TOKEN),
+        SkillStakeError::FaucetCooldownActive,
+    );
+    pool.svm.warp(3_599);
+    assert_error(
+        pool.faucet(&user, TOKEN),
+        SkillStakeError::FaucetCooldownActive,
+    );
+    pool.svm.warp(1);
+    pool.faucet(&user, TOKEN).unwrap();
+
+    // Other wallets are not affected.
+    let other = pool.user(0);
+    pool.faucet(&other, TOKEN).unwrap();
+}
+
+#[test]
+fn window_cap_resets_after_the_window() {
+    let mut pool = Pool::new();
+    set_limits(&mut pool, 0, SECONDS_PER_DAY, 3 * TOKEN, 0);
+    let user = pool.user(0);
+
+    pool.faucet(&user, 2 * TOKEN).unwrap();
+    pool.svm.warp(SECONDS_PER_DAY - 1);
+    let event: FaucetClaimed = pool.faucet(&user, TOKEN).unwrap().event();
+    assert_eq!(event.window_claimed_after, 3 * TOKEN);
+    assert_error(
+        pool.faucet(&user, 1),
+        SkillStakeError::FaucetWindowCapExceeded,
+    );
+
+    // The window started at the first claim, so one more second closes it.
+    pool.svm.warp(1);
+    let event: FaucetClaimed = pool.faucet(&user, 3 * TOKEN).unwrap().event();
+    assert_eq!(event.window_claimed_after, 3 * TOKEN);
+    let stake_account = pool.stake_account(&user.key());
+    assert_eq!(stake_account.faucet_window_start, pool.svm.now());
+}
+
+#[test]
+fn daily_budget_is_shared_and_resets_at_midnight() {
+    let mut pool = Pool::new();
+    set_limits(&mut pool, 0, 0, 0, 5 * TOKEN);
+    let midnight = (pool.svm.now().div_euclid(SECONDS_PER_DAY) + 1) * SECONDS_PER_DAY;
+    pool.svm.set_timestamp(midnight - 60);
+    let alice = pool.user(0);
+    let bob = pool.user(0);
+
+    pool.faucet(&alice, 3 * TOKEN).unwrap();
+    let event: FaucetClaimed = pool.faucet(&bob, 2 * TOKEN).unwrap().event();
+    assert_eq!(event.day_minted_after, 5 * TOKEN);
+    assert_error(
+        pool.faucet(&bob, 1),
+        SkillStakeError::FaucetDailyBudgetExceeded,
+    );
+
+    pool.svm.set_timestamp(midnight);
+    pool.faucet(&bob, 5 * TOKEN).unwrap();
+    assert_eq!(pool.state().faucet_day_minted, 5 * TOKEN);
+}
+
+#[test]
+fn faucet_limits_are_validated_and_role_gated() {
+    let mut pool = Pool::new();
+    let admin = pool.admin.pubkey();
+    for (cooldown, window, window_cap) in [(-1, 0, 0), (0, -1, 1), (0, 60, 0), (0, 0, 1)] {
+        let ix = instructions::set_faucet_limits(&admin, cooldown, window, window_cap, 0);
+        assert_error(pool.send_admin(&[ix]), SkillStakeError::InvalidFaucetLimits);
+    }
+
+    let outsider = pool.outsider();
+    let ix = instructions::set_faucet_limits(&outsider.pubkey(), 60, 0, 0, 0);
+    assert_error(pool.send(&[ix], &outsider), SkillStakeError::Unauthorized);
+
+    let manager = pool.role_member(ROLE_FAUCET_MANAGER);
+    let ix = instructions::set_faucet_limits(&manager.pubkey(), 60, 3_600, TOKEN, 10 * TOKEN);
+    let changed: ParamChanged = pool.send(&[ix], &manager).unwrap().event();
+    assert_eq!(
+        changed.after,
+        ProgramParamChange::FaucetLimits {
+            cooldown: 60,
+            window: 3_600,
+            window_cap: TOKEN,
+            daily_budget: 10 * TOKEN,
+        }
+    );
+    let state = pool.state();
+    assert_eq!(
+        (
+            state.faucet_cooldown,
+            state.faucet_window,
+            state.faucet_window_cap
+        ),
+        (60, 3_600, TOKEN)
+    );
+    assert_eq!(state.faucet_daily_budget, 10 * TOKEN);
+}
+
+#[test]
+fn faucet_is_closed_until_the_budget_is_topped_up() {
+    let mut pool = Pool::with_config(Config {
+        faucet_budget: 0,
+        ..Config::default()
+    });
+    let user = pool.user(0);
+    assert_error(pool.faucet(&user, 1), SkillStakeError::FaucetBudgetExceeded);
+
+    let faucet_manager = pool.role_member(ROLE_FAUCET_MANAGER);
+    let ix = instructions::top_up_faucet_budget(&faucet_manager.pubkey(), TOKEN);
+    assert_error(
+        pool.send(&[ix], &faucet_manager),
+        SkillStakeError::Unauthorized,
+    );
+
+    let treasury = pool.role_member(ROLE_TREASURY_MANAGER);
+    let ix = instructions::top_up_faucet_budget(&treasury.pubkey(), 0);
+    assert_error(pool.send(&[ix], &treasury), SkillStakeError::InvalidAmount);
+    let ix = instructions::top_up_faucet_budget(&treasury.pubkey(), 3 * TOKEN);
+    let event: FaucetBudgetToppedUp = pool.send(&[ix], &treasury).unwrap().event();
+    assert_eq!(
+        (event.faucet_budget_before, event.faucet_budget_after),
+        (0, 3 * TOKEN)
+    );
+    pool.faucet(&user, TOKEN).unwrap();
+}
+
+#[test]
+fn budget_shuts_the_faucet_off_across_wallets() {
+    let mut pool = Pool::with_config(Config {
+        faucet_budget: 5 * TOKEN,
+        ..Config::default()
+    });
+    let alice = pool.user(0);
+    let bob = pool.user(0);
+
+    let meta = pool.faucet(&alice, 3 * TOKEN).unwrap();
+    assert!(meta.events::<FaucetBudgetExhausted>().is_empty());
+    assert_error(
+        pool.faucet(&bob, 3 * TOKEN),
+        SkillStakeError::FaucetBudgetExceeded,
+    );
+
+    let meta = pool.faucet(&bob, 2 * TOKEN).unwrap();
+    assert_eq!(meta.event::<FaucetClaimed>().faucet_minted_after, 5 * TOKEN);
+    assert_eq!(
+        meta.event::<FaucetBudgetExhausted>().faucet_budget,
+        5 * TOKEN
+    );
+    assert_error(
+        pool.faucet(&alice, 1),
+        SkillStakeError::FaucetBudgetExceeded,
+    );
+
+    // A top-up reopens it for exactly the added amount.
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::top_up_faucet_budget(&admin, TOKEN)])
+        .unwrap();
+    pool.faucet(&alice, TOKEN).unwrap();
+    assert_error(
+        pool.faucet(&alice, 1),
+        SkillStakeError::FaucetBudgetExceeded,
+    );
+    let state = pool.state();
+    assert_eq!(
+        (state.faucet_budget, state.faucet_minted),
+        (6 * TOKEN, 6 * TOKEN)
+    );
+}
+
+fn set_gate(pool: &mut Pool, gate: u8, pow_difficulty: u8) {
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::set_faucet_gate(&admin, gate, pow_difficulty)])
+        .unwrap();
+}
+
+/// An ed25519 instruction carrying `signer`'s signature over the faucet attestation message.
+fn attestation(
+    pool: &Pool,
+    signer: &Keypair,
+    user: &User,
+    amount: u64,
+    faucet_claimed: u64,
+    expiry: i64,
+) -> Instruction {
+    let message =
+        faucet_attestation_message(&user.key(), &pool.mint, amount, faucet_claimed, expiry);
+    let signature = signer.sign_message(&message);
+    instructions::ed25519_verify(&signer.pubkey(), &signature, &message)
+}
+
+#[test]
+fn faucet_gate_is_validated_and_role_gated() {
+    let mut pool = Pool::new();
+    let admin = pool.admin.pubkey();
+    for (gate, difficulty) in [
+        (FAUCET_GATE_ORACLE + 1, 0),
+        (FAUCET_GATE_POW, MAX_POW_DIFFICULTY + 1),
+    ] {
+        let ix = instructions::set_faucet_gate(&admin, gate, difficulty);
+        assert_error(pool.send_admin(&[ix]), SkillStakeError::InvalidFaucetGate);
+    }
+
+    let outsider = pool.outsider();
+    let ix = instructions::set_faucet_gate(&outsider.pubkey(), FAUCET_GATE_POW, 8);
+    assert_error(pool.send(&[ix], &outsider), SkillStakeError::Unauthorized);
+
+    let manager = pool.role_member(ROLE_FAUCET_MANAGER);
+    let ix = instructions::set_faucet_gate(&manager.pubkey(), FAUCET_GATE_POW, 8);
+    let changed: ParamChanged = pool.send(&[ix], &manager).unwrap().event();
+    assert_eq!(
+        (changed.before, changed.after),
+        (
+            ProgramParamChange::FaucetGate {
+                gate: FAUCET_GATE_NONE,
+                pow_difficulty: 0,
+            },
+            ProgramParamChange::FaucetGate {
+                gate: FAUCET_GATE_POW,
+                pow_difficulty: 8,
+            },
+        )
+    );
+    let state = pool.state();
+    assert_eq!(
+        (state.faucet_gate, state.faucet_pow_difficulty),
+        (FAUCET_GATE_POW, 8)
+    );
+}
+
+#[test]
+fn pow_gate_needs_a_solution_per_claim() {
+    let mut pool = Pool::new();
+    set_gate(&mut pool, FAUCET_GATE_POW, 8);
+    let user = pool.user(0);
+    let (key, mint) = (user.key(), pool.mint);
+
+    for proof in [
+        FaucetProof::None,
+        FaucetProof::Attestation { expiry: i64::MAX },
+    ] {
+        let ix = pool.faucet_ix(&user, TOKEN, proof);
+        assert_error(
+            pool.send(&[ix], &user.keypair),
+            SkillStakeError::FaucetProofMismatch,
+        );
+    }
+    let weak = mine_faucet_exact(&key, &mint, 0, 7);
+    let ix = pool.faucet_ix(&user, TOKEN, FaucetProof::Pow { nonce: weak });
+    assert_error(
+        pool.send(&[ix], &user.keypair),
+        SkillStakeError::ProofDifficultyNotMet,
+    );
+
+    // A solution for the first claim that does not also solve the second.
+    let nonce = (0..)
+        .find(|nonce| {
+            meets_difficulty(&faucet_pow_hash(&key, &mint, 0, *nonce), 8)
+                && !meets_difficulty(&faucet_pow_hash(&key, &mint, TOKEN, *nonce), 8)
+        })
+        .unwrap();
+    let ix = pool.faucet_ix(&user, TOKEN, FaucetProof::Pow { nonce });
+    pool.send(std::slice::from_ref(&ix), &user.keypair).unwrap();
+    assert_error(
+        pool.send(&[ix], &user.keypair),
+        SkillStakeError::ProofDifficultyNotMet,
+    );
+
+    let nonce = (0..)
+        .find(|nonce| meets_difficulty(&faucet_pow_hash(&key, &mint, TOKEN, *nonce), 8))
+        .unwrap();
+    let ix = pool.faucet_ix(&user, TOKEN, FaucetProof::Pow { nonce });
+    pool.send(&[ix], &user.keypair).unwrap();
+    assert_eq!(pool.stake_account(&key).faucet_claimed, 2 * TOKEN);
+}
+
+/// First nonce whose faucet hash has exactly `bits` leading zero bits.
+fn mine_faucet_exact(user: &Pubkey, mint: &Pubkey, faucet_claimed: u64, bits: u8) -> u64 {
+    (0..)
+        .find(|nonce| {
+            leading_zero_bits(&faucet_pow_hash(user, mint, faucet_claimed, *nonce)) == bits as u32
+        })
+        .unwrap()
+}
+
+#[test]
+fn oracle_gate_needs_a_fresh_attestation() {
+    let mut pool = Pool::new();
+    set_gate(&mut pool, FAUCET_GATE_ORACLE, 0);
+    let oracle = pool.admin.insecure_clone();
+    let user = pool.user(0);
+    let expiry = pool.svm.now() + 60;
+    let proof = FaucetProof::Attestation { expiry };
+    let claim = pool.faucet_ix(&user, TOKEN, proof);
+
+    let ix = pool.faucet_ix(&user, TOKEN, FaucetProof::Pow { nonce: 0 });
+    assert_error(
+        pool.send(&[ix], &user.keypair),
+        SkillStakeError::FaucetProofMismatch,
+    );
+    assert_error(
+        pool.send(std::slice::from_ref(&claim), &user.keypair),
+        SkillStakeError::AttestationMissing,
+    );
+
+    // Wrong signer, wrong amount, wrong claimed total.
+    let outsider = pool.outsider();
+    for attest in [
+        attestation(&pool, &outsider, &user, TOKEN, 0, expiry),
+        attestation(&pool, &oracle, &user, 2 * TOKEN, 0, expiry),
+        attestation(&pool, &oracle, &user, TOKEN, TOKEN, expiry),
+    ] {
+        assert_error(
+            pool.send(&[attest, claim.clone()], &user.keypair),
+            SkillStakeError::AttestationMismatch,
+        );
+    }
+
+    let attest = attestation(&pool, &oracle, &user, TOKEN, 0, expiry);
+    pool.send(&[attest.clone(), claim.clone()], &user.keypair)
+        .unwrap();
+    // Replaying the same attestation fails once the claimed total has moved on.
+    assert_error(
+        pool.send(&[attest, claim.clone()], &user.keypair),
+        SkillStakeError::AttestationMismatch,
+    );
+
+    let attest = attestation(&pool, &oracle, &user, TOKEN, TOKEN, expiry);
+    pool.svm.warp(61);
+    assert_error(
+        pool.send(&[attest, claim], &user.keypair),
+        SkillStakeError::AttestationExpired,
+    );
+}
+
+#[test]
+fn oracle_attestation_must_be_verified_inline() {
+    let mut pool = Pool::new();
+    set_gate(&mut pool, FAUCET_GATE_ORACLE, 0);
+    let oracle = pool.admin.insecure_clone();
+    let user = pool.user(0);
+    let expiry = pool.svm.now() + 60;
+    let claim = pool.faucet_ix(&user, TOKEN, FaucetProof::Attestation { expiry });
+
+    // The precompile rejects a forged signature before the program runs.
+    let mut forged = attestation(&pool, &oracle, &user, TOKEN, 0, expiry);
+    forged.data[16 + PUBKEY_SERIALIZED_SIZE] ^= 1;
+    let err = pool
+        .send(&[forged, claim.clone()], &user.keypair)
+        .unwrap_err();
+    assert_eq!(err.index, 0);
+
+    // Offsets pointing at another instruction's data are not accepted.
+    let mut indirect = attestation(&pool, &oracle, &user, TOKEN, 0, expiry);
+    let valid = indirect.clone();
+    indirect.data[4..6].copy_from_slice(&0u16.to_le_bytes());
+    assert_error(
+        pool.send(&[valid.clone(), indirect, claim.clone()], &user.keypair),
+        SkillStakeError::AttestationMismatch,
+    );
+
+    // The attestation has to sit right before the claim.
+    let memo = instructions::get_pool_info();
+    assert_error(
+        pool.send(&[valid.clone(), memo, claim.clone()], &user.keypair),
+        SkillStakeError::AttestationMissing,
+    );
+    pool.send(&[valid, claim], &user.keypair).unwrap();
+}
@@ -0,0 +1,314 @@
+//! Properties of the reward, proof-of-work, retargeting, replay-window and stake boost helpers.
+
+use proptest::prelude::*;
+use skill_stake::{
+    accrue_rewards, accrued_rewards, boosted_proof_reward, meets_difficulty, retarget_difficulty,
+    PowRetarget, ProofStakePolicy, StakeAccount, BPS_DENOMINATOR, MAX_POW_DIFFICULTY,
+    MAX_PROOF_BOOST_BPS, PROOF_REPLAY_WINDOW, SECONDS_PER_YEAR,
+};
+use std::collections::HashSet;
+
+/// Reference accrual in wide arithmetic: `None` where the program reports `MathOverflow`.
+fn reference(amount: u64, apr_bps: u64, last: i64, now: i64) -> Option<u64> {
+    let elapsed = (now as i128 - last as i128).min(i64::MAX as i128);
+    if elapsed <= 0 || amount == 0 {
+        return Some(0);
+    }
+    let numerator = (amount as u128)
+        .checked_mul(apr_bps as u128)?
+        .checked_mul(elapsed as u128)?;
+    let rewards = numerator / (BPS_DENOMINATOR as u128 * SECONDS_PER_YEAR as u128);
+    u64::try_from(rewards).ok()
+}
+
+/// Reference count of leading zero bits.
+fn leading_zero_bits(hash: &[u8; 32]) -> u32 {
+    let mut bits = 0;
+    for byte in hash {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    bits
+}
+
+fn stake_account(amount_staked: u64, last_accrued_ts: i64) -> StakeAccount {
+    StakeAccount {
+        owner: Default::default(),
+        amount_staked,
+        pending_rewards: 0,
+        faucet_claimed: 0,
+        last_accrued_ts,
+        last_proof_ts: 0,
+        last_task_id: 0,
+        bump: 0,
+        version: StakeAccount::VERSION,
+        _padding: [0; 6],
+        last_proof: Vec::new(),
+        last_faucet_ts: 0,
+        faucet_window_start: 0,
+        faucet_window_claimed: 0,
+        task_bitmap: [0; 4],
+        pow_challenge_slot: 0,
+        pow_challenge_hash: [0; 32],
+    }
+}
+
+/// Hashes with a random run of leading zero bytes, so every difficulty is reachable.
+fn hash() -> impl Strategy<Value = [u8; 32]> {
+    (0..=32usize, any::<[u8; 32]>()).prop_map(|(zeros, mut hash)| {
+        hash[..zeros].fill(0);
+        hash
+    })
+}
+
+/// Valid retarget configurations, as `set_pow_retarget` accepts them.
+fn retarget() -> impl Strategy<Value = PowRetarget> {
+    (
+        1..=30 * 86_400i64,
+        1..=1_000_000u64,
+        1..=16u8,
+        0..=MAX_POW_DIFFICULTY,
+        0..=MAX_POW_DIFFICULTY,
+    )
+        .prop_map(|(window, target_proofs, max_step, a, b)| PowRetarget {
+            window,
+            target_proofs,
+            max_step,
+            min_difficulty: a.min(b),
+            max_difficulty: a.max(b),
+        })
+}
+
+/// Boosting policies, as `set_proof_stake_policy` accepts them.
+fn boost_policy() -> impl Strategy<Value = ProofStakePolicy> {
+    (1..=u64::MAX, 1..=MAX_PROOF_BOOST_BPS).prop_map(|(full_boost_stake, max_boost_bps)| {
+        ProofStakePolicy {
+            min_stake: 0,
+            full_boost_stake,
+            max_boost_bps,
+        }
+    })
+}
+
+/// `reward` with `boost_bps` added, in wide arithmetic.
+fn with_boost(reward: u64, boost_bps: u64) -> u128 {
+    reward as u128 * (BPS_DENOMINATOR + boost_bps) as u128 / BPS_DENOMINATOR as u128
+}
+
+proptest! {
+    #[test]
+    fn accrual_matches_reference(
+        amount in any::<u64>(),
+        apr_bps in 0..=5 * BPS_DENOMINATOR,
+        last in any::<i64>(),
+        now in any::<i64>(),
+    ) {
+        let result = accrued_rewards(amount, apr_bps, last, now).ok();
+        prop_assert_eq!(result, reference(amount, apr_bps, last, now));
+    }
+
+    #[test]
+    fn accrual_never_panics(amount in any::<u64>(), apr_bps in any::<u64>(), last in any::<i64>(), now in any::<i64>()) {
+        let _ = accrued_rewards(amount, apr_bps, last, now);
+    }
+
+    #[test]
+    fn accrual_is_monotonic_in_elapsed_time(
+        amount in any::<u64>(),
+        apr_bps in 0..=5 * BPS_DENOMINATOR,
+        last in any::<i64>(),
+        a in any::<i64>(),
+        b in any::<i64>(),
+    ) {
+        let (earlier, later) = (a.min(b), a.max(b));
+        // If the later timestamp accrues without overflow, the earlier one does too and earns no more.
+        if let Ok(later) = accrued_rewards(amount, apr_bps, last, later) {
+            prop_assert!(accrued_rewards(amount, apr_bps, last, earlier).unwrap() <= later);
+        }
+    }
+
+    #[test]
+    fn accrual_is_monotonic_in_amount_and_apr(
+        amount in any::<u64>(),
+        extra in any::<u64>(),
+        apr_bps in 0..=5 * BPS_DENOMINATOR,
+        elapsed in 0..=100 * SECONDS_PER_YEAR,
+    ) {
+        let larger = amount.saturating_add(extra);
+        if let Ok(more) = accrued_rewards(larger, apr_bps, 0, elapsed) {
+            prop_assert!(accrued_rewards(amount, apr_bps, 0, elapsed).unwrap() <= more);
+        }
+        if let Ok(more) = accrued_rewards(amount, apr_bps + 1, 0, elapsed) {
+            prop_assert!(accrued_rewards(amount, apr_bps, 0, elapsed).unwrap() <= more);
+        }
+    }
+
+    #[test]
+    fn split_accrual_never_beats_a_single_step(
+        amount in 0..=u64::MAX / 1_000,
+        apr_bps in 0..=5 * BPS_DENOMINATOR,
+        start in -SECONDS_PER_YEAR..SECONDS_PER_YEAR,
+        first in 0..=10 * SECONDS_PER_YEAR,
+        second in 0..=10 * SECONDS_PER_YEAR,
+    ) {
+        let mut split = stake_account(amount, start);
+        accrue_rewards(&mut split, apr_bps, start + first).unwrap();
+        accrue_rewards(&mut split, apr_bps, start + first + second).unwrap();
+
+        let mut single = stake_account(amount, start);
+        accrue_rewards(&mut single, apr_bps, start + first + second).unwrap();
+
+        // Each step floors once, so splitting loses at most one base unit.
+        prop_assert!(split.pending_rewards <= single.pending_rewards);
+        prop_assert!(split.pending_rewards + 1 >= single.pending_rewards);
+        prop_assert_eq!(split.last_accrued_ts, single.last_accrued_ts);
+    }
+
+    #[test]
+    fn difficulty_matches_leading_zero_bits(hash in hash(), difficulty in any::<u8>()) {
+        prop_assert_eq!(
+            meets_difficulty(&hash, difficulty),
+            leading_zero_bits(&hash) >= difficulty as u32
+        );
+    }
+
+    #[test]
+    fn difficulty_is_monotonic(hash in hash(), difficulty in 1..=u8::MAX) {
+        if meets_difficulty(&hash, difficulty) {
+            prop_assert!(meets_difficulty(&hash, difficulty - 1));
+        }
+    }
+
+    #[test]
+    fn retarget_is_bounded(
+        retarget in retarget(),
+        difficulty in 0..=MAX_POW_DIFFICULTY,
+        proofs in any::<u64>(),
+        elapsed in any::<i64>(),
+    ) {
+        let after = retarget_difficulty(&retarget, difficulty, proofs, elapsed);
+        prop_assert!((retarget.min_difficulty..=retarget.max_difficulty).contains(&after));
+        // Within the bounds, one retarget moves at most `max_step` bits.
+        let clamped = difficulty.clamp(retarget.min_difficulty, retarget.max_difficulty);
+        prop_assert!(after.abs_diff(clamped) <= retarget.max_step);
+    }
+
+    #[test]
+    fn retarget_is_monotonic_in_proofs(
+        retarget in retarget(),
+        difficulty in 0..=MAX_POW_DIFFICULTY,
+        a in any::<u64>(),
+        b in any::<u64>(),
+        elapsed in 1..=365 * 86_400i64,
+    ) {
+        let (fewer, more) = (a.min(b), a.max(b));
+        prop_assert!(
+            retarget_difficulty(&retarget, difficulty, fewer, elapsed)
+                <= retarget_difficulty(&retarget, difficulty, more, elapsed)
+        );
+    }
+
+    #[test]
+    fn retarget_holds_at_the_target_rate(
+        retarget in retarget(),
+        difficulty in 0..=MAX_POW_DIFFICULTY,
+        windows in 1..=100i64,
+    ) {
+        let proofs = retarget.target_proofs * windows as u64;
+        let elapsed = retarget.window * windows;
+        let clamped = difficulty.clamp(retarget.min_difficulty, retarget.max_difficulty);
+        prop_assert_eq!(retarget_difficulty(&retarget, difficulty, proofs, elapsed), clamped);
+    }
+
+    #[test]
+    fn replay_bitmap_matches_a_set_of_recorded_tasks(
+        tasks in prop::collection::vec(0..1_000u64, 1..200),
+    ) {
+        let mut account = stake_account(0, 0);
+        let mut recorded = HashSet::new();
+        let mut highest = 0u64;
+        for task_id in tasks {
+            let expected = recorded.contains(&task_id)
+                || highest.saturating_sub(task_id) >= PROOF_REPLAY_WINDOW;
+            let spent = account.is_task_expired(task_id) || account.is_task_recorded(task_id);
+            prop_assert_eq!(spent, expected, "task {}", task_id);
+            if !spent {
+                account.record_task(task_id);
+                recorded.insert(task_id);
+                highest = highest.max(task_id);
+            }
+        }
+        prop_assert_eq!(account.last_task_id, highest);
+    }
+
+    #[test]
+    fn boost_is_bounded_by_the_cap(
+        policy in boost_policy(),
+        amount_staked in any::<u64>(),
+        reward in any::<u64>(),
+    ) {
+        let most = with_boost(reward, policy.max_boost_bps);
+        match boosted_proof_reward(&policy, amount_staked, reward) {
+            Ok(boosted) => prop_assert!(reward <= boosted && boosted as u128 <= most),
+            Err(_) => prop_assert!(most > u64::MAX as u128),
+        }
+        // Stake past `full_boost_stake` earns exactly the full boost.
+        let full = amount_staked.max(policy.full_boost_stake);
+        let at_cap = boosted_proof_reward(&policy, full, reward).ok();
+        prop_assert_eq!(at_cap, u64::try_from(most).ok());
+    }
+
+    #[test]
+    fn boost_is_monotonic_in_stake(
+        policy in boost_policy(),
+        a in any::<u64>(),
+        b in any::<u64>(),
+        reward in 0..=u32::MAX as u64,
+    ) {
+        let (less, more) = (a.min(b), a.max(b));
+        prop_assert!(
+            boosted_proof_reward(&policy, less, reward).unwrap()
+                <= boosted_proof_reward(&policy, more, reward).unwrap()
+        );
+    }
+
+    #[test]
+    fn boost_follows_the_square_root_of_the_stake_share(
+        n in 1..=u32::MAX as u64,
+        m in any::<u32>(),
+        max_boost_bps in 1..=MAX_PROOF_BOOST_BPS,
+        reward in any::<u32>(),
+    ) {
+        // Staking m^2 of a full n^2 earns m / n of the boost.
+        let m = (m as u64).min(n);
+        let policy = ProofStakePolicy { min_stake: 0, full_boost_stake: n * n, max_boost_bps };
+        let boost_bps = max_boost_bps * m / n;
+        prop_assert_eq!(
+            boosted_proof_reward(&policy, m * m, reward as u64).unwrap() as u128,
+            with_boost(reward as u64, boost_bps)
+        );
+    }
+}
+
+#[test]
+fn unboosted_policies_pay_the_base_reward() {
+    let policy = ProofStakePolicy {
+        min_stake: 5,
+        ..ProofStakePolicy::default()
+    };
+    for amount_staked in [0, 5, u64::MAX] {
+        assert_eq!(
+            boosted_proof_reward(&policy, amount_staked, u64::MAX).unwrap(),
+            u64::MAX
+        );
+    }
+}
+
+#[test]
+fn accrual_overflow_is_an_error_not_a_truncation() {
+    // A year at the maximum APR earns 5x the stake: fits in u128, not in u64.
+    let result = accrued_rewards(u64::MAX, 5 * BPS_DENOMINATOR, 0, SECONDS_PER_YEAR);
+    assert!(result.is_err());
+}
@@ -0,0 +1,339 @@
+//! Layout migrations from accounts written before the `version` byte existed.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use skill_stake::{
+    AccountMigrated, EmergencyWithdrawn, GlobalState, ProofStakePolicy, SkillStakeError,
+    StakeAccount, FAUCET_GATE_NONE, POW_HASH_KECCAK256,
+};
+use skill_stake_client::{instructions, pda};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::rent::Rent;
+use solana_sdk::signature::Signer;
+
+/// `GlobalState` body size before versioning.
+const LEGACY_STATE_SPACE: usize = 176;
+
+/// `StakeAccount` body size in layout v1.
+const V1_STAKE_ACCOUNT_SPACE: usize = 156;
+
+/// `StakeAccount` body size in layout v2, before the replay bitmap.
+const V2_STAKE_ACCOUNT_SPACE: usize = 180;
+
+/// Rewrites the state account as the pre-versioning program left it: shorter, version byte 0.
+fn downgrade_state(pool: &mut Pool) {
+    let key = pda::state().0;
+    let mut account = pool.svm.account(&key).unwrap().clone();
+    account.data.truncate(8 + LEGACY_STATE_SPACE);
+    account.data[GlobalState::VERSION_OFFSET] = 0;
+    account.lamports = Rent::default().minimum_balance(account.data.len());
+    pool.svm.set_account(&key, account);
+}
+
+/// Full-size state whose version byte is still 0.
+fn clear_state_version(pool: &mut Pool) {
+    let key = pda::state().0;
+    let mut account = pool.svm.account(&key).unwrap().clone();
+    account.data[GlobalState::VERSION_OFFSET] = 0;
+    pool.svm.set_account(&key, account);
+}
+
+/// Legacy stake accounts had the same size; only the version byte differs.
+fn downgrade_stake_account(pool: &mut Pool, owner: &Pubkey) {
+    let key = pda::stake_account(owner).0;
+    let mut account = pool.svm.account(&key).unwrap().clone();
+    account.data[StakeAccount::VERSION_OFFSET] = 0;
+    pool.svm.set_account(&key, account);
+}
+
+#[test]
+fn current_accounts_are_not_migrated_again() {
+    let mut pool = Pool::new();
+    let user = pool.user(TOKEN);
+    pool.stake(&user, TOKEN).unwrap();
+
+    let admin = pool.admin.pubkey();
+    let result = pool.send_admin(&[instructions::migrate_state(&admin)]);
+    assert_error(result, SkillStakeError::AccountAlreadyMigrated);
+    let result = pool.send_admin(&[instructions::migrate_stake_account(&admin, &user.key())]);
+    assert_error(result, SkillStakeError::AccountAlreadyMigrated);
+}
+
+#[test]
+fn legacy_state_is_unreadable_until_migrated() {
+    let mut pool = Pool::new();
+    let user = pool.user(10 * TOKEN);
+    pool.stake(&user, 10 * TOKEN).unwrap();
+    let before = pool.state();
+    downgrade_state(&mut pool);
+
+    // The short legacy body does not deserialize into the current layout.
+    assert_anchor_error(
+        pool.stake(&user, TOKEN),
+        ErrorCode::AccountDidNotDeserialize,
+    );
+
+    let outsider = pool.outsider();
+    let result = pool.send(
+        &[instructions::migrate_state(&outsider.pubkey())],
+        &outsider,
+    );
+    assert_error(result, SkillStakeError::Unauthorized);
+
+    let admin = pool.admin.pubkey();
+    let admin_lamports = pool.svm.lamports(&admin);
+    let meta = pool
+        .send_admin(&[instructions::migrate_state(&admin)])
+        .unwrap();
+    let event: AccountMigrated = meta.event();
+    assert_eq!(
+        (event.version_before, event.version_after),
+        (0, GlobalState::VERSION)
+    );
+
+    // The account grew back to the current size and the admin paid the extra rent.
+    let account = pool.svm.account(&pda::state().0).unwrap();
+    assert_eq!(account.data.len(), 8 + GlobalState::SPACE);
+    assert_eq!(
+        account.lamports,
+        Rent::default().minimum_balance(8 + GlobalState::SPACE)
+    );
+    assert!(pool.svm.lamports(&admin) < admin_lamports);
+
+    let after = pool.state();
+    assert_eq!(after.version, GlobalState::VERSION);
+    assert_eq!(after.admin, before.admin);
+    assert_eq!(after.apr_bps, before.apr_bps);
+    assert_eq!(after.total_staked, before.total_staked);
+    assert_eq!(
+        (after.timelock_delay, after.change_count, after.paused),
+        (0, 0, 0)
+    );
+
+    // Migrated pools start ungated and without a faucet budget.
+    assert_eq!(after.faucet_gate, FAUCET_GATE_NONE);
+    assert!(!after.require_proof_attestation);
+    assert_eq!(after.proof_stake_policy, ProofStakePolicy::default());
+    assert_eq!(after.pow_challenge_slots, 0);
+    assert_eq!(after.pow_hash_algorithm, POW_HASH_KECCAK256);
+    assert_eq!((after.faucet_budget, after.faucet_minted), (0, 0));
+    assert_error(
+        pool.faucet(&user, TOKEN),
+        SkillStakeError::FaucetBudgetExceeded,
+    );
+    pool.send_admin(&[instructions::top_up_faucet_budget(&admin, TOKEN)])
+        .unwrap();
+    pool.faucet(&user, TOKEN).unwrap();
+}
+
+#[test]
+fn unversioned_state_blocks_instructions_until_migrated() {
+    let mut pool = Pool::new();
+    let user = pool.user(10 * TOKEN);
+    pool.stake(&user, 10 * TOKEN).unwrap();
+    clear_state_version(&mut pool);
+
+    assert_error(
+        pool.stake(&user, TOKEN),
+        SkillStakeError::AccountNotMigrated,
+    );
+    assert_error(pool.claim(&user), SkillStakeError::AccountNotMigrated);
+    assert_error(
+        pool.faucet(&user, TOKEN),
+        SkillStakeError::AccountNotMigrated,
+    );
+    let admin = pool.admin.pubkey();
+    let result = pool.send_admin(&[instructions::set_apr(&admin, 1)]);
+    assert_error(result, SkillStakeError::AccountNotMigrated);
+
+    // Emergency exits never depend on the migration.
+    pool.emergency_withdraw(&user).unwrap();
+
+    pool.send_admin(&[instructions::migrate_state(&admin)])
+        .unwrap();
+    pool.stake(&user, TOKEN).unwrap();
+}
+
+#[test]
+fn emergency_withdraw_reads_legacy_accounts() {
+    let mut pool = Pool::with_config(Config {
+        pow_difficulty: 0,
+        ..Config::default()
+    });
+    let user = pool.user(10 * TOKEN);
+    pool.stake(&user, 10 * TOKEN).unwrap();
+    pool.record_proof(&user, 1, 0).unwrap();
+    let other = pool.user(10 * TOKEN);
+    pool.stake(&other, 5 * TOKEN).unwrap();
+    pool.svm.warp(86_400);
+
+    // Both accounts in their oldest, shortest layouts.
+    downgrade_state(&mut pool);
+    let key = pda::stake_account(&user.key()).0;
+    let mut account = pool.svm.account(&key).unwrap().clone();
+    account.data.truncate(8 + V1_STAKE_ACCOUNT_SPACE);
+    account.data[StakeAccount::VERSION_OFFSET] = 0;
+    pool.svm.set_account(&key, account);
+
+    let balance = pool.svm.token_balance(&user.token);
+    let event: EmergencyWithdrawn = pool.emergency_withdraw(&user).unwrap().event();
+    assert_eq!(event.amount, 10 * TOKEN);
+    assert_eq!(
+        (event.total_staked_before, event.total_staked_after),
+        (15 * TOKEN, 5 * TOKEN)
+    );
+    assert_eq!(pool.svm.token_balance(&user.token), balance + 10 * TOKEN);
+    assert_error(
+        pool.emergency_withdraw(&user),
+        SkillStakeError::InsufficientStake,
+    );
+
+    // The accounts stay legacy, and migrating them keeps what the withdrawal wrote.
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[
+        instructions::migrate_state(&admin),
+        instructions::migrate_stake_account(&admin, &user.key()),
+    ])
+    .unwrap();
+    assert_eq!(pool.state().total_staked, 5 * TOKEN);
+    let stake_account = pool.stake_account(&user.key());
+    assert_eq!(
+        (stake_account.amount_staked, stake_account.pending_rewards),
+        (0, 0)
+    );
+    assert_eq!(stake_account.last_task_id, 1);
+}
+
+#[test]
+fn legacy_stake_account_is_migrated_by_anyone() {
+    let mut pool = Pool::new();
+    let user = pool.user(10 * TOKEN);
+    pool.stake(&user, 10 * TOKEN).unwrap();
+    pool.svm.warp(86_400);
+    downgrade_stake_account(&mut pool, &user.key());
+
+    assert_error(
+        pool.stake(&user, TOKEN),
+        SkillStakeError::AccountNotMigrated,
+    );
+    assert_error(
+        pool.unstake(&user, TOKEN),
+        SkillStakeError::AccountNotMigrated,
+    );
+    assert_error(pool.claim(&user), SkillStakeError::AccountNotMigrated);
+    assert_error(
+        pool.faucet(&user, TOKEN),
+        SkillStakeError::AccountNotMigrated,
+    );
+    assert_error(
+        pool.record_proof(&user, 1, 0),
+        SkillStakeError::AccountNotMigrated,
+    );
+
+    let payer = pool.outsider();
+    let meta = pool
+        .send(
+            &[instructions::migrate_stake_account(
+                &payer.pubkey(),
+                &user.key(),
+            )],
+            &payer,
+        )
+        .unwrap();
+    assert_eq!(meta.event::<AccountMigrated>().payer, payer.pubkey());
+
+    let stake_account = pool.stake_account(&user.key());
+    assert_eq!(stake_account.version, StakeAccount::VERSION);
+    assert_eq!(stake_account.amount_staked, 10 * TOKEN);
+    pool.claim(&user).unwrap();
+}
+
+#[test]
+fn v1_stake_account_with_a_proof_is_migrated() {
+    let mut pool = Pool::with_config(Config {
+        pow_difficulty: 0,
+        ..Config::default()
+    });
+    let user = pool.user(10 * TOKEN);
+    pool.stake(&user, 10 * TOKEN).unwrap();
+    pool.record_proof(&user, 1, 0).unwrap();
+
+    // v1 ended at the proof storage; a full 48-byte proof leaves too little room for v2 fields.
+    let key = pda::stake_account(&user.key()).0;
+    let mut account = pool.svm.account(&key).unwrap().clone();
+    account.data.truncate(8 + V1_STAKE_ACCOUNT_SPACE);
+    account.data[StakeAccount::VERSION_OFFSET] = 1;
+    account.lamports = Rent::default().minimum_balance(account.data.len());
+    pool.svm.set_account(&key, account);
+    assert_anchor_error(
+        pool.stake(&user, TOKEN),
+        ErrorCode::AccountDidNotDeserialize,
+    );
+
+    let payer = pool.outsider();
+    let ix = instructions::migrate_stake_account(&payer.pubkey(), &user.key());
+    let event: AccountMigrated = pool.send(&[ix], &payer).unwrap().event();
+    assert_eq!(
+        (event.version_before, event.version_after),
+        (1, StakeAccount::VERSION)
+    );
+
+    let stake_account = pool.stake_account(&user.key());
+    assert_eq!(stake_account.last_task_id, 1);
+    assert_eq!(stake_account.last_proof.len(), 48);
+    assert_eq!(stake_account.last_faucet_ts, 0);
+    pool.faucet(&user, TOKEN).unwrap();
+}
+
+#[test]
+fn v2_stake_account_keeps_its_tasks_spent() {
+    let mut pool = Pool::with_config(Config {
+        pow_difficulty: 0,
+        ..Config::default()
+    });
+    let user = pool.user(10 * TOKEN);
+    pool.stake(&user, 10 * TOKEN).unwrap();
+    pool.record_proof(&user, 10, 0).unwrap();
+
+    // v2 had no bitmap: the body ended after the faucet window fields, padded with zeros.
+    let key = pda::stake_account(&user.key()).0;
+    let mut account = pool.svm.account(&key).unwrap().clone();
+    let proof_len = pool.stake_account(&user.key()).last_proof.len();
+    let bitmap_offset = StakeAccount::VERSION_OFFSET + 1 + 6 + 4 + proof_len + 3 * 8;
+    account.data[bitmap_offset..].fill(0);
+    account.data.truncate(8 + V2_STAKE_ACCOUNT_SPACE);
+    account.data[StakeAccount::VERSION_OFFSET] = 2;
+    account.lamports = Rent::default().minimum_balance(account.data.len());
+    pool.svm.set_account(&key, account);
+
+    let payer = pool.outsider();
+    let ix = instructions::migrate_stake_account(&payer.pubkey(), &user.key());
+    pool.send(&[ix], &payer).unwrap();
+
+    // Under v2 nothing at or below the last task could be submitted; that still holds.
+    for task_id in [10, 5] {
+        assert_error(
+            pool.record_proof(&user, task_id, 0),
+            SkillStakeError::ProofTaskReplay,
+        );
+    }
+    pool.record_proof(&user, 11, 0).unwrap();
+}
+
+#[test]
+fn migration_checks_the_account_kind() {
+    let mut pool = Pool::new();
+    let user = pool.user(TOKEN);
+    // No stake account exists yet for this owner.
+    let payer = pool.outsider();
+    let result = pool.send(
+        &[instructions::migrate_stake_account(
+            &payer.pubkey(),
+            &user.key(),
+        )],
+        &payer,
+    );
+    assert_anchor_error(result, ErrorCode::ConstraintOwner);
+}
@@ -0,0 +1,445 @@
+//! The oracle set: M-of-N member signatures in place of the single oracle authority.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use skill_stake::{
+    faucet_attestation_message, pow_config_message, proof_attestation_message,
+    relayed_pow_config_message, OracleMemberAdded, OracleMemberRemoved, OracleSetInitialized,
+    OracleThresholdUpdated, PowConfigUpdated, SkillStakeError, TaskCreated, FAUCET_GATE_ORACLE,
+    MAX_ORACLE_MEMBERS, ROLE_POW_MANAGER,
+};
+use skill_stake_client::{
+    instructions, pda, task_message, FaucetProof, ParamChange, ProofAttestation, TaskParams,
+};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+/// A pool whose separate oracle authority has been replaced by a 2-of-3 oracle set.
+fn setup() -> (Pool, Keypair, Vec<Keypair>) {
+    let oracle = Keypair::new();
+    let mut pool = Pool::with_config(Config {
+        apr_bps: 0,
+        pow_difficulty: 0,
+        oracle_authority: Some(oracle.pubkey()),
+        ..Config::default()
+    });
+    pool.svm.airdrop(&oracle.pubkey(), 10 * SOL);
+    let members: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    let keys: Vec<Pubkey> = members.iter().map(Signer::pubkey).collect();
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::initialize_oracle_set(&admin, &keys, 2)])
+        .unwrap();
+    (pool, oracle, members)
+}
+
+/// One ed25519 instruction carrying each signer's signature over `message`.
+fn signed(signers: &[&Keypair], message: &[u8]) -> Instruction {
+    let signatures: Vec<_> = signers
+        .iter()
+        .map(|signer| (signer.pubkey(), signer.sign_message(message)))
+        .collect();
+    instructions::ed25519_verify_many(&signatures, message)
+}
+
+#[test]
+fn oracle_set_is_created_by_the_admin_and_validated() {
+    let mut pool = Pool::new();
+    let admin = pool.admin.pubkey();
+    let keys: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+
+    let outsider = pool.outsider();
+    let ix = instructions::initialize_oracle_set(&outsider.pubkey(), &keys, 2);
+    assert_anchor_error(pool.send(&[ix], &outsider), ErrorCode::ConstraintHasOne);
+
+    let too_many: Vec<Pubkey> = (0..=MAX_ORACLE_MEMBERS)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    for (members, threshold) in [
+        (keys.clone(), 0),
+        (keys.clone(), 4),
+        (vec![keys[0], keys[1], keys[0]], 2),
+        (vec![keys[0], Pubkey::default()], 1),
+        (Vec::new(), 0),
+        (too_many, 1),
+    ] {
+        assert_error(
+            pool.send_admin(&[instructions::initialize_oracle_set(
+                &admin, &members, threshold,
+            )]),
+            SkillStakeError::InvalidOracleSet,
+        );
+    }
+
+    let event: OracleSetInitialized = pool
+        .send_admin(&[instructions::initialize_oracle_set(&admin, &keys, 2)])
+        .unwrap()
+        .event();
+    assert_eq!(event.oracle_set, pda::oracle_set().0);
+    assert_eq!((event.members, event.threshold), (keys.clone(), 2));
+    let oracle_set = pool.oracle_set();
+    assert_eq!((oracle_set.members, oracle_set.threshold), (keys, 2));
+}
+
+#[test]
+fn membership_changes_keep_the_threshold_reachable() {
+    let (mut pool, _, members) = setup();
+    let admin = pool.admin.pubkey();
+    let member = Pubkey::new_unique();
+
+    let event: OracleMemberAdded = pool
+        .send_admin(&[instructions::add_oracle_member(&admin, &member)])
+        .unwrap()
+        .event();
+    assert_eq!(
+        (event.member, event.member_count, event.threshold),
+        (member, 4, 2)
+    );
+    assert_error(
+        pool.send_admin(&[instructions::add_oracle_member(&admin, &member)]),
+        SkillStakeError::InvalidOracleSet,
+    );
+
+    let event: OracleThresholdUpdated = pool
+        .send_admin(&[instructions::set_oracle_threshold(&admin, 4)])
+        .unwrap()
+        .event();
+    assert_eq!((event.threshold_before, event.threshold_after), (2, 4));
+    // Four members at a threshold of four: nobody can leave until the threshold drops.
+    assert_error(
+        pool.send_admin(&[instructions::remove_oracle_member(&admin, &member)]),
+        SkillStakeError::InvalidOracleSet,
+    );
+    assert_error(
+        pool.send_admin(&[instructions::set_oracle_threshold(&admin, 5)]),
+        SkillStakeError::InvalidOracleSet,
+    );
+    pool.send_admin(&[instructions::set_oracle_threshold(&admin, 3)])
+        .unwrap();
+
+    let event: OracleMemberRemoved = pool
+        .send_admin(&[instructions::remove_oracle_member(&admin, &member)])
+        .unwrap()
+        .event();
+    assert_eq!((event.member, event.member_count), (member, 3));
+    assert_error(
+        pool.send_admin(&[instructions::remove_oracle_member(&admin, &member)]),
+        SkillStakeError::InvalidOracleSet,
+    );
+    let keys: Vec<Pubkey> = members.iter().map(Signer::pubkey).collect();
+    assert_eq!(pool.oracle_set().members, keys);
+
+    let outsider = pool.outsider();
+    for ix in [
+        instructions::add_oracle_member(&outsider.pubkey(), &member),
+        instructions::remove_oracle_member(&outsider.pubkey(), &keys[0]),
+        instructions::set_oracle_threshold(&outsider.pubkey(), 1),
+    ] {
+        assert_anchor_error(pool.send(&[ix], &outsider), ErrorCode::ConstraintHasOne);
+    }
+}
+
+#[test]
+fn timelocked_pools_cannot_change_the_oracle_set() {
+    let (mut pool, _, _) = setup();
+    let admin = pool.admin.pubkey();
+    let now = pool.svm.now();
+    pool.send_admin(&[
+        instructions::queue_change(&admin, 0, ParamChange::TimelockDelay { delay: 60 }, now),
+        instructions::execute_change(&admin, &admin, 0),
+    ])
+    .unwrap();
+    assert_error(
+        pool.send_admin(&[instructions::set_oracle_threshold(&admin, 1)]),
+        SkillStakeError::TimelockActive,
+    );
+}
+
+#[test]
+fn proof_attestations_need_the_threshold() {
+    let (mut pool, oracle, members) = setup();
+    let user = pool.user(TOKEN);
+    pool.stake(&user, TOKEN).unwrap();
+    pool.ensure_task(1);
+    let attested = ProofAttestation {
+        reward: 7 * TOKEN,
+        expiry: pool.svm.now() + 60,
+    };
+    let message =
+        proof_attestation_message(&user.key(), &pool.mint, 1, attested.reward, attested.expiry);
+    let record = instructions::record_proof(&user.key(), 1, 0, Some(attested));
+
+    let outsider = Keypair::new();
+    for (signers, expected) in [
+        // The replaced oracle authority is not a member.
+        (vec![&oracle], SkillStakeError::AttestationMismatch),
+        (
+            vec![&members[0], &outsider],
+            SkillStakeError::AttestationMismatch,
+        ),
+        (vec![&members[0]], SkillStakeError::OracleThresholdNotMet),
+        // A member signing twice still counts once.
+        (
+            vec![&members[1], &members[1]],
+            SkillStakeError::OracleThresholdNotMet,
+        ),
+    ] {
+        assert_error(
+            pool.send(&[signed(&signers, &message), record.clone()], &user.keypair),
+            expected,
+        );
+    }
+
+    pool.send(
+        &[signed(&[&members[2], &members[0]], &message), record],
+        &user.keypair,
+    )
+    .unwrap();
+    assert_eq!(
+        pool.stake_account(&user.key()).pending_rewards,
+        attested.reward
+    );
+}
+
+#[test]
+fn member_signatures_may_be_split_across_ed25519_instructions() {
+    let (mut pool, _, members) = setup();
+    let user = pool.user(TOKEN);
+    pool.stake(&user, TOKEN).unwrap();
+    pool.ensure_task(1);
+    let attested = ProofAttestation {
+        reward: 7 * TOKEN,
+        expiry: pool.svm.now() + 60,
+    };
+    let message =
+        proof_attestation_message(&user.key(), &pool.mint, 1, attested.reward, attested.expiry);
+    let record = instructions::record_proof(&user.key(), 1, 0, Some(attested));
+
+    // Every split signature is checked, and only consecutive ones right before the proof count.
+    let outsider = Keypair::new();
+    assert_error(
+        pool.send(
+            &[
+                signed(&[&outsider], &message),
+                signed(&[&members[0]], &message),
+                record.clone(),
+            ],
+            &user.keypair,
+        ),
+        SkillStakeError::AttestationMismatch,
+    );
+    let spacer = instructions::commit_pow_challenge(&user.key());
+    assert_error(
+        pool.send(
+            &[
+                signed(&[&members[1]], &message),
+                spacer,
+                signed(&[&members[0]], &message),
+                record.clone(),
+            ],
+            &user.keypair,
+        ),
+        SkillStakeError::OracleThresholdNotMet,
+    );
+
+    pool.send(
+        &[
+            signed(&[&members[1]], &message),
+            signed(&[&members[0]], &message),
+            record,
+        ],
+        &user.keypair,
+    )
+    .unwrap();
+    assert_eq!(
+        pool.stake_account(&user.key()).pending_rewards,
+        attested.reward
+    );
+}
+
+#[test]
+fn pow_config_updates_are_relayed_with_member_signatures() {
+    let (mut pool, oracle, members) = setup();
+    let relayer = pool.outsider();
+    let update = |nonce| instructions::set_pow_config(&relayer.pubkey(), 12, 3 * TOKEN, nonce);
+
+    // The oracle authority alone no longer moves the config.
+    let ix = instructions::set_pow_config(&oracle.pubkey(), 12, 3 * TOKEN, 1);
+    assert_error(
+        pool.send(&[ix], &oracle),
+        SkillStakeError::AttestationMissing,
+    );
+
+    let message = pow_config_message(&pool.mint, 12, 3 * TOKEN, 1);
+    assert_error(
+        pool.send(&[signed(&[&members[0]], &message), update(1)], &relayer),
+        SkillStakeError::OracleThresholdNotMet,
+    );
+    // Signatures bind the values and the nonce.
+    assert_error(
+        pool.send(
+            &[signed(&[&members[0], &members[1]], &message), update(2)],
+            &relayer,
+        ),
+        SkillStakeError::AttestationMismatch,
+    );
+
+    let attest = signed(&[&members[0], &members[1]], &message);
+    let event: PowConfigUpdated = pool
+        .send(&[attest.clone(), update(1)], &relayer)
+        .unwrap()
+        .event();
+    assert_eq!(event.authority, relayer.pubkey());
+    assert_eq!(
+        (event.pow_difficulty_after, event.pow_reward_after),
+        (12, 3 * TOKEN)
+    );
+    assert_error(
+        pool.send(&[attest, update(1)], &relayer),
+        SkillStakeError::StaleOracleUpdate,
+    );
+
+    // No single key moves the config any more, not even the admin's or a PoW manager's.
+    let manager = pool.role_member(ROLE_POW_MANAGER);
+    let ix = instructions::set_pow_config(&manager.pubkey(), 4, TOKEN, 2);
+    assert_error(
+        pool.send(&[ix], &manager),
+        SkillStakeError::AttestationMissing,
+    );
+    let admin = pool.admin.pubkey();
+    let ix = instructions::set_pow_config(&admin, 4, TOKEN, 2);
+    assert_error(pool.send_admin(&[ix]), SkillStakeError::AttestationMissing);
+
+    let message = pow_config_message(&pool.mint, 4, TOKEN, 2);
+    let attest = signed(&[&members[1], &members[2]], &message);
+    let ix = instructions::set_pow_config(&manager.pubkey(), 4, TOKEN, 2);
+    pool.send(&[attest, ix], &manager).unwrap();
+    assert_eq!(pool.state().pow_difficulty, 4);
+}
+
+#[test]
+fn relayed_pow_config_needs_the_member_threshold() {
+    let (mut pool, oracle, members) = setup();
+    let relayer = pool.outsider();
+    let expiry = pool.svm.now() + 60;
+    let message = relayed_pow_config_message(&pool.mint, 12, 3 * TOKEN, 1, expiry);
+    let relay = instructions::relay_pow_config(&relayer.pubkey(), 12, 3 * TOKEN, 1, expiry);
+
+    // The replaced oracle authority's signature is not enough.
+    assert_error(
+        pool.send(&[signed(&[&oracle], &message), relay.clone()], &relayer),
+        SkillStakeError::AttestationMismatch,
+    );
+    assert_error(
+        pool.send(&[signed(&[&members[2]], &message), relay.clone()], &relayer),
+        SkillStakeError::OracleThresholdNotMet,
+    );
+
+    pool.send(
+        &[signed(&[&members[1], &members[2]], &message), relay],
+        &relayer,
+    )
+    .unwrap();
+    let state = pool.state();
+    assert_eq!(
+        (state.pow_difficulty, state.pow_reward, state.oracle_nonce),
+        (12, 3 * TOKEN, 1)
+    );
+}
+
+#[test]
+fn only_pow_managers_create_tasks_under_an_oracle_set() {
+    let (mut pool, oracle, _) = setup();
+    let params = pool.default_task();
+    let ix = instructions::create_task(&oracle.pubkey(), 1, params);
+    assert_error(pool.send(&[ix], &oracle), SkillStakeError::Unauthorized);
+
+    let manager = pool.role_member(ROLE_POW_MANAGER);
+    let ix = instructions::create_task(&manager.pubkey(), 1, params);
+    pool.send(&[ix], &manager).unwrap();
+}
+
+#[test]
+fn oracle_set_members_sign_tasks_for_relay() {
+    let (mut pool, oracle, members) = setup();
+    let relayer = pool.outsider();
+    let params = TaskParams {
+        reward: 3 * TOKEN,
+        ..pool.default_task()
+    };
+    let message = task_message(&pool.mint, 1, &params);
+    let relay = instructions::relay_create_task(&relayer.pubkey(), 1, params);
+
+    // The replaced oracle authority's signature is not enough, nor is one member's.
+    assert_error(
+        pool.send(&[signed(&[&oracle], &message), relay.clone()], &relayer),
+        SkillStakeError::AttestationMismatch,
+    );
+    assert_error(
+        pool.send(&[signed(&[&members[0]], &message), relay.clone()], &relayer),
+        SkillStakeError::OracleThresholdNotMet,
+    );
+    // The signatures cover the task's parameters.
+    let inflated = TaskParams {
+        reward: 30 * TOKEN,
+        ..params
+    };
+    let tampered = instructions::relay_create_task(&relayer.pubkey(), 1, inflated);
+    assert_error(
+        pool.send(
+            &[signed(&[&members[0], &members[1]], &message), tampered],
+            &relayer,
+        ),
+        SkillStakeError::AttestationMismatch,
+    );
+
+    let meta = pool
+        .send(
+            &[signed(&[&members[0], &members[1]], &message), relay.clone()],
+            &relayer,
+        )
+        .unwrap();
+    let created: TaskCreated = meta.event();
+    assert_eq!(
+        (created.authority, created.task_id, created.params.reward),
+        (relayer.pubkey(), 1, 3 * TOKEN)
+    );
+    let task = pool.task(1);
+    assert_eq!((task.reward, task.opens_at), (3 * TOKEN, params.opens_at));
+
+    // The task exists now, so the same message cannot open it twice.
+    assert!(pool
+        .send(
+            &[signed(&[&members[1], &members[2]], &message), relay],
+            &relayer,
+        )
+        .is_err());
+}
+
+#[test]
+fn faucet_attestations_need_the_threshold() {
+    let (mut pool, _, members) = setup();
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::set_faucet_gate(&admin, FAUCET_GATE_ORACLE, 0)])
+        .unwrap();
+    let user = pool.user(0);
+    let expiry = pool.svm.now() + 60;
+    let claim = pool.faucet_ix(&user, TOKEN, FaucetProof::Attestation { expiry });
+    let message = faucet_attestation_message(&user.key(), &pool.mint, TOKEN, 0, expiry);
+
+    assert_error(
+        pool.send(
+            &[signed(&[&members[1]], &message), claim.clone()],
+            &user.keypair,
+        ),
+        SkillStakeError::OracleThresholdNotMet,
+    );
+    pool.send(
+        &[signed(&[&members[0], &members[1]], &message), claim],
+        &user.keypair,
+    )
+    .unwrap();
+    assert_eq!(pool.svm.token_balance(&user.token), TOKEN);
+}
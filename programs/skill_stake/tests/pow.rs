@@ -0,0 +1,839 @@
+//! `record_proof` and `check_proof`: difficulty targets, replay protection, rewards and retargeting.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use skill_stake::{
+    proof_attestation_message, PowChallengeCommitted, PowChallengeUpdated, PowDifficultyRetargeted,
+    PowHashAlgorithmUpdated, PowRetargetUpdated, ProofAttestationUpdated, ProofCheck, ProofHistory,
+    ProofRecorded, ProofStakePolicyUpdated, SkillStakeError, MAX_POW_CHALLENGE_SLOTS,
+    MAX_POW_DIFFICULTY, MAX_PROOF_BOOST_BPS, MAX_PROOF_HISTORY, POW_HASH_BLAKE3,
+    POW_HASH_KECCAK256, POW_HASH_SHA256, PROOF_REPLAY_WINDOW, ROLE_POW_MANAGER,
+};
+use skill_stake_client::{instructions, pda, ProgramAccount};
+use skill_stake_client::{PowRetarget, ProofAttestation, ProofStakePolicy, TaskParams};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::signature::{Keypair, Signer};
+
+/// A pool at `difficulty` with a user whose stake account exists but earns no APR.
+fn setup(difficulty: u8) -> (Pool, User) {
+    let mut pool = Pool::with_config(Config {
+        apr_bps: 0,
+        pow_difficulty: difficulty,
+        ..Config::default()
+    });
+    let user = pool.user(TOKEN);
+    pool.stake(&user, TOKEN).unwrap();
+    (pool, user)
+}
+
+fn check_proof(pool: &mut Pool, user: &User, task_id: u64, nonce: u64) -> ProofCheck {
+    pool.ensure_task(task_id);
+    let admin = pool.admin.insecure_clone();
+    pool.svm
+        .simulate(
+            &[instructions::check_proof(&user.key(), task_id, nonce, true)],
+            &[&admin],
+        )
+        .unwrap()
+        .return_value()
+}
+
+#[test]
+fn proofs_are_accepted_and_rejected_at_several_difficulties() {
+    for difficulty in [1, 4, 8, 12, 16] {
+        let (mut pool, user) = setup(difficulty);
+
+        let weak = mine_exact(&user.key(), &pool.mint, 1, difficulty - 1);
+        let check = check_proof(&mut pool, &user, 1, weak);
+        assert!(!check.meets_difficulty && check.reward == 0);
+        assert_error(
+            pool.record_proof(&user, 1, weak),
+            SkillStakeError::ProofDifficultyNotMet,
+        );
+
+        let nonce = mine(&user.key(), &pool.mint, 1, difficulty);
+        let check = check_proof(&mut pool, &user, 1, nonce);
+        assert!(check.meets_difficulty && !check.is_replay);
+        assert_eq!(check.reward, TOKEN);
+
+        let event: ProofRecorded = pool.record_proof(&user, 1, nonce).unwrap().event();
+        assert_eq!(event.hash, pow_hash(&user.key(), &pool.mint, 1, nonce));
+        assert_eq!(event.hash, check.hash);
+        assert_eq!(
+            (event.pending_rewards_before, event.pending_rewards_after),
+            (0, TOKEN)
+        );
+    }
+}
+
+#[test]
+fn difficulty_zero_accepts_any_nonce() {
+    let (mut pool, user) = setup(0);
+    for task_id in 1..=5 {
+        pool.record_proof(&user, task_id, task_id * 7).unwrap();
+    }
+    assert_eq!(pool.stake_account(&user.key()).pending_rewards, 5 * TOKEN);
+}
+
+#[test]
+fn proof_updates_stake_account() {
+    let (mut pool, user) = setup(8);
+    let nonce = mine(&user.key(), &pool.mint, 42, 8);
+    pool.svm.warp(60);
+    pool.record_proof(&user, 42, nonce).unwrap();
+
+    let stake_account = pool.stake_account(&user.key());
+    assert_eq!(stake_account.last_task_id, 42);
+    assert_eq!(stake_account.last_proof_ts, pool.svm.now());
+    assert_eq!(stake_account.pending_rewards, TOKEN);
+
+    // The client decodes the stored proof record.
+    let data = &pool
+        .svm
+        .account(&pda::stake_account(&user.key()).0)
+        .unwrap()
+        .data;
+    let record = skill_stake_client::StakeAccount::try_from_account_data(data)
+        .unwrap()
+        .last_proof_record()
+        .unwrap()
+        .unwrap();
+    assert_eq!((record.task_id, record.nonce), (42, nonce));
+
+    let history = pool.proof_history(&user.key());
+    assert_eq!((history.owner, history.entries.len()), (user.key(), 1));
+    let entry = &history.entries[0];
+    assert_eq!((entry.record.task_id, entry.record.nonce), (42, nonce));
+    assert_eq!((entry.reward, entry.timestamp), (TOKEN, pool.svm.now()));
+
+    // Rewards from proofs are claimable like staking rewards.
+    pool.claim(&user).unwrap();
+    assert_eq!(pool.svm.token_balance(&user.token), TOKEN);
+}
+
+#[test]
+fn tasks_pay_once_in_any_order() {
+    let (mut pool, user) = setup(4);
+    let nonce = mine(&user.key(), &pool.mint, 10, 4);
+    pool.record_proof(&user, 10, nonce).unwrap();
+
+    assert!(check_proof(&mut pool, &user, 10, nonce).is_replay);
+    assert_error(
+        pool.record_proof(&user, 10, nonce),
+        SkillStakeError::ProofTaskReplay,
+    );
+
+    // An earlier task solved later still pays, once.
+    let older = mine(&user.key(), &pool.mint, 5, 4);
+    assert!(!check_proof(&mut pool, &user, 5, older).is_replay);
+    pool.record_proof(&user, 5, older).unwrap();
+    assert_error(
+        pool.record_proof(&user, 5, older),
+        SkillStakeError::ProofTaskReplay,
+    );
+
+    let newer = mine(&user.key(), &pool.mint, 11, 4);
+    pool.record_proof(&user, 11, newer).unwrap();
+    let stake_account = pool.stake_account(&user.key());
+    assert_eq!(stake_account.last_task_id, 11);
+    assert_eq!(stake_account.pending_rewards, 3 * TOKEN);
+}
+
+#[test]
+fn tasks_below_the_replay_window_are_rejected() {
+    let (mut pool, user) = setup(0);
+    let highest = PROOF_REPLAY_WINDOW + 10;
+    pool.record_proof(&user, highest, 0).unwrap();
+
+    // The oldest task still inside the window is accepted; the one below it is not.
+    pool.record_proof(&user, highest - PROOF_REPLAY_WINDOW + 1, 0)
+        .unwrap();
+    assert!(check_proof(&mut pool, &user, 10, 0).is_replay);
+    assert_error(
+        pool.record_proof(&user, 10, 0),
+        SkillStakeError::ProofTaskTooOld,
+    );
+
+    // Sliding the window forward frees the slots the new tasks reuse.
+    pool.record_proof(&user, highest + PROOF_REPLAY_WINDOW, 0)
+        .unwrap();
+    pool.record_proof(&user, highest + 1, 0).unwrap();
+}
+
+#[test]
+fn proofs_are_bound_to_the_miner() {
+    let (mut pool, alice) = setup(12);
+    let bob = pool.user(TOKEN);
+    pool.stake(&bob, TOKEN).unwrap();
+
+    // A nonce mined for Alice hashes differently for Bob.
+    let nonce = mine(&alice.key(), &pool.mint, 1, 12);
+    assert!(leading_zero_bits(&pow_hash(&bob.key(), &pool.mint, 1, nonce)) < 12);
+    assert_error(
+        pool.record_proof(&bob, 1, nonce),
+        SkillStakeError::ProofDifficultyNotMet,
+    );
+    pool.record_proof(&alice, 1, nonce).unwrap();
+}
+
+#[test]
+fn raising_difficulty_invalidates_weaker_nonces() {
+    let (mut pool, user) = setup(4);
+    let nonce = mine_exact(&user.key(), &pool.mint, 1, 4);
+
+    // Tasks registered after the update take the new difficulty and reward.
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::set_pow_config(&admin, 8, 2 * TOKEN, 1)])
+        .unwrap();
+    assert_error(
+        pool.record_proof(&user, 1, nonce),
+        SkillStakeError::ProofDifficultyNotMet,
+    );
+
+    let nonce = mine(&user.key(), &pool.mint, 1, 8);
+    let event: ProofRecorded = pool.record_proof(&user, 1, nonce).unwrap().event();
+    assert_eq!(event.reward, 2 * TOKEN);
+}
+
+#[test]
+fn record_proof_requires_a_stake_account() {
+    let mut pool = Pool::with_config(Config {
+        pow_difficulty: 0,
+        ..Config::default()
+    });
+    let user = pool.user(0);
+    assert_anchor_error(
+        pool.record_proof(&user, 1, 0),
+        ErrorCode::AccountNotInitialized,
+    );
+
+    // `check_proof` works for wallets that never staked.
+    let admin = pool.admin.insecure_clone();
+    let check: ProofCheck = pool
+        .svm
+        .simulate(
+            &[instructions::check_proof(&user.key(), 1, 0, false)],
+            &[&admin],
+        )
+        .unwrap()
+        .return_value();
+    assert!(check.meets_difficulty && !check.is_replay);
+}
+
+#[test]
+fn reward_overflow_is_reported() {
+    let mut pool = Pool::with_config(Config {
+        apr_bps: 0,
+        pow_difficulty: 0,
+        pow_reward: u64::MAX,
+        ..Config::default()
+    });
+    let user = pool.user(TOKEN);
+    pool.stake(&user, TOKEN).unwrap();
+
+    pool.record_proof(&user, 1, 0).unwrap();
+    assert_error(
+        pool.record_proof(&user, 2, 0),
+        SkillStakeError::MathOverflow,
+    );
+}
+
+fn set_retarget(pool: &mut Pool, retarget: PowRetarget) {
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::set_pow_retarget(&admin, retarget)])
+        .unwrap();
+}
+
+/// Records the next task for `user` at the current difficulty.
+fn prove(pool: &mut Pool, user: &User) -> TxMeta {
+    let task_id = pool.stake_account(&user.key()).last_task_id + 1;
+    let difficulty = pool.state().pow_difficulty;
+    let nonce = mine(&user.key(), &pool.mint, task_id, difficulty);
+    pool.record_proof(user, task_id, nonce).unwrap()
+}
+
+const HOUR: PowRetarget = PowRetarget {
+    window: 3_600,
+    target_proofs: 2,
+    max_step: 2,
+    min_difficulty: 1,
+    max_difficulty: 8,
+};
+
+#[test]
+fn retarget_config_is_validated_and_role_gated() {
+    let mut pool = Pool::new();
+    let admin = pool.admin.pubkey();
+    for invalid in [
+        PowRetarget { window: -1, ..HOUR },
+        PowRetarget {
+            target_proofs: 0,
+            ..HOUR
+        },
+        PowRetarget {
+            max_step: 0,
+            ..HOUR
+        },
+        PowRetarget {
+            min_difficulty: 9,
+            ..HOUR
+        },
+        PowRetarget {
+            max_difficulty: MAX_POW_DIFFICULTY + 1,
+            ..HOUR
+        },
+    ] {
+        let ix = instructions::set_pow_retarget(&admin, invalid);
+        assert_error(
+            pool.send_admin(&[ix]),
+            SkillStakeError::InvalidRetargetConfig,
+        );
+    }
+
+    let outsider = pool.outsider();
+    let ix = instructions::set_pow_retarget(&outsider.pubkey(), HOUR);
+    assert_error(pool.send(&[ix], &outsider), SkillStakeError::Unauthorized);
+
+    let manager = pool.role_member(ROLE_POW_MANAGER);
+    let ix = instructions::set_pow_retarget(&manager.pubkey(), HOUR);
+    let event: PowRetargetUpdated = pool.send(&[ix], &manager).unwrap().event();
+    assert_eq!(event.before, skill_stake::PowRetarget::default());
+    assert_eq!(event.after.window, HOUR.window);
+    let state = pool.state();
+    assert_eq!(state.pow_retarget.target_proofs, 2);
+    assert_eq!(
+        (state.pow_window_start, state.pow_window_proofs),
+        (pool.svm.now(), 0)
+    );
+}
+
+#[test]
+fn fast_proofs_raise_the_difficulty_by_bounded_steps() {
+    let (mut pool, user) = setup(2);
+    set_retarget(&mut pool, HOUR);
+
+    // Nine proofs in one window against a target of two: over 4x the rate, so +2 bits.
+    for _ in 0..8 {
+        assert!(prove(&mut pool, &user)
+            .events::<PowDifficultyRetargeted>()
+            .is_empty());
+    }
+    assert_eq!(pool.state().pow_window_proofs, 8);
+    pool.svm.warp(3_600);
+    let event: PowDifficultyRetargeted = prove(&mut pool, &user).event();
+    assert_eq!((event.window_proofs, event.elapsed), (9, 3_600));
+    assert_eq!((event.difficulty_before, event.difficulty_after), (2, 4));
+
+    // Far faster still, but one retarget moves at most `max_step`, and never above the maximum.
+    for _ in 0..40 {
+        prove(&mut pool, &user);
+    }
+    pool.svm.warp(3_600);
+    let event: PowDifficultyRetargeted = prove(&mut pool, &user).event();
+    assert_eq!((event.difficulty_before, event.difficulty_after), (4, 6));
+    let state = pool.state();
+    assert_eq!(
+        (state.pow_window_start, state.pow_window_proofs),
+        (pool.svm.now(), 0)
+    );
+}
+
+#[test]
+fn slow_proofs_lower_the_difficulty_down_to_the_minimum() {
+    let (mut pool, user) = setup(4);
+    set_retarget(&mut pool, HOUR);
+
+    // One proof over two hours is a quarter of the target rate: -2 bits.
+    pool.svm.warp(7_200);
+    let event: PowDifficultyRetargeted = prove(&mut pool, &user).event();
+    assert_eq!((event.difficulty_before, event.difficulty_after), (4, 2));
+
+    // At the target rate nothing changes.
+    prove(&mut pool, &user);
+    pool.svm.warp(3_600);
+    let event: PowDifficultyRetargeted = prove(&mut pool, &user).event();
+    assert_eq!(event.difficulty_after, 2);
+
+    pool.svm.warp(10 * 3_600);
+    let event: PowDifficultyRetargeted = prove(&mut pool, &user).event();
+    assert_eq!(event.difficulty_after, HOUR.min_difficulty);
+}
+
+#[test]
+fn retargets_apply_to_tasks_that_follow_the_pool() {
+    let (mut pool, user) = setup(2);
+    set_retarget(&mut pool, HOUR);
+    let pinned = TaskParams {
+        difficulty: 2,
+        ..pool.default_task()
+    };
+    pool.create_task(100, pinned).unwrap();
+    pool.ensure_task(101);
+    for _ in 0..8 {
+        prove(&mut pool, &user);
+    }
+    pool.svm.warp(3_600);
+    let event: PowDifficultyRetargeted = prove(&mut pool, &user).event();
+    assert_eq!(event.difficulty_after, 4);
+
+    // A nonce at the old difficulty no longer pays on a task following the pool...
+    let weak = mine_exact(&user.key(), &pool.mint, 101, 2);
+    assert_eq!(check_proof(&mut pool, &user, 101, weak).difficulty, 4);
+    assert_error(
+        pool.record_proof(&user, 101, weak),
+        SkillStakeError::ProofDifficultyNotMet,
+    );
+    let nonce = mine(&user.key(), &pool.mint, 101, 4);
+    pool.record_proof(&user, 101, nonce).unwrap();
+
+    // ...while a task with its own difficulty keeps it.
+    let weak = mine_exact(&user.key(), &pool.mint, 100, 2);
+    pool.record_proof(&user, 100, weak).unwrap();
+}
+
+#[test]
+fn oracle_override_restarts_the_window() {
+    let (mut pool, user) = setup(2);
+    set_retarget(&mut pool, HOUR);
+    for _ in 0..8 {
+        prove(&mut pool, &user);
+    }
+    pool.svm.warp(3_000);
+
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::set_pow_config(&admin, 3, TOKEN, 1)])
+        .unwrap();
+    let state = pool.state();
+    assert_eq!(
+        (state.pow_window_start, state.pow_window_proofs),
+        (pool.svm.now(), 0)
+    );
+
+    // The old window's proofs are forgotten; the override holds until a full window passes.
+    pool.svm.warp(600);
+    assert!(prove(&mut pool, &user)
+        .events::<PowDifficultyRetargeted>()
+        .is_empty());
+    assert_eq!(pool.state().pow_difficulty, 3);
+
+    // Turning retargeting off leaves the difficulty where it is.
+    set_retarget(&mut pool, PowRetarget::default());
+    pool.svm.warp(10 * 3_600);
+    assert!(prove(&mut pool, &user)
+        .events::<PowDifficultyRetargeted>()
+        .is_empty());
+    assert_eq!(pool.state().pow_difficulty, 3);
+}
+
+/// The oracle's ed25519 instruction paying `reward` for `task_id`.
+fn attestation(
+    pool: &Pool,
+    signer: &Keypair,
+    user: &User,
+    task_id: u64,
+    attested: ProofAttestation,
+) -> Instruction {
+    let message = proof_attestation_message(
+        &user.key(),
+        &pool.mint,
+        task_id,
+        attested.reward,
+        attested.expiry,
+    );
+    let signature = signer.sign_message(&message);
+    instructions::ed25519_verify(&signer.pubkey(), &signature, &message)
+}
+
+#[test]
+fn proof_attestation_switch_is_role_gated() {
+    let mut pool = Pool::new();
+    let outsider = pool.outsider();
+    let ix = instructions::set_proof_attestation(&outsider.pubkey(), true);
+    assert_error(pool.send(&[ix], &outsider), SkillStakeError::Unauthorized);
+
+    let manager = pool.role_member(ROLE_POW_MANAGER);
+    let ix = instructions::set_proof_attestation(&manager.pubkey(), true);
+    let event: ProofAttestationUpdated = pool.send(&[ix], &manager).unwrap().event();
+    assert_eq!((event.required_before, event.required_after), (false, true));
+    assert!(pool.state().require_proof_attestation);
+}
+
+#[test]
+fn attested_proofs_pay_the_attested_reward() {
+    let (mut pool, user) = setup(0);
+    let oracle = pool.admin.insecure_clone();
+    let attested = ProofAttestation {
+        reward: 3 * TOKEN,
+        expiry: pool.svm.now() + 60,
+    };
+    pool.ensure_task(1);
+    pool.ensure_task(2);
+    let record = instructions::record_proof(&user.key(), 1, 0, Some(attested));
+
+    assert_error(
+        pool.send(std::slice::from_ref(&record), &user.keypair),
+        SkillStakeError::AttestationMissing,
+    );
+
+    // Wrong signer, wrong reward, wrong task.
+    let outsider = pool.outsider();
+    for attest in [
+        attestation(&pool, &outsider, &user, 1, attested),
+        attestation(
+            &pool,
+            &oracle,
+            &user,
+            1,
+            ProofAttestation {
+                reward: 4 * TOKEN,
+                ..attested
+            },
+        ),
+        attestation(&pool, &oracle, &user, 2, attested),
+    ] {
+        assert_error(
+            pool.send(&[attest, record.clone()], &user.keypair),
+            SkillStakeError::AttestationMismatch,
+        );
+    }
+
+    let attest = attestation(&pool, &oracle, &user, 1, attested);
+    let event: ProofRecorded = pool.send(&[attest, record], &user.keypair).unwrap().event();
+    assert_eq!(event.reward, 3 * TOKEN);
+    assert_eq!(pool.stake_account(&user.key()).pending_rewards, 3 * TOKEN);
+
+    let attest = attestation(&pool, &oracle, &user, 2, attested);
+    let record = instructions::record_proof(&user.key(), 2, 0, Some(attested));
+    pool.svm.warp(61);
+    assert_error(
+        pool.send(&[attest, record], &user.keypair),
+        SkillStakeError::AttestationExpired,
+    );
+}
+
+#[test]
+fn required_attestations_reject_bare_proofs() {
+    let (mut pool, user) = setup(0);
+    let check = check_proof(&mut pool, &user, 1, 0);
+    assert!(!check.requires_attestation);
+    assert!(check.reward > 0);
+
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::set_proof_attestation(&admin, true)])
+        .unwrap();
+    let check = check_proof(&mut pool, &user, 1, 0);
+    assert!(check.requires_attestation && check.meets_difficulty);
+    assert_eq!(check.reward, 0);
+    assert_error(
+        pool.record_proof(&user, 1, 0),
+        SkillStakeError::AttestationMissing,
+    );
+
+    let oracle = pool.admin.insecure_clone();
+    let attested = ProofAttestation {
+        reward: 0,
+        expiry: pool.svm.now(),
+    };
+    let attest = attestation(&pool, &oracle, &user, 1, attested);
+    let record = instructions::record_proof(&user.key(), 1, 0, Some(attested));
+    pool.ensure_task(1);
+    pool.send(&[attest, record], &user.keypair).unwrap();
+    assert_eq!(pool.stake_account(&user.key()).last_task_id, 1);
+}
+
+fn set_stake_policy(pool: &mut Pool, policy: ProofStakePolicy) {
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::set_proof_stake_policy(&admin, policy)])
+        .unwrap();
+}
+
+#[test]
+fn proof_stake_policy_is_validated_and_role_gated() {
+    let mut pool = Pool::new();
+    let admin = pool.admin.pubkey();
+    let policy = ProofStakePolicy {
+        min_stake: TOKEN,
+        full_boost_stake: 100 * TOKEN,
+        max_boost_bps: MAX_PROOF_BOOST_BPS,
+    };
+    for invalid in [
+        ProofStakePolicy {
+            max_boost_bps: MAX_PROOF_BOOST_BPS + 1,
+            ..policy.clone()
+        },
+        ProofStakePolicy {
+            full_boost_stake: 0,
+            ..policy.clone()
+        },
+        ProofStakePolicy {
+            max_boost_bps: 0,
+            ..policy.clone()
+        },
+    ] {
+        let ix = instructions::set_proof_stake_policy(&admin, invalid);
+        assert_error(
+            pool.send_admin(&[ix]),
+            SkillStakeError::InvalidProofStakePolicy,
+        );
+    }
+
+    let outsider = pool.outsider();
+    let ix = instructions::set_proof_stake_policy(&outsider.pubkey(), policy.clone());
+    assert_error(pool.send(&[ix], &outsider), SkillStakeError::Unauthorized);
+
+    let manager = pool.role_member(ROLE_POW_MANAGER);
+    let ix = instructions::set_proof_stake_policy(&manager.pubkey(), policy);
+    let event: ProofStakePolicyUpdated = pool.send(&[ix], &manager).unwrap().event();
+    assert_eq!(event.before, skill_stake::ProofStakePolicy::default());
+    assert_eq!(event.after.min_stake, TOKEN);
+    assert_eq!(
+        pool.state().proof_stake_policy.full_boost_stake,
+        100 * TOKEN
+    );
+}
+
+#[test]
+fn proofs_need_the_minimum_stake() {
+    let (mut pool, user) = setup(0);
+    let newcomer = pool.user(TOKEN);
+    pool.stake(&newcomer, 1).unwrap();
+    pool.unstake(&newcomer, 1).unwrap();
+    // The default policy accepts an empty stake account.
+    pool.record_proof(&newcomer, 1, 0).unwrap();
+
+    set_stake_policy(
+        &mut pool,
+        ProofStakePolicy {
+            min_stake: TOKEN,
+            ..ProofStakePolicy::default()
+        },
+    );
+    let check = check_proof(&mut pool, &newcomer, 2, 0);
+    assert!(!check.meets_min_stake && check.reward == 0);
+    assert_error(
+        pool.record_proof(&newcomer, 2, 0),
+        SkillStakeError::InsufficientProofStake,
+    );
+
+    assert!(check_proof(&mut pool, &user, 2, 0).meets_min_stake);
+    pool.record_proof(&user, 2, 0).unwrap();
+    pool.stake(&newcomer, TOKEN).unwrap();
+    pool.record_proof(&newcomer, 2, 0).unwrap();
+}
+
+#[test]
+fn rewards_grow_with_stake_up_to_the_cap() {
+    let (mut pool, small) = setup(0);
+    let large = pool.user(8 * TOKEN);
+    pool.stake(&large, 8 * TOKEN).unwrap();
+    // A quarter of the full boost stake earns half the boost.
+    set_stake_policy(
+        &mut pool,
+        ProofStakePolicy {
+            min_stake: 0,
+            full_boost_stake: 4 * TOKEN,
+            max_boost_bps: 10_000,
+        },
+    );
+
+    assert_eq!(check_proof(&mut pool, &small, 1, 0).reward, 3 * TOKEN / 2);
+    let event: ProofRecorded = pool.record_proof(&small, 1, 0).unwrap().event();
+    assert_eq!(event.reward, 3 * TOKEN / 2);
+    let event: ProofRecorded = pool.record_proof(&large, 1, 0).unwrap().event();
+    assert_eq!(event.reward, 2 * TOKEN);
+
+    // Attested rewards are boosted too.
+    let oracle = pool.admin.insecure_clone();
+    let attested = ProofAttestation {
+        reward: 4 * TOKEN,
+        expiry: pool.svm.now() + 60,
+    };
+    pool.ensure_task(2);
+    let attest = attestation(&pool, &oracle, &small, 2, attested);
+    let record = instructions::record_proof(&small.key(), 2, 0, Some(attested));
+    let event: ProofRecorded = pool
+        .send(&[attest, record], &small.keypair)
+        .unwrap()
+        .event();
+    assert_eq!(event.reward, 6 * TOKEN);
+}
+
+#[test]
+fn pow_challenge_window_is_validated_and_role_gated() {
+    let mut pool = Pool::new();
+    let admin = pool.admin.pubkey();
+    let ix = instructions::set_pow_challenge(&admin, MAX_POW_CHALLENGE_SLOTS + 1);
+    assert_error(
+        pool.send_admin(&[ix]),
+        SkillStakeError::InvalidPowChallengeConfig,
+    );
+
+    let outsider = pool.outsider();
+    let ix = instructions::set_pow_challenge(&outsider.pubkey(), 20);
+    assert_error(pool.send(&[ix], &outsider), SkillStakeError::Unauthorized);
+
+    let manager = pool.role_member(ROLE_POW_MANAGER);
+    let ix = instructions::set_pow_challenge(&manager.pubkey(), 20);
+    let event: PowChallengeUpdated = pool.send(&[ix], &manager).unwrap().event();
+    assert_eq!(
+        (event.window_slots_before, event.window_slots_after),
+        (0, 20)
+    );
+    assert_eq!(pool.state().pow_challenge_slots, 20);
+}
+
+/// First nonce for `task_id` that meets `difficulty` over `user`'s committed slot hash.
+fn mine_challenge(pool: &Pool, user: &User, task_id: u64, difficulty: u8) -> u64 {
+    let slot_hash = pool.stake_account(&user.key()).pow_challenge_hash;
+    (0..)
+        .find(|nonce| {
+            let hash = challenge_pow_hash(&user.key(), &pool.mint, task_id, &slot_hash, *nonce);
+            leading_zero_bits(&hash) >= difficulty as u32
+        })
+        .unwrap()
+}
+
+#[test]
+fn challenged_proofs_need_a_fresh_slot_hash() {
+    let (mut pool, user) = setup(8);
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::set_pow_challenge(&admin, 20)])
+        .unwrap();
+    let precomputed = mine(&user.key(), &pool.mint, 1, 8);
+    assert!(!check_proof(&mut pool, &user, 1, precomputed).is_challenge_fresh);
+    assert_error(
+        pool.record_proof(&user, 1, precomputed),
+        SkillStakeError::PowChallengeMissing,
+    );
+
+    let commit = instructions::commit_pow_challenge(&user.key());
+    let event: PowChallengeCommitted = pool.send(&[commit], &user.keypair).unwrap().event();
+    let slot = pool.svm.slot() - 1;
+    assert_eq!((event.slot_before, event.slot), (0, slot));
+    assert_eq!(event.slot_hash, svm::slot_hash(slot).to_bytes());
+    assert_eq!(pool.stake_account(&user.key()).pow_challenge_slot, slot);
+
+    let nonce = mine_challenge(&pool, &user, 1, 8);
+    let check = check_proof(&mut pool, &user, 1, nonce);
+    assert!(check.is_challenge_fresh && check.meets_difficulty);
+    assert_eq!(check.reward, TOKEN);
+    pool.record_proof(&user, 1, nonce).unwrap();
+
+    // 10 seconds later the commitment is 21 slots old.
+    let nonce = mine_challenge(&pool, &user, 2, 8);
+    pool.svm.warp(10);
+    let check = check_proof(&mut pool, &user, 2, nonce);
+    assert!(!check.is_challenge_fresh && check.reward == 0);
+    assert_error(
+        pool.record_proof(&user, 2, nonce),
+        SkillStakeError::PowChallengeExpired,
+    );
+
+    let commit = instructions::commit_pow_challenge(&user.key());
+    pool.send(&[commit], &user.keypair).unwrap();
+    let nonce = mine_challenge(&pool, &user, 2, 8);
+    pool.record_proof(&user, 2, nonce).unwrap();
+}
+
+#[test]
+fn proofs_are_mined_with_the_configured_hash() {
+    let (mut pool, user) = setup(8);
+    let admin = pool.admin.pubkey();
+    let ix = instructions::set_pow_hash_algorithm(&admin, POW_HASH_BLAKE3 + 1);
+    assert_error(
+        pool.send_admin(&[ix]),
+        SkillStakeError::InvalidPowHashAlgorithm,
+    );
+    let outsider = pool.outsider();
+    let ix = instructions::set_pow_hash_algorithm(&outsider.pubkey(), POW_HASH_SHA256);
+    assert_error(pool.send(&[ix], &outsider), SkillStakeError::Unauthorized);
+
+    let manager = pool.role_member(ROLE_POW_MANAGER);
+    let (key, mint) = (user.key(), pool.mint);
+    let meets = |algorithm, task_id, nonce| {
+        let hash = skill_stake_client::pow_hash(algorithm, &key, &mint, task_id, nonce);
+        leading_zero_bits(&hash) >= 8
+    };
+    let mut before = POW_HASH_KECCAK256;
+    for (task_id, algorithm) in [
+        (1, POW_HASH_SHA256),
+        (2, POW_HASH_BLAKE3),
+        (3, POW_HASH_KECCAK256),
+    ] {
+        let ix = instructions::set_pow_hash_algorithm(&manager.pubkey(), algorithm);
+        let event: PowHashAlgorithmUpdated = pool.send(&[ix], &manager).unwrap().event();
+        assert_eq!(
+            (event.algorithm_before, event.algorithm_after),
+            (before, algorithm)
+        );
+
+        // A nonce mined under the previous hash is worthless now.
+        let stale = (0..)
+            .find(|nonce| meets(before, task_id, *nonce) && !meets(algorithm, task_id, *nonce))
+            .unwrap();
+        assert_error(
+            pool.record_proof(&user, task_id, stale),
+            SkillStakeError::ProofDifficultyNotMet,
+        );
+        before = algorithm;
+
+        let nonce = (0..)
+            .find(|nonce| meets(algorithm, task_id, *nonce))
+            .unwrap();
+        let expected = skill_stake_client::pow_hash(algorithm, &key, &mint, task_id, nonce);
+        assert_eq!(check_proof(&mut pool, &user, task_id, nonce).hash, expected);
+        let event: ProofRecorded = pool.record_proof(&user, task_id, nonce).unwrap().event();
+        assert_eq!(event.hash, expected);
+    }
+}
+
+#[test]
+fn proof_history_grows_then_wraps() {
+    let (mut pool, user) = setup(0);
+    let address = pda::proof_history(&user.key()).0;
+    for task_id in 1..=3 {
+        pool.record_proof(&user, task_id, 0).unwrap();
+        let account = pool.svm.account(&address).unwrap();
+        assert_eq!(
+            account.data.len(),
+            8 + ProofHistory::space(task_id as usize)
+        );
+    }
+
+    let total = MAX_PROOF_HISTORY as u64 + 5;
+    for task_id in 4..=total {
+        pool.record_proof(&user, task_id, 0).unwrap();
+    }
+    let account = pool.svm.account(&address).unwrap();
+    assert_eq!(
+        account.data.len(),
+        8 + ProofHistory::space(MAX_PROOF_HISTORY)
+    );
+
+    // The oldest five were overwritten; reading from `head` restores the order.
+    let history = pool.proof_history(&user.key());
+    assert_eq!(history.head, 5);
+    let tasks: Vec<u64> = history
+        .chronological()
+        .map(|entry| entry.record.task_id)
+        .collect();
+    assert_eq!(tasks, (6..=total).collect::<Vec<_>>());
+
+    let decoded = skill_stake_client::ProofHistory::try_from_account_data(&account.data).unwrap();
+    assert_eq!(
+        decoded.chronological().last().unwrap().record.task_id,
+        total
+    );
+}
+
+#[test]
+fn prefunded_proof_history_address_is_still_created() {
+    let (mut pool, user) = setup(0);
+    let address = pda::proof_history(&user.key()).0;
+    pool.svm.airdrop(&address, 1);
+
+    pool.record_proof(&user, 1, 0).unwrap();
+    assert_eq!(pool.proof_history(&user.key()).entries.len(), 1);
+}
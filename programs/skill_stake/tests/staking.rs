@@ -0,0 +1,363 @@
+//! Stake, unstake, claim, emergency withdraw, reward accrual and the read-only views.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use skill_stake::{
+    Claimed, EmergencyWithdrawn, PoolInfo, RewardsPreview, SkillStakeError, Staked, UnstakePreview,
+    Unstaked, BPS_DENOMINATOR, SECONDS_PER_YEAR,
+};
+use skill_stake_client::{instructions, pda, FaucetProof};
+use solana_sdk::instruction::AccountMeta;
+use solana_sdk::signature::Signer;
+
+const DAY: i64 = 86_400;
+
+/// Reference accrual: `amount * apr * elapsed / (BPS * year)`, rounded down.
+fn expected_rewards(amount: u64, apr_bps: u64, elapsed: i64) -> u64 {
+    (amount as u128 * apr_bps as u128 * elapsed as u128
+        / (BPS_DENOMINATOR as u128 * SECONDS_PER_YEAR as u128)) as u64
+}
+
+fn preview_rewards(pool: &mut Pool, user: &User) -> RewardsPreview {
+    let admin = pool.admin.insecure_clone();
+    pool.svm
+        .simulate(&[instructions::preview_rewards(&user.key())], &[&admin])
+        .unwrap()
+        .return_value()
+}
+
+#[test]
+fn stake_moves_tokens_and_records_position() {
+    let mut pool = Pool::new();
+    let user = pool.user(100 * TOKEN);
+
+    let meta = pool.stake(&user, 40 * TOKEN).unwrap();
+    let event: Staked = meta.event();
+    assert_eq!(
+        (event.amount_staked_before, event.amount_staked_after),
+        (0, 40 * TOKEN)
+    );
+    assert_eq!(
+        (event.total_staked_before, event.total_staked_after),
+        (0, 40 * TOKEN)
+    );
+
+    assert_eq!(pool.svm.token_balance(&user.token), 60 * TOKEN);
+    assert_eq!(pool.vault_balance(), 40 * TOKEN);
+    assert_eq!(pool.state().total_staked, 40 * TOKEN);
+
+    let stake_account = pool.stake_account(&user.key());
+    assert_eq!(stake_account.owner, user.key());
+    assert_eq!(stake_account.amount_staked, 40 * TOKEN);
+    assert_eq!(stake_account.last_accrued_ts, pool.svm.now());
+    assert_eq!(stake_account.version, skill_stake::StakeAccount::VERSION);
+}
+
+#[test]
+fn one_year_at_ten_percent() {
+    let mut pool = Pool::new();
+    let user = pool.user(1_000 * TOKEN);
+    pool.stake(&user, 1_000 * TOKEN).unwrap();
+    pool.svm.warp(365 * DAY);
+    assert_eq!(
+        preview_rewards(&mut pool, &user).pending_rewards,
+        100 * TOKEN
+    );
+}
+
+#[test]
+fn rewards_accrue_across_warped_clock() {
+    let mut pool = Pool::new();
+    let apr = pool.state().apr_bps;
+    let user = pool.user(1_000 * TOKEN);
+
+    // Topping up settles the first period at the old balance before adding.
+    pool.stake(&user, 500 * TOKEN).unwrap();
+    pool.svm.warp(10 * DAY);
+    let meta = pool.stake(&user, 500 * TOKEN).unwrap();
+    let first = expected_rewards(500 * TOKEN, apr, 10 * DAY);
+    assert_eq!(meta.event::<Staked>().pending_rewards, first);
+
+    pool.svm.warp(20 * DAY + 123);
+    let second = expected_rewards(1_000 * TOKEN, apr, 20 * DAY + 123);
+    let preview = preview_rewards(&mut pool, &user);
+    assert_eq!(preview.accrued_since_last, second);
+    assert_eq!(preview.pending_rewards, first + second);
+
+    let meta = pool.claim(&user).unwrap();
+    assert_eq!(meta.event::<Claimed>().amount, first + second);
+    assert_eq!(pool.svm.token_balance(&user.token), first + second);
+    assert_eq!(pool.stake_account(&user.key()).pending_rewards, 0);
+    assert_eq!(
+        pool.svm.mint_supply(&pool.mint),
+        1_000 * TOKEN + first + second
+    );
+}
+
+#[test]
+fn apr_change_applies_from_next_settlement() {
+    let mut pool = Pool::new();
+    let user = pool.user(1_000 * TOKEN);
+    pool.stake(&user, 1_000 * TOKEN).unwrap();
+
+    pool.svm.warp(30 * DAY);
+    // Unstaking settles at the old APR.
+    pool.unstake(&user, TOKEN).unwrap();
+    let settled = expected_rewards(1_000 * TOKEN, 1_000, 30 * DAY);
+    assert_eq!(pool.stake_account(&user.key()).pending_rewards, settled);
+
+    let admin = pool.admin.insecure_clone();
+    pool.send(&[instructions::set_apr(&admin.pubkey(), 4_000)], &admin)
+        .unwrap();
+    pool.svm.warp(30 * DAY);
+    let preview = preview_rewards(&mut pool, &user);
+    assert_eq!(
+        preview.pending_rewards,
+        settled + expected_rewards(999 * TOKEN, 4_000, 30 * DAY)
+    );
+}
+
+#[test]
+fn clock_moving_backwards_accrues_nothing() {
+    let mut pool = Pool::new();
+    let user = pool.user(100 * TOKEN);
+    pool.stake(&user, 100 * TOKEN).unwrap();
+
+    pool.svm.warp(-DAY);
+    let preview = preview_rewards(&mut pool, &user);
+    assert_eq!(preview.accrued_since_last, 0);
+    assert_error(pool.claim(&user), SkillStakeError::NothingToClaim);
+}
+
+#[test]
+fn unstake_returns_tokens_and_keeps_rewards() {
+    let mut pool = Pool::new();
+    let user = pool.user(100 * TOKEN);
+    pool.stake(&user, 100 * TOKEN).unwrap();
+    pool.svm.warp(DAY);
+
+    let meta = pool.unstake(&user, 30 * TOKEN).unwrap();
+    let event: Unstaked = meta.event();
+    assert_eq!(event.amount_staked_after, 70 * TOKEN);
+    assert_eq!(event.total_staked_after, 70 * TOKEN);
+    assert_eq!(
+        event.pending_rewards,
+        expected_rewards(100 * TOKEN, 1_000, DAY)
+    );
+
+    assert_eq!(pool.svm.token_balance(&user.token), 30 * TOKEN);
+    assert_eq!(pool.vault_balance(), 70 * TOKEN);
+    assert_eq!(pool.state().total_staked, 70 * TOKEN);
+
+    pool.unstake(&user, 70 * TOKEN).unwrap();
+    assert_eq!(pool.vault_balance(), 0);
+    pool.claim(&user).unwrap();
+}
+
+#[test]
+fn zero_amounts_are_rejected() {
+    let mut pool = Pool::new();
+    let user = pool.user(100 * TOKEN);
+    assert_error(pool.stake(&user, 0), SkillStakeError::InvalidAmount);
+    pool.stake(&user, TOKEN).unwrap();
+    assert_error(pool.unstake(&user, 0), SkillStakeError::InvalidAmount);
+    assert_error(pool.faucet(&user, 0), SkillStakeError::InvalidAmount);
+}
+
+#[test]
+fn unstake_more_than_staked_fails() {
+    let mut pool = Pool::new();
+    let user = pool.user(100 * TOKEN);
+    pool.stake(&user, 10 * TOKEN).unwrap();
+    assert_error(
+        pool.unstake(&user, 10 * TOKEN + 1),
+        SkillStakeError::InsufficientStake,
+    );
+}
+
+#[test]
+fn claim_without_rewards_fails() {
+    let mut pool = Pool::new();
+    let user = pool.user(100 * TOKEN);
+    pool.stake(&user, 10 * TOKEN).unwrap();
+    assert_error(pool.claim(&user), SkillStakeError::NothingToClaim);
+}
+
+#[test]
+fn emergency_withdraw_forfeits_rewards() {
+    let mut pool = Pool::new();
+    let user = pool.user(100 * TOKEN);
+    pool.stake(&user, 100 * TOKEN).unwrap();
+    pool.svm.warp(90 * DAY);
+
+    let meta = pool.emergency_withdraw(&user).unwrap();
+    let event: EmergencyWithdrawn = meta.event();
+    assert_eq!(event.amount, 100 * TOKEN);
+    // Rewards since the last settlement are not accrued, only previously settled ones forfeit.
+    assert_eq!(event.forfeited_rewards, 0);
+    assert_eq!(pool.svm.token_balance(&user.token), 100 * TOKEN);
+
+    let stake_account = pool.stake_account(&user.key());
+    assert_eq!(
+        (stake_account.amount_staked, stake_account.pending_rewards),
+        (0, 0)
+    );
+    assert_eq!(pool.state().total_staked, 0);
+
+    assert_error(
+        pool.emergency_withdraw(&user),
+        SkillStakeError::InsufficientStake,
+    );
+    assert_error(pool.claim(&user), SkillStakeError::NothingToClaim);
+}
+
+#[test]
+fn users_cannot_touch_each_others_positions() {
+    let mut pool = Pool::new();
+    let alice = pool.user(100 * TOKEN);
+    let mallory = pool.user(0);
+    pool.stake(&alice, 100 * TOKEN).unwrap();
+    pool.svm.warp(DAY);
+
+    // Mallory signs but points at Alice's stake account.
+    let mut unstake = instructions::unstake(
+        &mallory.key(),
+        &pool.mint,
+        &mallory.token,
+        &pool.token_program,
+        TOKEN,
+    );
+    unstake.accounts[5] = AccountMeta::new(pda::stake_account(&alice.key()).0, false);
+    assert_anchor_error(
+        pool.send(&[unstake], &mallory.keypair),
+        ErrorCode::ConstraintSeeds,
+    );
+
+    // Alice's rewards cannot be minted into Mallory's token account.
+    let mut claim =
+        instructions::claim(&alice.key(), &pool.mint, &alice.token, &pool.token_program);
+    claim.accounts[4] = AccountMeta::new(mallory.token, false);
+    assert_anchor_error(
+        pool.send(&[claim], &alice.keypair),
+        ErrorCode::ConstraintTokenOwner,
+    );
+
+    assert_eq!(pool.stake_account(&alice.key()).amount_staked, 100 * TOKEN);
+}
+
+#[test]
+fn foreign_mint_is_rejected() {
+    let mut pool = Pool::new();
+    let user = pool.user(100 * TOKEN);
+    pool.stake(&user, 100 * TOKEN).unwrap();
+    pool.svm.warp(DAY);
+
+    let other_mint = pool
+        .svm
+        .create_mint(&pool.token_program, &pda::mint_auth().0, DECIMALS);
+    let other_token =
+        pool.svm
+            .create_token_account(&pool.token_program, &other_mint, &user.key(), 0);
+
+    let claim = instructions::claim(&user.key(), &other_mint, &other_token, &pool.token_program);
+    assert_error(
+        pool.send(&[claim], &user.keypair),
+        SkillStakeError::MintMismatch,
+    );
+    let faucet = instructions::faucet(
+        &user.key(),
+        &other_mint,
+        &other_token,
+        &pool.token_program,
+        1,
+        FaucetProof::None,
+    );
+    assert_error(
+        pool.send(&[faucet], &user.keypair),
+        SkillStakeError::MintMismatch,
+    );
+}
+
+#[test]
+fn accrual_overflow_is_reported() {
+    let mut pool = Pool::with_config(Config {
+        apr_bps: 5 * BPS_DENOMINATOR,
+        ..Config::default()
+    });
+    let whale = pool.user(u64::MAX / 2);
+    pool.stake(&whale, u64::MAX / 2).unwrap();
+
+    // amount * apr * elapsed no longer fits in u128.
+    pool.svm.warp(1_000_000_000_000_000);
+    assert_error(pool.claim(&whale), SkillStakeError::MathOverflow);
+}
+
+#[test]
+fn preview_unstake_matches_unstake() {
+    let mut pool = Pool::new();
+    let user = pool.user(100 * TOKEN);
+    pool.stake(&user, 100 * TOKEN).unwrap();
+    pool.svm.warp(7 * DAY);
+
+    let admin = pool.admin.insecure_clone();
+    let preview: UnstakePreview = pool
+        .svm
+        .simulate(
+            &[instructions::preview_unstake(&user.key(), 25 * TOKEN)],
+            &[&admin],
+        )
+        .unwrap()
+        .return_value();
+    let event: Unstaked = pool.unstake(&user, 25 * TOKEN).unwrap().event();
+    assert_eq!(preview.remaining_staked, event.amount_staked_after);
+    assert_eq!(preview.total_staked_after, event.total_staked_after);
+    assert_eq!(preview.pending_rewards, event.pending_rewards);
+
+    let result = pool
+        .svm
+        .simulate(&[instructions::preview_unstake(&user.key(), 0)], &[&admin]);
+    assert_error(result, SkillStakeError::InvalidAmount);
+    let result = pool.svm.simulate(
+        &[instructions::preview_unstake(&user.key(), 76 * TOKEN)],
+        &[&admin],
+    );
+    assert_error(result, SkillStakeError::InsufficientStake);
+}
+
+#[test]
+fn pool_info_reports_configuration_and_balances() {
+    let mut pool = Pool::new();
+    let user = pool.user(100 * TOKEN);
+    pool.stake(&user, 60 * TOKEN).unwrap();
+
+    let admin = pool.admin.insecure_clone();
+    let info: PoolInfo = pool
+        .svm
+        .simulate(&[instructions::get_pool_info()], &[&admin])
+        .unwrap()
+        .return_value();
+    assert_eq!(info.admin, admin.pubkey());
+    assert_eq!(info.mint, pool.mint);
+    assert_eq!(info.vault_balance, 60 * TOKEN);
+    assert_eq!(info.total_staked, 60 * TOKEN);
+    assert_eq!(info.apr_bps, 1_000);
+    assert_eq!(info.version, skill_stake::GlobalState::VERSION);
+}
+
+#[test]
+fn token_2022_mints_are_supported() {
+    let mut pool = Pool::with_config(Config {
+        token_program: spl_token_2022::ID,
+        ..Config::default()
+    });
+    let user = pool.user(100 * TOKEN);
+
+    pool.stake(&user, 100 * TOKEN).unwrap();
+    pool.svm.warp(365 * DAY);
+    pool.unstake(&user, 100 * TOKEN).unwrap();
+    pool.claim(&user).unwrap();
+    pool.faucet(&user, TOKEN).unwrap();
+    assert_eq!(pool.svm.token_balance(&user.token), 111 * TOKEN);
+}
@@ -0,0 +1,195 @@
+//! Oracle-registered tasks: who creates them, and how they bound `record_proof`.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use skill_stake::{ProofCheck, ProofRecorded, SkillStakeError, TaskCreated, ROLE_POW_MANAGER};
+use skill_stake_client::{instructions, TaskParams};
+use solana_sdk::signature::{Keypair, Signer};
+
+fn setup() -> (Pool, User) {
+    let mut pool = Pool::with_config(Config {
+        apr_bps: 0,
+        pow_difficulty: 0,
+        ..Config::default()
+    });
+    let user = pool.user(TOKEN);
+    pool.stake(&user, TOKEN).unwrap();
+    (pool, user)
+}
+
+fn check_proof(pool: &mut Pool, user: &User, task_id: u64, nonce: u64) -> ProofCheck {
+    let admin = pool.admin.insecure_clone();
+    pool.svm
+        .simulate(
+            &[instructions::check_proof(&user.key(), task_id, nonce, true)],
+            &[&admin],
+        )
+        .unwrap()
+        .return_value()
+}
+
+#[test]
+fn tasks_are_created_by_the_oracle_or_a_pow_manager() {
+    let oracle = Keypair::new();
+    let mut pool = Pool::with_config(Config {
+        oracle_authority: Some(oracle.pubkey()),
+        ..Config::default()
+    });
+    pool.svm.airdrop(&oracle.pubkey(), 10 * SOL);
+    let params = pool.default_task();
+
+    let outsider = pool.outsider();
+    let ix = instructions::create_task(&outsider.pubkey(), 1, params);
+    assert_error(pool.send(&[ix], &outsider), SkillStakeError::Unauthorized);
+
+    let ix = instructions::create_task(&oracle.pubkey(), 1, params);
+    let event: TaskCreated = pool.send(&[ix], &oracle).unwrap().event();
+    assert_eq!((event.authority, event.task_id), (oracle.pubkey(), 1));
+    let task = pool.task(1);
+    assert_eq!(
+        (task.reward, task.difficulty),
+        (params.reward, params.difficulty)
+    );
+    assert_eq!(task.solvers, 0);
+
+    let manager = pool.role_member(ROLE_POW_MANAGER);
+    let ix = instructions::create_task(&manager.pubkey(), 2, params);
+    pool.send(&[ix], &manager).unwrap();
+
+    // Each task id is registered once.
+    let ix = instructions::create_task(&oracle.pubkey(), 1, params);
+    assert!(pool.send(&[ix], &oracle).is_err());
+}
+
+#[test]
+fn task_config_is_validated() {
+    let mut pool = Pool::new();
+    let params = pool.default_task();
+    for invalid in [
+        TaskParams {
+            difficulty: skill_stake::MAX_POW_DIFFICULTY + 1,
+            ..params
+        },
+        TaskParams {
+            closes_at: params.opens_at,
+            ..params
+        },
+    ] {
+        assert_error(
+            pool.create_task(1, invalid),
+            SkillStakeError::InvalidTaskConfig,
+        );
+    }
+}
+
+#[test]
+fn proofs_use_the_task_difficulty_and_reward() {
+    let (mut pool, user) = setup();
+    let params = TaskParams {
+        reward: 5 * TOKEN,
+        difficulty: 8,
+        ..pool.default_task()
+    };
+    pool.create_task(1, params).unwrap();
+
+    // The pool's difficulty of 0 does not apply to a registered task.
+    let weak = mine_exact(&user.key(), &pool.mint, 1, 7);
+    assert_error(
+        pool.record_proof(&user, 1, weak),
+        SkillStakeError::ProofDifficultyNotMet,
+    );
+
+    let nonce = mine(&user.key(), &pool.mint, 1, 8);
+    let check = check_proof(&mut pool, &user, 1, nonce);
+    assert_eq!((check.difficulty, check.reward), (8, 5 * TOKEN));
+    assert!(check.is_open);
+
+    let event: ProofRecorded = pool.record_proof(&user, 1, nonce).unwrap().event();
+    assert_eq!(event.reward, 5 * TOKEN);
+    assert_eq!(pool.task(1).solvers, 1);
+}
+
+#[test]
+fn unregistered_tasks_are_rejected() {
+    let (mut pool, user) = setup();
+    let result = pool.send(
+        &[instructions::record_proof(&user.key(), 1, 0, None)],
+        &user.keypair,
+    );
+    assert_anchor_error(result, ErrorCode::AccountNotInitialized);
+}
+
+#[test]
+fn proofs_are_accepted_only_while_the_task_is_open() {
+    let (mut pool, user) = setup();
+    let now = pool.svm.now();
+    let params = TaskParams {
+        opens_at: now + 60,
+        closes_at: now + 120,
+        ..pool.default_task()
+    };
+    pool.create_task(1, params).unwrap();
+    pool.create_task(2, params).unwrap();
+
+    assert!(!check_proof(&mut pool, &user, 1, 0).is_open);
+    assert_error(pool.record_proof(&user, 1, 0), SkillStakeError::TaskNotOpen);
+
+    pool.svm.warp(60);
+    pool.record_proof(&user, 1, 0).unwrap();
+
+    // `closes_at` itself is already too late.
+    pool.svm.warp(60);
+    let check = check_proof(&mut pool, &user, 2, 0);
+    assert!(!check.is_open && check.reward == 0);
+    assert_error(pool.record_proof(&user, 2, 0), SkillStakeError::TaskClosed);
+}
+
+#[test]
+fn solver_limit_caps_the_task() {
+    let (mut pool, alice) = setup();
+    let bob = pool.user(TOKEN);
+    pool.stake(&bob, TOKEN).unwrap();
+    let carol = pool.user(TOKEN);
+    pool.stake(&carol, TOKEN).unwrap();
+
+    let params = TaskParams {
+        max_solvers: 2,
+        ..pool.default_task()
+    };
+    pool.create_task(1, params).unwrap();
+    pool.record_proof(&alice, 1, 0).unwrap();
+    pool.record_proof(&bob, 1, 0).unwrap();
+    assert_eq!(pool.task(1).solvers, 2);
+
+    assert!(!check_proof(&mut pool, &carol, 1, 0).is_open);
+    assert_error(pool.record_proof(&carol, 1, 0), SkillStakeError::TaskFull);
+}
+
+#[test]
+fn tasks_without_their_own_values_follow_pow_config_updates() {
+    let (mut pool, user) = setup();
+    pool.ensure_task(1);
+    let pinned = TaskParams {
+        reward: 7 * TOKEN,
+        difficulty: 1,
+        ..pool.default_task()
+    };
+    pool.create_task(2, pinned).unwrap();
+
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::set_pow_config(&admin, 6, 3 * TOKEN, 1)])
+        .unwrap();
+
+    let check = check_proof(&mut pool, &user, 1, 0);
+    assert_eq!(check.difficulty, 6);
+    let nonce = mine(&user.key(), &pool.mint, 1, 6);
+    let event: ProofRecorded = pool.record_proof(&user, 1, nonce).unwrap().event();
+    assert_eq!(event.reward, 3 * TOKEN);
+
+    assert_eq!(check_proof(&mut pool, &user, 2, 0).difficulty, 1);
+    let nonce = mine(&user.key(), &pool.mint, 2, 1);
+    let event: ProofRecorded = pool.record_proof(&user, 2, nonce).unwrap().event();
+    assert_eq!(event.reward, 7 * TOKEN);
+}
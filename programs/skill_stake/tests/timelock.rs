@@ -0,0 +1,416 @@
+//! Timelocked parameter changes: queue, execute and cancel.
+
+mod common;
+
+use anchor_lang::error::ErrorCode;
+use common::*;
+use skill_stake::{
+    ChangeCancelled, ChangeQueued, ParamChange as ProgramParamChange, ParamChanged,
+    SkillStakeError, MAX_POW_CHALLENGE_SLOTS, MAX_TIMELOCK_DELAY, POW_HASH_BLAKE3,
+    POW_HASH_KECCAK256, POW_HASH_SHA256, ROLE_APR_MANAGER, ROLE_POW_MANAGER, TIMELOCK_GRACE_PERIOD,
+};
+use skill_stake_client::{
+    instructions, pda, ParamChange, PowRetarget, ProofStakePolicy, TaskParams,
+};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+
+const DELAY: i64 = 2 * 86_400;
+
+/// A pool whose admin has turned on a `DELAY` timelock.
+fn timelocked_pool() -> Pool {
+    let mut pool = Pool::new();
+    let admin = pool.admin.pubkey();
+    let now = pool.svm.now();
+    pool.send_admin(&[
+        instructions::queue_change(&admin, 0, ParamChange::TimelockDelay { delay: DELAY }, now),
+        instructions::execute_change(&admin, &admin, 0),
+    ])
+    .unwrap();
+    assert_eq!(pool.state().timelock_delay, DELAY);
+    pool
+}
+
+fn queue(
+    pool: &mut Pool,
+    proposer: &Keypair,
+    change: ParamChange,
+    eta: i64,
+) -> Result<TxMeta, TxError> {
+    let id = pool.state().change_count;
+    pool.send(
+        &[instructions::queue_change(
+            &proposer.pubkey(),
+            id,
+            change,
+            eta,
+        )],
+        proposer,
+    )
+}
+
+#[test]
+fn direct_updates_are_blocked_while_timelocked() {
+    let mut pool = timelocked_pool();
+    let admin = pool.admin.pubkey();
+    let result = pool.send_admin(&[instructions::set_apr(&admin, 2_000)]);
+    assert_error(result, SkillStakeError::TimelockActive);
+    let result = pool.send_admin(&[instructions::update_faucet_cap(&admin, 1)]);
+    assert_error(result, SkillStakeError::TimelockActive);
+    let result = pool.send_admin(&[instructions::set_oracle_authority(
+        &admin,
+        &Pubkey::new_unique(),
+    )]);
+    assert_error(result, SkillStakeError::TimelockActive);
+}
+
+#[test]
+fn pow_managers_cannot_move_rewards_or_difficulty_while_timelocked() {
+    let mut pool = timelocked_pool();
+    // The admin holds every role, so granting it to themselves must not help either.
+    let manager = pool.role_member(ROLE_POW_MANAGER);
+    let key = manager.pubkey();
+
+    let result = pool.send(&[instructions::set_pow_config(&key, 1, 1, 0)], &manager);
+    assert_error(result, SkillStakeError::TimelockActive);
+    let retarget = PowRetarget {
+        window: 3_600,
+        target_proofs: 2,
+        max_step: 2,
+        min_difficulty: 1,
+        max_difficulty: 8,
+    };
+    let result = pool.send(&[instructions::set_pow_retarget(&key, retarget)], &manager);
+    assert_error(result, SkillStakeError::TimelockActive);
+    let policy = ProofStakePolicy {
+        min_stake: 0,
+        full_boost_stake: TOKEN,
+        max_boost_bps: 5_000,
+    };
+    let result = pool.send(
+        &[instructions::set_proof_stake_policy(&key, policy)],
+        &manager,
+    );
+    assert_error(result, SkillStakeError::TimelockActive);
+
+    let pinned = TaskParams {
+        reward: 1_000 * TOKEN,
+        ..pool.default_task()
+    };
+    let result = pool.send(&[instructions::create_task(&key, 1, pinned)], &manager);
+    assert_error(result, SkillStakeError::TimelockActive);
+
+    // Tasks that follow the pool stay open to managers, and the oracle keeps its updates.
+    let follows = pool.default_task();
+    pool.send(&[instructions::create_task(&key, 1, follows)], &manager)
+        .unwrap();
+    let admin = pool.admin.pubkey();
+    pool.send_admin(&[instructions::set_pow_config(&admin, 1, 1, 1)])
+        .unwrap();
+}
+
+#[test]
+fn pow_challenge_window_goes_through_the_timelock() {
+    let mut pool = timelocked_pool();
+    let manager = pool.role_member(ROLE_POW_MANAGER);
+    let key = manager.pubkey();
+
+    let result = pool.send(&[instructions::set_pow_challenge(&key, 20)], &manager);
+    assert_error(result, SkillStakeError::TimelockActive);
+
+    let eta = pool.svm.now() + DELAY;
+    let too_wide = ParamChange::PowChallenge {
+        window_slots: MAX_POW_CHALLENGE_SLOTS + 1,
+    };
+    assert_error(
+        queue(&mut pool, &manager, too_wide, eta),
+        SkillStakeError::InvalidPowChallengeConfig,
+    );
+    let outsider = pool.outsider();
+    let change = ParamChange::PowChallenge { window_slots: 20 };
+    assert_error(
+        queue(&mut pool, &outsider, change.clone(), eta),
+        SkillStakeError::Unauthorized,
+    );
+    queue(&mut pool, &manager, change, eta).unwrap();
+
+    pool.svm.warp(DELAY);
+    let meta = pool
+        .send(&[instructions::execute_change(&key, &key, 1)], &manager)
+        .unwrap();
+    let changed: ParamChanged = meta.event();
+    assert_eq!(
+        (changed.before, changed.after),
+        (
+            ProgramParamChange::PowChallenge { window_slots: 0 },
+            ProgramParamChange::PowChallenge { window_slots: 20 },
+        )
+    );
+    assert_eq!(pool.state().pow_challenge_slots, 20);
+}
+
+#[test]
+fn pow_hash_algorithm_goes_through_the_timelock() {
+    let mut pool = timelocked_pool();
+    let manager = pool.role_member(ROLE_POW_MANAGER);
+    let key = manager.pubkey();
+
+    let result = pool.send(
+        &[instructions::set_pow_hash_algorithm(&key, POW_HASH_SHA256)],
+        &manager,
+    );
+    assert_error(result, SkillStakeError::TimelockActive);
+
+    let eta = pool.svm.now() + DELAY;
+    let unknown = ParamChange::PowHashAlgorithm {
+        algorithm: POW_HASH_BLAKE3 + 1,
+    };
+    assert_error(
+        queue(&mut pool, &manager, unknown, eta),
+        SkillStakeError::InvalidPowHashAlgorithm,
+    );
+    let change = ParamChange::PowHashAlgorithm {
+        algorithm: POW_HASH_SHA256,
+    };
+    queue(&mut pool, &manager, change, eta).unwrap();
+
+    pool.svm.warp(DELAY);
+    let meta = pool
+        .send(&[instructions::execute_change(&key, &key, 1)], &manager)
+        .unwrap();
+    let changed: ParamChanged = meta.event();
+    assert_eq!(
+        (changed.before, changed.after),
+        (
+            ProgramParamChange::PowHashAlgorithm {
+                algorithm: POW_HASH_KECCAK256
+            },
+            ProgramParamChange::PowHashAlgorithm {
+                algorithm: POW_HASH_SHA256
+            },
+        )
+    );
+    assert_eq!(pool.state().pow_hash_algorithm, POW_HASH_SHA256);
+}
+
+#[test]
+fn queued_change_executes_after_delay() {
+    let mut pool = timelocked_pool();
+    let manager = pool.role_member(ROLE_APR_MANAGER);
+    let eta = pool.svm.now() + DELAY;
+
+    let meta = queue(
+        &mut pool,
+        &manager,
+        ParamChange::Apr { apr_bps: 2_000 },
+        eta,
+    )
+    .unwrap();
+    let queued: ChangeQueued = meta.event();
+    assert_eq!((queued.id, queued.eta), (1, eta));
+    assert_eq!(pool.state().change_count, 2);
+
+    let executor = pool.outsider();
+    let execute = instructions::execute_change(&executor.pubkey(), &manager.pubkey(), 1);
+    let result = pool.send(std::slice::from_ref(&execute), &executor);
+    assert_error(result, SkillStakeError::TimelockNotElapsed);
+
+    pool.svm.warp(DELAY);
+    let lamports_before = pool.svm.lamports(&manager.pubkey());
+    let meta = pool.send(&[execute], &executor).unwrap();
+    let changed: ParamChanged = meta.event();
+    assert_eq!(changed.pending_change_id, Some(1));
+    assert_eq!(changed.after, ProgramParamChange::Apr { apr_bps: 2_000 });
+    assert_eq!(pool.state().apr_bps, 2_000);
+
+    // The pending account is closed and its rent returned to the proposer.
+    assert!(pool.svm.account(&pda::pending_change(1).0).is_none());
+    assert!(pool.svm.lamports(&manager.pubkey()) > lamports_before);
+}
+
+#[test]
+fn eta_must_respect_delay() {
+    let mut pool = timelocked_pool();
+    let admin = pool.admin.insecure_clone();
+    let eta = pool.svm.now() + DELAY - 1;
+    let result = queue(&mut pool, &admin, ParamChange::Apr { apr_bps: 2_000 }, eta);
+    assert_error(result, SkillStakeError::TimelockTooShort);
+}
+
+#[test]
+fn queued_values_are_validated() {
+    let mut pool = timelocked_pool();
+    let admin = pool.admin.insecure_clone();
+    let eta = pool.svm.now() + DELAY;
+
+    let result = queue(&mut pool, &admin, ParamChange::Apr { apr_bps: 50_001 }, eta);
+    assert_error(result, SkillStakeError::AprTooHigh);
+    let change = ParamChange::OracleAuthority {
+        new_authority: Pubkey::default(),
+    };
+    assert_error(
+        queue(&mut pool, &admin, change, eta),
+        SkillStakeError::InvalidOracleAuthority,
+    );
+    let change = ParamChange::TimelockDelay {
+        delay: MAX_TIMELOCK_DELAY + 1,
+    };
+    assert_error(
+        queue(&mut pool, &admin, change, eta),
+        SkillStakeError::InvalidTimelockDelay,
+    );
+    let change = ParamChange::TimelockDelay { delay: -1 };
+    assert_error(
+        queue(&mut pool, &admin, change, eta),
+        SkillStakeError::InvalidTimelockDelay,
+    );
+}
+
+#[test]
+fn queueing_requires_the_matching_role() {
+    let mut pool = timelocked_pool();
+    let eta = pool.svm.now() + DELAY;
+
+    let outsider = pool.outsider();
+    let result = queue(&mut pool, &outsider, ParamChange::Apr { apr_bps: 1 }, eta);
+    assert_error(result, SkillStakeError::Unauthorized);
+
+    // Oracle and timelock changes are admin-only even for role holders.
+    let manager = pool.role_member(ROLE_APR_MANAGER);
+    let change = ParamChange::OracleAuthority {
+        new_authority: manager.pubkey(),
+    };
+    assert_error(
+        queue(&mut pool, &manager, change, eta),
+        SkillStakeError::Unauthorized,
+    );
+    let change = ParamChange::TimelockDelay { delay: 0 };
+    assert_error(
+        queue(&mut pool, &manager, change, eta),
+        SkillStakeError::Unauthorized,
+    );
+}
+
+#[test]
+fn cancel_before_eta_only() {
+    let mut pool = timelocked_pool();
+    let admin = pool.admin.insecure_clone();
+    let eta = pool.svm.now() + DELAY;
+    queue(
+        &mut pool,
+        &admin,
+        ParamChange::FaucetCap { faucet_cap: 1 },
+        eta,
+    )
+    .unwrap();
+    queue(
+        &mut pool,
+        &admin,
+        ParamChange::FaucetCap { faucet_cap: 2 },
+        eta,
+    )
+    .unwrap();
+
+    let outsider = pool.outsider();
+    let cancel = instructions::cancel_change(&outsider.pubkey(), &admin.pubkey(), 1);
+    assert_anchor_error(pool.send(&[cancel], &outsider), ErrorCode::ConstraintHasOne);
+
+    let meta = pool
+        .send(
+            &[instructions::cancel_change(
+                &admin.pubkey(),
+                &admin.pubkey(),
+                1,
+            )],
+            &admin,
+        )
+        .unwrap();
+    let cancelled: ChangeCancelled = meta.event();
+    assert_eq!(cancelled.id, 1);
+    assert!(pool.svm.account(&pda::pending_change(1).0).is_none());
+
+    pool.svm.warp(DELAY);
+    let cancel = instructions::cancel_change(&admin.pubkey(), &admin.pubkey(), 2);
+    assert_error(
+        pool.send(&[cancel], &admin),
+        SkillStakeError::TimelockElapsed,
+    );
+
+    pool.send(
+        &[instructions::execute_change(
+            &admin.pubkey(),
+            &admin.pubkey(),
+            2,
+        )],
+        &admin,
+    )
+    .unwrap();
+    assert_eq!(pool.state().faucet_cap, 2);
+}
+
+#[test]
+fn role_holders_cannot_cancel_queued_changes() {
+    let mut pool = timelocked_pool();
+    let manager = pool.role_member(ROLE_APR_MANAGER);
+    let eta = pool.svm.now() + DELAY;
+    queue(
+        &mut pool,
+        &manager,
+        ParamChange::Apr { apr_bps: 2_000 },
+        eta,
+    )
+    .unwrap();
+    let admin = pool.admin.insecure_clone();
+    queue(&mut pool, &admin, ParamChange::Apr { apr_bps: 3_000 }, eta).unwrap();
+
+    let cancel = instructions::cancel_change(&manager.pubkey(), &manager.pubkey(), 1);
+    assert_anchor_error(pool.send(&[cancel], &manager), ErrorCode::ConstraintHasOne);
+    let cancel = instructions::cancel_change(&manager.pubkey(), &admin.pubkey(), 2);
+    assert_anchor_error(pool.send(&[cancel], &manager), ErrorCode::ConstraintHasOne);
+
+    // The admin cancels either, refunding whoever queued it.
+    let cancel = instructions::cancel_change(&admin.pubkey(), &manager.pubkey(), 1);
+    pool.send_admin(&[cancel]).unwrap();
+    assert!(pool.svm.account(&pda::pending_change(1).0).is_none());
+}
+
+#[test]
+fn queued_change_expires_after_the_grace_period() {
+    let mut pool = timelocked_pool();
+    let admin = pool.admin.insecure_clone();
+    let eta = pool.svm.now() + DELAY;
+    queue(&mut pool, &admin, ParamChange::Apr { apr_bps: 2_000 }, eta).unwrap();
+    let execute = instructions::execute_change(&admin.pubkey(), &admin.pubkey(), 1);
+
+    pool.svm.warp(DELAY + TIMELOCK_GRACE_PERIOD + 1);
+    assert_error(
+        pool.send(std::slice::from_ref(&execute), &admin),
+        SkillStakeError::ChangeExpired,
+    );
+    assert_eq!(pool.state().apr_bps, Config::default().apr_bps);
+
+    // Expired changes can be cancelled to reclaim their rent.
+    let cancel = instructions::cancel_change(&admin.pubkey(), &admin.pubkey(), 1);
+    pool.send(&[cancel], &admin).unwrap();
+    assert!(pool.svm.account(&pda::pending_change(1).0).is_none());
+}
+
+#[test]
+fn queued_change_executes_until_the_grace_period_ends() {
+    let mut pool = timelocked_pool();
+    let admin = pool.admin.insecure_clone();
+    let eta = pool.svm.now() + DELAY;
+    queue(&mut pool, &admin, ParamChange::Apr { apr_bps: 2_000 }, eta).unwrap();
+
+    pool.svm.warp(DELAY + TIMELOCK_GRACE_PERIOD);
+    pool.send(
+        &[instructions::execute_change(
+            &admin.pubkey(),
+            &admin.pubkey(),
+            1,
+        )],
+        &admin,
+    )
+    .unwrap();
+    assert_eq!(pool.state().apr_bps, 2_000);
+}
@@ -0,0 +1,66 @@
+//! Known-answer vectors for the proof-of-work hashes. Off-chain miners (the Rust client and
+//! `app/src/hooks/useProofOfWork.ts`) must reproduce these bytes.
+
+use skill_stake::{POW_HASH_BLAKE3, POW_HASH_KECCAK256, POW_HASH_SHA256};
+use solana_sdk::pubkey::Pubkey;
+
+const USER: Pubkey = Pubkey::new_from_array([1; 32]);
+const MINT: Pubkey = Pubkey::new_from_array([2; 32]);
+const TASK_ID: u64 = 7;
+const SLOT_HASH: [u8; 32] = [3; 32];
+const NONCE: u64 = 42;
+
+/// (algorithm, `pow_hash`, `challenge_pow_hash` over `SLOT_HASH`)
+const VECTORS: [(u8, &str, &str); 3] = [
+    (
+        POW_HASH_KECCAK256,
+        "9fe9d7afdb54d7db9f33f3699abb57832c1ccec98e3cbbb75dfdfe698fcbf966",
+        "fd0476909f84d3d98a18ffafb59e14defdd7b881e24dd4e1a89c42842e96f079",
+    ),
+    (
+        POW_HASH_SHA256,
+        "f4571350c86cfdcd34b746bd9a15156485bcc1d04fd8c4c0b4751b3deb899620",
+        "82505390aac820f2ff6590b9930a6cfb7d8095bbaf2b8e6ac42de621d3f0e5e5",
+    ),
+    (
+        POW_HASH_BLAKE3,
+        "6ca7fd28a0d635bd50e743b8328d87c49e9098ce327bb6c5307f38a1957b6a82",
+        "a87ddd105c20e99dc688167814ac16492c1036ee37d8dbae244953f70e65e861",
+    ),
+];
+
+fn hex(hash: [u8; 32]) -> String {
+    hash.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[test]
+fn program_hashes_match_the_vectors() {
+    for (algorithm, plain, challenged) in VECTORS {
+        let hash = skill_stake::pow_hash(algorithm, &USER, &MINT, TASK_ID, NONCE);
+        assert_eq!(hex(hash), plain, "algorithm {algorithm}");
+        let hash =
+            skill_stake::challenge_pow_hash(algorithm, &USER, &MINT, TASK_ID, &SLOT_HASH, NONCE);
+        assert_eq!(hex(hash), challenged, "algorithm {algorithm}");
+    }
+}
+
+#[test]
+fn client_hashes_match_the_vectors() {
+    for (algorithm, plain, challenged) in VECTORS {
+        let hash = skill_stake_client::pow_hash(algorithm, &USER, &MINT, TASK_ID, NONCE);
+        assert_eq!(hex(hash), plain, "algorithm {algorithm}");
+        let hash = skill_stake_client::challenge_pow_hash(
+            algorithm, &USER, &MINT, TASK_ID, &SLOT_HASH, NONCE,
+        );
+        assert_eq!(hex(hash), challenged, "algorithm {algorithm}");
+    }
+}
+
+#[test]
+fn unknown_algorithms_fall_back_to_keccak256() {
+    let keccak = skill_stake::pow_hash(POW_HASH_KECCAK256, &USER, &MINT, TASK_ID, NONCE);
+    assert_eq!(
+        skill_stake::pow_hash(u8::MAX, &USER, &MINT, TASK_ID, NONCE),
+        keccak
+    );
+}
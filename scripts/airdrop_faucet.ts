@@ -112,7 +112,7 @@ async function main() {
   });
 
   const signature = await program.methods
-    .faucet(amountRaw)
+    .faucet(amountRaw, { none: {} })
     .accounts({
       admin: provider.wallet.publicKey,
       state: statePda,